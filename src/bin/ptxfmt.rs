@@ -0,0 +1,212 @@
+//! Rewrites PTX files into a canonical layout.
+//!
+//! ```text
+//! ptxfmt [--check] [--indent <tab|N>] [--operand-column <N>] [FILE]...
+//! ```
+//!
+//! Files are formatted in place; with no files, standard input is formatted to standard
+//! output. With `--check` nothing is written, and the exit code is 1 if any input is not
+//! already formatted. Errors exit with code 2 after listing every failing input, and no
+//! file is written unless all of them could be formatted.
+
+use std::{
+    env, fs,
+    io::{self, Read, Write},
+    process::ExitCode,
+};
+
+use ptx_parser::prelude::*;
+
+const USAGE: &str = "\
+usage: ptxfmt [--check] [--indent <tab|N>] [--operand-column <N>] [FILE]...
+
+Formats each FILE in place, or standard input to standard output if no FILE is given.
+
+options:
+    --check                 write nothing, and exit with 1 if any input is not formatted
+    --indent <tab|N>        indent with a tab or with N spaces [default: tab]
+    --operand-column <N>    start operands N columns after the instruction [default: 16]
+    -h, --help              print this message
+";
+
+const OPERAND_COLUMN: usize = 16;
+
+#[derive(Debug, PartialEq)]
+struct Options {
+    check: bool,
+    printer: Printer,
+    files: Vec<String>,
+}
+
+fn parse_args(args: impl IntoIterator<Item = String>) -> Result<Option<Options>, String> {
+    let mut args = args.into_iter();
+    let mut check = false;
+    let mut indent = Indent::Tab;
+    let mut operand_column = OPERAND_COLUMN;
+    let mut files = Vec::new();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(None),
+            "--check" => check = true,
+            "--indent" => {
+                indent = match args.next().as_deref() {
+                    Some("tab") => Indent::Tab,
+                    Some(width) => Indent::Spaces(width.parse().map_err(|_| format!("invalid indent `{width}`"))?),
+                    None => return Err("`--indent` needs a value".to_string()),
+                }
+            }
+            "--operand-column" => {
+                let column = args.next().ok_or("`--operand-column` needs a value")?;
+                operand_column = column.parse().map_err(|_| format!("invalid operand column `{column}`"))?;
+            }
+            option if option.starts_with('-') && option != "-" => return Err(format!("unknown option `{option}`")),
+            file => files.push(file.to_string()),
+        }
+    }
+    let printer = Printer::new().with_indent(indent).with_operand_column(operand_column);
+    Ok(Some(Options { check, printer, files }))
+}
+
+/// Formats `source`, refusing to if the result would not parse to the same module.
+fn format(printer: &Printer, source: &str) -> Result<String, String> {
+    let tree = SyntaxTree::try_from(source).map_err(|err| format!("cannot parse the preamble: {err}"))?;
+    let formatted = printer.format(&tree);
+    if let Ok(original) = PtxFile::try_from(source) {
+        if !PtxFile::try_from(formatted.as_str()).is_ok_and(|formatted| formatted.equivalent(&original)) {
+            return Err("formatting would change the meaning of the file".to_string());
+        }
+    }
+    Ok(formatted)
+}
+
+/// Reads and formats every file, giving the formatted text of each which changed, or
+/// every failure if any file could not be read or formatted.
+fn format_files(printer: &Printer, files: &[String]) -> Result<Vec<(String, String)>, Vec<String>> {
+    let mut changed = Vec::new();
+    let mut errors = Vec::new();
+    for path in files {
+        let formatted = fs::read_to_string(path)
+            .map_err(|err| err.to_string())
+            .and_then(|source| Ok((format(printer, &source)?, source)));
+        match formatted {
+            Ok((formatted, source)) if formatted != source => changed.push((path.clone(), formatted)),
+            Ok(_) => (),
+            Err(err) => errors.push(format!("{path}: {err}")),
+        }
+    }
+    match errors.is_empty() {
+        true => Ok(changed),
+        false => Err(errors),
+    }
+}
+
+/// Whether every input was already formatted or has been, or every failure. No file is
+/// written unless all of them could be formatted.
+fn run(options: &Options) -> Result<bool, Vec<String>> {
+    if options.files.is_empty() {
+        let mut source = String::new();
+        io::stdin().read_to_string(&mut source).map_err(|err| vec![format!("<stdin>: {err}")])?;
+        let formatted = format(&options.printer, &source).map_err(|err| vec![format!("<stdin>: {err}")])?;
+        if options.check {
+            return Ok(formatted == source);
+        }
+        io::stdout().write_all(formatted.as_bytes()).map_err(|err| vec![format!("<stdout>: {err}")])?;
+        return Ok(true);
+    }
+    let changed = format_files(&options.printer, &options.files)?;
+    if options.check {
+        for (path, _) in &changed {
+            println!("{path} is not formatted");
+        }
+        return Ok(changed.is_empty());
+    }
+    let errors = changed
+        .into_iter()
+        .filter_map(|(path, formatted)| fs::write(&path, formatted).err().map(|err| format!("{path}: {err}")))
+        .collect::<Vec<_>>();
+    match errors.is_empty() {
+        true => Ok(true),
+        false => Err(errors),
+    }
+}
+
+fn main() -> ExitCode {
+    let options = match parse_args(env::args().skip(1)) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            print!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Err(err) => {
+            eprint!("ptxfmt: {err}\n\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+    match run(&options) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(errors) => {
+            for err in errors {
+                eprintln!("ptxfmt: {err}");
+            }
+            ExitCode::from(2)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_ptxfmt {
+    use ptx_parser::prelude::{Indent, Printer};
+
+    use super::{format, format_files, parse_args, Options, OPERAND_COLUMN};
+
+    fn args(args: &[&str]) -> Result<Option<Options>, String> {
+        parse_args(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn options() {
+        assert_eq!(
+            args(&["--check", "a.ptx", "--indent", "4", "b.ptx"]),
+            Ok(Some(Options {
+                check: true,
+                printer: Printer::new().with_indent(Indent::Spaces(4)).with_operand_column(OPERAND_COLUMN),
+                files: vec!["a.ptx".to_string(), "b.ptx".to_string()],
+            }))
+        );
+        assert_eq!(args(&["--help"]), Ok(None));
+        assert!(args(&["--indent"]).is_err());
+        assert!(args(&["--operand-column", "wide"]).is_err());
+        assert!(args(&["--verbose"]).is_err());
+    }
+
+    #[test]
+    fn formats_once() {
+        let source = ".version 8.0\n.target sm_90\n.address_size 64\n.visible .entry k(.param .u64 k_param_0)\n{\n\tret;\n}\n";
+        let printer = Printer::new().with_operand_column(OPERAND_COLUMN);
+        let formatted = format(&printer, source).unwrap();
+        assert_ne!(formatted, source);
+        assert_eq!(format(&printer, &formatted).unwrap(), formatted);
+        assert!(format(&printer, "not ptx").is_err());
+    }
+
+    #[test]
+    fn failures_are_collected() {
+        let directory = std::env::temp_dir().join(format!("ptxfmt-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        let path = |name: &str| directory.join(name).to_str().unwrap().to_string();
+        let source = ".version 8.0\n.target sm_90\n.address_size 64\n.visible .entry k(.param .u64 k_param_0)\n{\n\tret;\n}\n";
+        std::fs::write(path("good.ptx"), source).unwrap();
+        std::fs::write(path("bad.ptx"), "not ptx").unwrap();
+        let printer = Printer::new().with_operand_column(OPERAND_COLUMN);
+
+        let files = [path("bad.ptx"), path("good.ptx"), path("missing.ptx")];
+        let errors = format_files(&printer, &files).unwrap_err();
+        assert_eq!(errors.len(), 2);
+        assert!(errors[0].starts_with(&files[0]) && errors[1].starts_with(&files[2]));
+
+        let changed = format_files(&printer, &files[1..2]).unwrap();
+        assert_eq!(changed, [(files[1].clone(), format(&printer, source).unwrap())]);
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+}
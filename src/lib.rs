@@ -1,2 +1,3 @@
 pub mod parser;
+pub mod prelude;
 mod ptx_files;
@@ -0,0 +1,445 @@
+//! The PTX ISA version and target architecture each instruction needs.
+
+use core::fmt;
+#[cfg(feature = "std")]
+use std::{format, vec::Vec};
+
+use crate::parser::function::{body::Operation, Directive};
+#[cfg(feature = "std")]
+use crate::parser::{
+    diagnostic::{Code, Diagnostic},
+    visit::{walk_module, Visitor},
+    PtxFile,
+};
+
+/// A target architecture such as `sm_80`, or `sm_90a` with its architecture-specific
+/// features, which no other architecture has.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SmTarget {
+    pub number: u32,
+    pub accelerated: bool,
+}
+
+impl SmTarget {
+    pub const fn new(number: u32) -> Self {
+        SmTarget { number, accelerated: false }
+    }
+
+    /// The architecture-specific variant, e.g. `sm_90a` for 90.
+    pub const fn accelerated(number: u32) -> Self {
+        SmTarget { number, accelerated: true }
+    }
+
+    /// Parses a target name such as `sm_52` or `sm_90a`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        let number = name.strip_prefix("sm_")?;
+        let (number, accelerated) = match number.strip_suffix('a') {
+            Some(number) => (number, true),
+            None => (number, false),
+        };
+        Some(SmTarget { number: number.parse().ok()?, accelerated })
+    }
+
+    /// Whether code for this target may use what `required` introduced.
+    pub fn supports(&self, required: SmTarget) -> bool {
+        match required.accelerated {
+            true => self.accelerated && self.number == required.number,
+            false => self.number >= required.number,
+        }
+    }
+}
+
+impl fmt::Display for SmTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "sm_{}{}", self.number, if self.accelerated { "a" } else { "" })
+    }
+}
+
+/// The `.version` and `.target` an instruction, directive or module needs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Requirement {
+    pub version: (u32, u32),
+    pub target: SmTarget,
+}
+
+impl Requirement {
+    /// What every module can use.
+    pub const BASELINE: Requirement = Requirement { version: (1, 0), target: SmTarget::new(10) };
+
+    const fn new(version: (u32, u32), target: u32) -> Self {
+        Requirement { version, target: SmTarget::new(target) }
+    }
+
+    /// The requirement of using both what `self` and what `other` need, or `None` if no
+    /// target has both, as for the architecture-specific features of `sm_90a` and `sm_100a`.
+    pub fn max(self, other: Requirement) -> Option<Requirement> {
+        let target = match (self.target.accelerated, other.target.accelerated) {
+            (false, false) => SmTarget::new(self.target.number.max(other.target.number)),
+            (true, _) if self.target.supports(other.target) => self.target,
+            (_, true) if other.target.supports(self.target) => other.target,
+            _ => return None,
+        };
+        Some(Requirement { version: self.version.max(other.version), target })
+    }
+
+    /// Whether a module with `.version` `version` and `.target` `target` meets the requirement.
+    pub fn is_met_by(&self, version: (u32, u32), target: SmTarget) -> bool {
+        version >= self.version && target.supports(self.target)
+    }
+}
+
+/// Formats as `PTX ISA 8.0 and sm_90a`.
+impl fmt::Display for Requirement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "PTX ISA {}.{} and {}", self.version.0, self.version.1, self.target)
+    }
+}
+
+/// An entry of the availability table: an opcode, or any opcode if `None`, together
+/// with modifiers which must all be present.
+struct Feature {
+    opcode: Option<&'static str>,
+    modifiers: &'static [&'static str],
+    requirement: Requirement,
+}
+
+const fn feature(opcode: &'static str, modifiers: &'static [&'static str], version: (u32, u32), target: u32) -> Feature {
+    Feature { opcode: Some(opcode), modifiers, requirement: Requirement::new(version, target) }
+}
+
+const fn modifier(modifier: &'static [&'static str], version: (u32, u32), target: u32) -> Feature {
+    Feature { opcode: None, modifiers: modifier, requirement: Requirement::new(version, target) }
+}
+
+const fn accelerated(opcode: &'static str, version: (u32, u32), target: u32) -> Feature {
+    Feature {
+        opcode: Some(opcode),
+        modifiers: &[],
+        requirement: Requirement { version, target: SmTarget::accelerated(target) },
+    }
+}
+
+/// When instructions and modifiers were introduced, following the PTX ISA reference.
+const FEATURES: &[Feature] = &[
+    feature("shfl", &["sync"], (6, 0), 30),
+    feature("vote", &["sync"], (6, 0), 30),
+    feature("bar", &["warp"], (6, 0), 30),
+    feature("barrier", &[], (6, 0), 30),
+    feature("match", &[], (6, 0), 70),
+    feature("activemask", &[], (6, 2), 30),
+    feature("redux", &[], (7, 0), 80),
+    feature("fns", &[], (6, 0), 30),
+    feature("brx", &[], (6, 0), 30),
+    feature("bmsk", &[], (7, 6), 70),
+    feature("szext", &[], (7, 6), 70),
+    feature("dp4a", &[], (5, 0), 61),
+    feature("dp2a", &[], (5, 0), 61),
+    feature("tanh", &[], (7, 0), 75),
+    feature("nanosleep", &[], (6, 3), 70),
+    feature("fence", &[], (6, 0), 70),
+    feature("fence", &["proxy"], (7, 5), 70),
+    feature("ld", &["relaxed"], (6, 0), 70),
+    feature("ld", &["acquire"], (6, 0), 70),
+    feature("st", &["relaxed"], (6, 0), 70),
+    feature("st", &["release"], (6, 0), 70),
+    feature("atom", &["sys"], (5, 0), 60),
+    feature("atom", &["cta"], (5, 0), 60),
+    feature("atom", &["add", "f64"], (5, 0), 60),
+    feature("atom", &["noftz"], (6, 3), 70),
+    feature("red", &["sys"], (5, 0), 60),
+    feature("red", &["cta"], (5, 0), 60),
+    feature("red", &["add", "f64"], (5, 0), 60),
+    feature("red", &["noftz"], (6, 3), 70),
+    feature("wmma", &[], (6, 0), 70),
+    feature("mma", &[], (6, 4), 70),
+    feature("mma", &["m16n8k8"], (6, 5), 75),
+    feature("mma", &["m16n8k16"], (7, 0), 80),
+    feature("ldmatrix", &[], (6, 5), 75),
+    feature("movmatrix", &[], (7, 8), 75),
+    feature("stmatrix", &[], (7, 8), 90),
+    feature("cp", &["async"], (7, 0), 80),
+    feature("cp", &["bulk"], (8, 0), 90),
+    feature("cp", &["tensor"], (8, 0), 90),
+    feature("mbarrier", &[], (7, 0), 80),
+    feature("mbarrier", &["try_wait"], (7, 8), 90),
+    feature("mbarrier", &["expect_tx"], (8, 0), 90),
+    feature("createpolicy", &[], (7, 4), 80),
+    feature("applypriority", &[], (7, 4), 80),
+    feature("discard", &[], (7, 4), 80),
+    feature("mapa", &[], (7, 8), 90),
+    feature("getctarank", &[], (7, 8), 90),
+    feature("griddepcontrol", &[], (7, 8), 90),
+    feature("elect", &[], (8, 0), 90),
+    feature("st", &["async"], (8, 1), 90),
+    feature("red", &["async"], (8, 1), 90),
+    feature("multimem", &[], (8, 1), 90),
+    accelerated("wgmma", (8, 0), 90),
+    accelerated("setmaxnreg", (8, 0), 90),
+    accelerated("tensormap", (8, 3), 90),
+    accelerated("tcgen05", (8, 6), 100),
+    modifier(&["cluster"], (7, 8), 90),
+    modifier(&["shared::cluster"], (7, 8), 90),
+    modifier(&["shared::cta"], (7, 8), 10),
+    modifier(&["bf16"], (7, 0), 80),
+    modifier(&["bf16x2"], (7, 0), 80),
+    modifier(&["tf32"], (7, 0), 80),
+    modifier(&["relu"], (7, 0), 80),
+    modifier(&["NaN"], (7, 0), 80),
+    modifier(&["xorsign"], (7, 2), 86),
+    modifier(&["e4m3"], (7, 8), 89),
+    modifier(&["e5m2"], (7, 8), 89),
+    modifier(&["e4m3x2"], (7, 8), 89),
+    modifier(&["e5m2x2"], (7, 8), 89),
+    modifier(&["L2::cache_hint"], (7, 4), 80),
+];
+
+/// What `operation` needs, or `Requirement::BASELINE` for instructions every target has.
+pub fn operation_requirement(operation: &Operation<'_>) -> Requirement {
+    FEATURES
+        .iter()
+        .filter(|feature| feature.opcode.is_none_or(|opcode| opcode == operation.opcode()))
+        .filter(|feature| {
+            feature
+                .modifiers
+                .iter()
+                .all(|required| operation.modifiers().any(|modifier| modifier == *required))
+        })
+        .try_fold(Requirement::BASELINE, |requirement, feature| requirement.max(feature.requirement))
+        .expect("the features of an instruction need at most one architecture-specific target")
+}
+
+/// What a performance tuning directive needs; the cluster directives are the newest.
+pub fn directive_requirement(directive: &Directive<'_>) -> Requirement {
+    match directive {
+        Directive::ExplicitCluster | Directive::Reqnctapercluster(_) | Directive::Maxclusterrank(_) => {
+            Requirement::new((7, 8), 90)
+        }
+        Directive::Maxnreg(_) => Requirement::new((1, 3), 10),
+        Directive::Minnctapersm(_) => Requirement::new((2, 0), 10),
+        _ => Requirement::new((1, 3), 10),
+    }
+}
+
+/// The instructions and directives of a module, with their requirements.
+#[cfg(feature = "std")]
+#[derive(Default)]
+struct Requirements<'a> {
+    uses: Vec<(&'a str, Requirement)>,
+}
+
+#[cfg(feature = "std")]
+impl<'a> Visitor<'a> for Requirements<'a> {
+    fn visit_function(&mut self, function: &crate::parser::function::Function<'a>) {
+        if let Some(directives) = &function.signature.directives {
+            for directive in directives.iter() {
+                self.uses.push((directives.raw_string(), directive_requirement(&directive)));
+            }
+        }
+        crate::parser::visit::walk_function(self, function);
+    }
+
+    fn visit_operation(&mut self, operation: &Operation<'a>) {
+        if !operation.operation().starts_with('.') {
+            self.uses.push((operation.operation(), operation_requirement(operation)));
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+fn requirements<'a>(file: &PtxFile<'a>) -> Vec<(&'a str, Requirement)> {
+    let mut requirements = Requirements::default();
+    walk_module(&mut requirements, file);
+    requirements.uses
+}
+
+/// The oldest `.version` and `.target` which allow everything `file` uses, or `None` if
+/// it uses the architecture-specific features of two targets, which no module can target.
+///
+/// ```
+/// use ptx_parser::prelude::*;
+///
+/// let ptx: PtxFile = "
+/// .version 8.0
+/// .target sm_90a
+/// .address_size 64
+///
+/// .visible .entry kernel(
+///     .param .u64 kernel_param_0
+/// )
+/// {
+///     .reg .b32 %r<3>;
+///     shfl.sync.bfly.b32 %r1, %r2, 1, 31, -1;
+///     wgmma.fence.sync.aligned;
+///     ret;
+/// }
+/// "
+/// .try_into()
+/// .unwrap();
+///
+/// let requirement = ptx.requirement().unwrap();
+/// assert_eq!(requirement.version, (8, 0));
+/// assert_eq!(requirement.target.to_string(), "sm_90a");
+/// ```
+#[cfg(feature = "std")]
+pub fn requirement(file: &PtxFile<'_>) -> Option<Requirement> {
+    requirements(file)
+        .into_iter()
+        .try_fold(Requirement::BASELINE, |requirement, (_, used)| requirement.max(used))
+}
+
+/// Flags every instruction and directive the `.version` and `.target` of `file` do not allow.
+#[cfg(feature = "std")]
+pub fn check<'a>(file: &PtxFile<'a>) -> Vec<Diagnostic<'a>> {
+    let preamble = file.preamble();
+    let (Some(version), Some(target)) = (
+        preamble.version().number(),
+        SmTarget::from_name(preamble.target().target()),
+    ) else {
+        return Vec::new();
+    };
+    requirements(file)
+        .into_iter()
+        .filter(|(_, requirement)| !requirement.is_met_by(version, target))
+        .map(|(text, requirement)| {
+            let message = format!(
+                "`{text}` needs {requirement}, but the module targets PTX ISA {}.{} and {target}",
+                version.0, version.1,
+            );
+            Diagnostic::new(Code::Unavailable, message, text)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test_availability {
+    use crate::parser::function::body::Operation;
+
+    use super::{operation_requirement, Requirement, SmTarget};
+
+    fn requirement(operation: &str) -> Requirement {
+        operation_requirement(&Operation::new(operation, ""))
+    }
+
+    #[test]
+    fn targets() {
+        let sm_90a = SmTarget::from_name("sm_90a").unwrap();
+        assert_eq!(sm_90a, SmTarget::accelerated(90));
+        assert_eq!(SmTarget::from_name("sm_52"), Some(SmTarget::new(52)));
+        assert_eq!(SmTarget::from_name("compute_52"), None);
+        assert!(sm_90a.supports(SmTarget::new(80)));
+        assert!(!SmTarget::new(90).supports(sm_90a));
+        assert!(!SmTarget::accelerated(100).supports(sm_90a));
+    }
+
+    #[test]
+    fn instructions() {
+        assert_eq!(requirement("add.s32"), Requirement::BASELINE);
+        assert_eq!(requirement("shfl.sync.bfly.b32"), Requirement::new((6, 0), 30));
+        assert_eq!(requirement("mma.sync.aligned.m16n8k16.row.col.f32.bf16.bf16.f32"), Requirement::new((7, 0), 80));
+        assert_eq!(requirement("atom.global.add.f64"), Requirement::new((5, 0), 60));
+        assert_eq!(requirement("atom.global.add.f32"), Requirement::BASELINE);
+        assert_eq!(requirement("ld.shared::cluster.u32"), Requirement::new((7, 8), 90));
+        let wgmma = requirement("wgmma.fence.sync.aligned");
+        assert_eq!(wgmma.to_string(), "PTX ISA 8.0 and sm_90a");
+        assert!(wgmma.is_met_by((8, 0), SmTarget::accelerated(90)));
+        assert!(!wgmma.is_met_by((8, 0), SmTarget::new(90)));
+        assert!(!wgmma.is_met_by((7, 8), SmTarget::accelerated(90)));
+    }
+
+    #[test]
+    fn combined() {
+        let sm_90a = Requirement { version: (8, 0), target: SmTarget::accelerated(90) };
+        let sm_100a = Requirement { version: (8, 6), target: SmTarget::accelerated(100) };
+        assert_eq!(Requirement::new((6, 0), 30).max(Requirement::new((5, 0), 80)), Some(Requirement::new((6, 0), 80)));
+        assert_eq!(sm_90a.max(Requirement::new((7, 8), 90)), Some(sm_90a));
+        assert_eq!(Requirement::new((7, 0), 80).max(sm_90a), Some(sm_90a));
+        assert_eq!(sm_90a.max(sm_90a), Some(sm_90a));
+        assert_eq!(sm_90a.max(sm_100a), None);
+        assert_eq!(sm_100a.max(sm_90a), None);
+        assert_eq!(sm_90a.max(Requirement::new((7, 0), 100)), None);
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg(test)]
+mod test_check_availability {
+    use std::{string::ToString, vec::Vec};
+
+    use crate::{
+        parser::{diagnostic::Code, PtxFile},
+        ptx_files,
+    };
+
+    use super::{Requirement, SmTarget};
+
+    const SOURCE: &str = "
+.version 5.0
+.target sm_80
+.address_size 64
+
+.visible .entry kernel(
+    .param .u64 kernel_param_0
+)
+.reqnctapercluster 2, 1, 1
+{
+    .reg .pred %p<2>;
+    .reg .b32 %r<3>;
+    shfl.sync.bfly.b32 %r1, %r2, 1, 31, -1;
+    @%p1 wgmma.fence.sync.aligned;
+    ret;
+}
+";
+
+    #[test]
+    fn flags_unavailable_instructions() {
+        let file = PtxFile::try_from(SOURCE).unwrap();
+        let diagnostics = file.check_availability();
+        assert!(diagnostics.iter().all(|diagnostic| diagnostic.code() == Code::Unavailable));
+        let texts = diagnostics.iter().map(|diagnostic| diagnostic.text()).collect::<Vec<_>>();
+        assert_eq!(texts, [".reqnctapercluster 2, 1, 1", "shfl.sync.bfly.b32", "wgmma.fence.sync.aligned"]);
+        assert_eq!(
+            diagnostics[1].to_string(),
+            "error[E0008]: `shfl.sync.bfly.b32` needs PTX ISA 6.0 and sm_30, but the module targets PTX ISA 5.0 and sm_80"
+        );
+        assert_eq!(
+            file.requirement(),
+            Some(Requirement { version: (8, 0), target: SmTarget::accelerated(90) })
+        );
+    }
+
+    #[test]
+    fn conflicting_targets() {
+        let file = PtxFile::try_from(
+            "
+.version 8.6
+.target sm_100a
+.address_size 64
+
+.visible .entry kernel(
+    .param .u64 kernel_param_0
+)
+{
+    wgmma.fence.sync.aligned;
+    tcgen05.fence::before_thread_sync;
+    ret;
+}
+",
+        )
+        .unwrap();
+        assert_eq!(file.requirement(), None);
+        let texts = file.check_availability().iter().map(|diagnostic| diagnostic.text()).collect::<Vec<_>>();
+        assert_eq!(texts, ["wgmma.fence.sync.aligned"]);
+    }
+
+    #[test]
+    fn sample_files_are_available() {
+        for source in ptx_files::ALL {
+            let file = PtxFile::try_from(source).unwrap();
+            assert_eq!(file.check_availability(), [], "{}", &source[..source.len().min(200)]);
+        }
+        let tensor = PtxFile::try_from(ptx_files::tensor::_PTX).unwrap();
+        assert_eq!(tensor.requirement().unwrap().target, SmTarget::accelerated(90));
+        let kernel = PtxFile::try_from(ptx_files::kernel::_PTX).unwrap();
+        assert!(kernel.requirement().unwrap().version <= (6, 0));
+    }
+}
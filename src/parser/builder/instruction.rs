@@ -0,0 +1,395 @@
+use core::fmt;
+
+use crate::parser::{
+    function::body::Operation,
+    instruction::types::{ScalarType, StateSpace},
+    printer::Print,
+};
+
+use super::{Label, Param, Reg};
+
+/// A dimension of a special register such as `%tid.x`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Dim {
+    X,
+    Y,
+    Z,
+}
+
+/// The special registers a builder can read with `mov`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum SpecialRegister {
+    Tid(Dim),
+    Ntid(Dim),
+    Ctaid(Dim),
+    Nctaid(Dim),
+    LaneId,
+    WarpId,
+}
+
+impl fmt::Display for SpecialRegister {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (name, dim) = match self {
+            SpecialRegister::Tid(dim) => ("tid", Some(dim)),
+            SpecialRegister::Ntid(dim) => ("ntid", Some(dim)),
+            SpecialRegister::Ctaid(dim) => ("ctaid", Some(dim)),
+            SpecialRegister::Nctaid(dim) => ("nctaid", Some(dim)),
+            SpecialRegister::LaneId => ("laneid", None),
+            SpecialRegister::WarpId => ("warpid", None),
+        };
+        write!(f, "%{name}")?;
+        match dim {
+            Some(Dim::X) => f.write_str(".x"),
+            Some(Dim::Y) => f.write_str(".y"),
+            Some(Dim::Z) => f.write_str(".z"),
+            None => Ok(()),
+        }
+    }
+}
+
+/// A source operand.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[non_exhaustive]
+pub enum Value {
+    Reg(Reg),
+    Integer(i64),
+    /// Written in PTX's exact `0f` hexadecimal form.
+    F32(f32),
+    /// Written in PTX's exact `0d` hexadecimal form.
+    F64(f64),
+    Special(SpecialRegister),
+}
+
+impl From<Reg> for Value {
+    fn from(reg: Reg) -> Self {
+        Value::Reg(reg)
+    }
+}
+
+impl From<i64> for Value {
+    fn from(value: i64) -> Self {
+        Value::Integer(value)
+    }
+}
+
+impl From<i32> for Value {
+    fn from(value: i32) -> Self {
+        Value::Integer(value.into())
+    }
+}
+
+impl From<u32> for Value {
+    fn from(value: u32) -> Self {
+        Value::Integer(value.into())
+    }
+}
+
+impl From<f32> for Value {
+    fn from(value: f32) -> Self {
+        Value::F32(value)
+    }
+}
+
+impl From<f64> for Value {
+    fn from(value: f64) -> Self {
+        Value::F64(value)
+    }
+}
+
+impl From<SpecialRegister> for Value {
+    fn from(register: SpecialRegister) -> Self {
+        Value::Special(register)
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Reg(reg) => write!(f, "{reg}"),
+            Value::Integer(value) => write!(f, "{value}"),
+            Value::F32(value) => write!(f, "0f{:08X}", value.to_bits()),
+            Value::F64(value) => write!(f, "0d{:016X}", value.to_bits()),
+            Value::Special(register) => write!(f, "{register}"),
+        }
+    }
+}
+
+/// What an address is relative to.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Base {
+    Reg(Reg),
+    Param(Param),
+}
+
+/// A memory operand, `[base+offset]`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Address {
+    base: Base,
+    offset: i64,
+}
+
+impl Address {
+    pub fn offset(mut self, offset: i64) -> Self {
+        self.offset += offset;
+        self
+    }
+
+    pub fn base(&self) -> &Base {
+        &self.base
+    }
+}
+
+impl From<Reg> for Address {
+    fn from(reg: Reg) -> Self {
+        Address { base: Base::Reg(reg), offset: 0 }
+    }
+}
+
+impl From<&Param> for Address {
+    fn from(param: &Param) -> Self {
+        Address { base: Base::Param(param.clone()), offset: 0 }
+    }
+}
+
+impl fmt::Display for Address {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("[")?;
+        match &self.base {
+            Base::Reg(reg) => write!(f, "{reg}")?,
+            Base::Param(param) => f.write_str(param.name())?,
+        }
+        match self.offset {
+            0 => f.write_str("]"),
+            offset if offset < 0 => write!(f, "{offset}]"),
+            offset => write!(f, "+{offset}]"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BinaryOp {
+    Add,
+    Sub,
+    /// The low half of the product, or the product of floats.
+    Mul,
+    /// The full product, in a register twice as wide as the operands.
+    MulWide,
+    Div,
+    Rem,
+    Min,
+    Max,
+    And,
+    Or,
+    Xor,
+    Shl,
+    Shr,
+}
+
+impl BinaryOp {
+    /// Whether PTX defines the operation on `ty`: `rem` and `mul.wide` only on integers,
+    /// the bitwise operations only on untyped or integer registers, and none of them on
+    /// 8-bit types.
+    pub fn supports(self, ty: ScalarType) -> bool {
+        let integer = matches!(
+            ty,
+            ScalarType::S16 | ScalarType::S32 | ScalarType::S64 | ScalarType::U16 | ScalarType::U32 | ScalarType::U64
+        );
+        let float = matches!(
+            ty,
+            ScalarType::F16 | ScalarType::F16x2 | ScalarType::Bf16 | ScalarType::Bf16x2 | ScalarType::F32 | ScalarType::F64
+        );
+        let bits = matches!(ty, ScalarType::B16 | ScalarType::B32 | ScalarType::B64);
+        match self {
+            BinaryOp::Add | BinaryOp::Sub | BinaryOp::Mul | BinaryOp::Min | BinaryOp::Max => integer || float,
+            BinaryOp::MulWide => matches!(ty, ScalarType::S16 | ScalarType::S32 | ScalarType::U16 | ScalarType::U32),
+            BinaryOp::Div => integer || matches!(ty, ScalarType::F32 | ScalarType::F64),
+            BinaryOp::Rem => integer,
+            BinaryOp::And | BinaryOp::Or | BinaryOp::Xor => integer || bits || ty == ScalarType::Pred,
+            BinaryOp::Shl | BinaryOp::Shr => integer || bits,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Comparison {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+/// An instruction made by a `BlockBuilder`, with its destination registers already allocated.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum Instruction {
+    Mov { ty: ScalarType, dst: Reg, src: Value },
+    Ld { space: StateSpace, ty: ScalarType, dst: Reg, address: Address },
+    St { space: StateSpace, ty: ScalarType, address: Address, src: Value },
+    /// `cvta.to.<space>.u64`, converting a generic address to one in `space`.
+    CvtaTo { space: StateSpace, dst: Reg, src: Value },
+    Cvt { to: ScalarType, from: ScalarType, dst: Reg, src: Value },
+    Binary { op: BinaryOp, ty: ScalarType, dst: Reg, a: Value, b: Value },
+    /// `mad.lo` for integers and `fma.rn` for floats.
+    Mad { ty: ScalarType, dst: Reg, a: Value, b: Value, c: Value },
+    Setp { comparison: Comparison, ty: ScalarType, dst: Reg, a: Value, b: Value },
+    Selp { ty: ScalarType, dst: Reg, a: Value, b: Value, predicate: Reg },
+    /// A branch, taken when `guard` is `None` or its predicate equals its flag.
+    Bra { target: Label, guard: Option<(Reg, bool)> },
+    Ret,
+}
+
+impl Instruction {
+    /// The full instruction name with its modifiers, e.g. `ld.param.u64`.
+    pub fn name(&self) -> String {
+        match self {
+            Instruction::Mov { ty, .. } => format!("mov.{}", ty.modifier()),
+            Instruction::Ld { space, ty, .. } => format!("ld{}.{}", space_modifier(*space), ty.modifier()),
+            Instruction::St { space, ty, .. } => format!("st{}.{}", space_modifier(*space), ty.modifier()),
+            Instruction::CvtaTo { space, .. } => format!("cvta.to{}.u64", space_modifier(*space)),
+            Instruction::Cvt { to, from, .. } => format!("cvt{}.{}.{}", rounding(*to, *from), to.modifier(), from.modifier()),
+            Instruction::Binary { op, ty, .. } => binary_name(*op, *ty),
+            Instruction::Mad { ty, .. } if ty.is_float() => format!("fma.rn.{}", ty.modifier()),
+            Instruction::Mad { ty, .. } => format!("mad.lo.{}", ty.modifier()),
+            Instruction::Setp { comparison, ty, .. } => format!("setp.{}.{}", comparison_modifier(*comparison), ty.modifier()),
+            Instruction::Selp { ty, .. } => format!("selp.{}", ty.modifier()),
+            Instruction::Bra { guard: None, .. } => "bra.uni".into(),
+            Instruction::Bra { .. } => "bra".into(),
+            Instruction::Ret => "ret".into(),
+        }
+    }
+
+    /// The operands, separated by commas.
+    pub fn operands(&self) -> String {
+        match self {
+            Instruction::Mov { dst, src, .. } | Instruction::CvtaTo { dst, src, .. } | Instruction::Cvt { dst, src, .. } => {
+                format!("{dst}, {src}")
+            }
+            Instruction::Ld { dst, address, .. } => format!("{dst}, {address}"),
+            Instruction::St { address, src, .. } => format!("{address}, {src}"),
+            Instruction::Binary { dst, a, b, .. } | Instruction::Setp { dst, a, b, .. } => format!("{dst}, {a}, {b}"),
+            Instruction::Mad { dst, a, b, c, .. } => format!("{dst}, {a}, {b}, {c}"),
+            Instruction::Selp { dst, a, b, predicate, .. } => format!("{dst}, {a}, {b}, {predicate}"),
+            Instruction::Bra { target, .. } => format!("{target}"),
+            Instruction::Ret => String::new(),
+        }
+    }
+
+    /// Every register the instruction reads or writes.
+    pub fn registers(&self) -> Vec<Reg> {
+        let mut registers = Vec::new();
+        let mut value = |value: &Value| {
+            if let Value::Reg(reg) = value {
+                registers.push(*reg);
+            }
+        };
+        let (dst, values, address, predicate) = match self {
+            Instruction::Mov { dst, src, .. } | Instruction::CvtaTo { dst, src, .. } | Instruction::Cvt { dst, src, .. } => {
+                (Some(dst), Vec::from([src]), None, None)
+            }
+            Instruction::Ld { dst, address, .. } => (Some(dst), Vec::new(), Some(address), None),
+            Instruction::St { address, src, .. } => (None, Vec::from([src]), Some(address), None),
+            Instruction::Binary { dst, a, b, .. } | Instruction::Setp { dst, a, b, .. } => {
+                (Some(dst), Vec::from([a, b]), None, None)
+            }
+            Instruction::Mad { dst, a, b, c, .. } => (Some(dst), Vec::from([a, b, c]), None, None),
+            Instruction::Selp { dst, a, b, predicate, .. } => (Some(dst), Vec::from([a, b]), None, Some(predicate)),
+            Instruction::Bra { guard, .. } => (None, Vec::new(), None, guard.as_ref().map(|(reg, _)| reg)),
+            Instruction::Ret => (None, Vec::new(), None, None),
+        };
+        values.into_iter().for_each(&mut value);
+        if let Some(Address { base: Base::Reg(reg), .. }) = address {
+            value(&Value::Reg(*reg));
+        }
+        registers.extend(dst.into_iter().chain(predicate).copied());
+        registers
+    }
+}
+
+/// Writes the instruction through the default printer, with the predicate of a guarded branch.
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Instruction::Bra { guard: Some((predicate, flag)), .. } = self {
+            write!(f, "@{}{predicate} ", if *flag { "" } else { "!" })?;
+        }
+        let (name, operands) = (self.name(), self.operands());
+        Operation::new(&name, &operands).print(&Default::default(), f)
+    }
+}
+
+fn space_modifier(space: StateSpace) -> String {
+    space.modifier().map(|modifier| format!(".{modifier}")).unwrap_or_default()
+}
+
+/// The rounding modifier `cvt` requires for conversions which may lose precision.
+fn rounding(to: ScalarType, from: ScalarType) -> &'static str {
+    match (to.is_float(), from.is_float()) {
+        (true, false) => ".rn",
+        (false, true) => ".rzi",
+        (true, true) if to.bits() < from.bits() => ".rn",
+        _ => "",
+    }
+}
+
+fn binary_name(op: BinaryOp, ty: ScalarType) -> String {
+    let name = match op {
+        BinaryOp::Add => "add",
+        BinaryOp::Sub => "sub",
+        BinaryOp::Mul if ty.is_float() => "mul",
+        BinaryOp::Mul => "mul.lo",
+        BinaryOp::MulWide => "mul.wide",
+        BinaryOp::Div if ty.is_float() => "div.rn",
+        BinaryOp::Div => "div",
+        BinaryOp::Rem => "rem",
+        BinaryOp::Min => "min",
+        BinaryOp::Max => "max",
+        BinaryOp::And => "and",
+        BinaryOp::Or => "or",
+        BinaryOp::Xor => "xor",
+        BinaryOp::Shl => "shl",
+        BinaryOp::Shr => "shr",
+    };
+    let ty = match op {
+        BinaryOp::And | BinaryOp::Or | BinaryOp::Xor | BinaryOp::Shl => untyped(ty),
+        _ => ty,
+    };
+    format!("{name}.{}", ty.modifier())
+}
+
+/// The `.b` type of the same width, which bitwise instructions require.
+pub(super) fn untyped(ty: ScalarType) -> ScalarType {
+    match ty.bits() {
+        1 => ScalarType::Pred,
+        8 => ScalarType::B8,
+        16 => ScalarType::B16,
+        32 => ScalarType::B32,
+        64 => ScalarType::B64,
+        _ => ScalarType::B128,
+    }
+}
+
+/// The type of the result of `mul.wide` on `ty`.
+pub(super) fn widened(ty: ScalarType) -> ScalarType {
+    match ty {
+        ScalarType::S16 => ScalarType::S32,
+        ScalarType::U16 => ScalarType::U32,
+        ScalarType::S32 => ScalarType::S64,
+        ScalarType::U32 => ScalarType::U64,
+        ScalarType::B16 => ScalarType::B32,
+        ScalarType::B32 => ScalarType::B64,
+        ty => ty,
+    }
+}
+
+fn comparison_modifier(comparison: Comparison) -> &'static str {
+    match comparison {
+        Comparison::Eq => "eq",
+        Comparison::Ne => "ne",
+        Comparison::Lt => "lt",
+        Comparison::Le => "le",
+        Comparison::Gt => "gt",
+        Comparison::Ge => "ge",
+    }
+}
@@ -0,0 +1,608 @@
+use core::fmt::{self, Write};
+use std::{collections::HashSet, string::String, vec::Vec};
+
+use crate::parser::{
+    instruction::types::{ScalarType, StateSpace},
+    printer::Printer,
+    ptx_file::OwnedPtxFile,
+    PtxFile,
+};
+
+use self::instruction::{widened, Address, Base, BinaryOp, Comparison, Instruction, Value};
+
+pub mod instruction;
+
+/// The kinds of register a function declares, each with its own `.reg` range.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum RegisterClass {
+    Pred,
+    B16,
+    B32,
+    B64,
+    B128,
+    F32,
+    F64,
+}
+
+impl RegisterClass {
+    const ALL: [RegisterClass; 7] = [
+        RegisterClass::Pred,
+        RegisterClass::B16,
+        RegisterClass::B32,
+        RegisterClass::B64,
+        RegisterClass::B128,
+        RegisterClass::F32,
+        RegisterClass::F64,
+    ];
+
+    fn of(ty: ScalarType) -> Self {
+        match (ty, ty.bits()) {
+            (ScalarType::F32, _) => RegisterClass::F32,
+            (ScalarType::F64, _) => RegisterClass::F64,
+            (_, 1) => RegisterClass::Pred,
+            (_, 8 | 16) => RegisterClass::B16,
+            (_, 32) => RegisterClass::B32,
+            (_, 64) => RegisterClass::B64,
+            _ => RegisterClass::B128,
+        }
+    }
+
+    /// The register name prefix nvcc uses for the class.
+    fn prefix(self) -> &'static str {
+        match self {
+            RegisterClass::Pred => "p",
+            RegisterClass::B16 => "rs",
+            RegisterClass::B32 => "r",
+            RegisterClass::B64 => "rd",
+            RegisterClass::B128 => "rq",
+            RegisterClass::F32 => "f",
+            RegisterClass::F64 => "fd",
+        }
+    }
+
+    fn ty(self) -> ScalarType {
+        match self {
+            RegisterClass::Pred => ScalarType::Pred,
+            RegisterClass::B16 => ScalarType::B16,
+            RegisterClass::B32 => ScalarType::B32,
+            RegisterClass::B64 => ScalarType::B64,
+            RegisterClass::B128 => ScalarType::B128,
+            RegisterClass::F32 => ScalarType::F32,
+            RegisterClass::F64 => ScalarType::F64,
+        }
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// A register allocated by a `FunctionBuilder`, such as `%rd3`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Reg {
+    function: u32,
+    class: RegisterClass,
+    index: u32,
+}
+
+impl Reg {
+    /// The type the register is declared with, e.g. `.b32` for `%r1`.
+    pub fn ty(&self) -> ScalarType {
+        self.class.ty()
+    }
+}
+
+impl fmt::Display for Reg {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "%{}{}", self.class.prefix(), self.index)
+    }
+}
+
+/// A label created by `FunctionBuilder::label`, unique within its module.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Label {
+    function: u32,
+    index: u32,
+}
+
+impl fmt::Display for Label {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "$L__BB{}_{}", self.function, self.index)
+    }
+}
+
+/// A kernel or function parameter, read with `ld.param`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Param {
+    function: u32,
+    name: String,
+    ty: ScalarType,
+}
+
+impl Param {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn ty(&self) -> ScalarType {
+        self.ty
+    }
+}
+
+/// Why a function cannot be added to a module.
+#[derive(Debug, PartialEq)]
+#[non_exhaustive]
+pub enum BuildError {
+    DuplicateFunction(String),
+    /// A branch targets a label which was never placed with `FunctionBuilder::block`.
+    UnplacedLabel(Label),
+    DuplicateLabel(Label),
+    /// A label, register or parameter of another function was used.
+    ForeignLabel(Label),
+    ForeignRegister(Reg),
+    ForeignParam(String),
+    /// A binary operation PTX does not define on the type, such as `rem` on floats.
+    UnsupportedType(BinaryOp, ScalarType),
+    /// The generated text failed to parse, which is a bug in the builder.
+    Parse(nom::Err<nom::error::Error<String>>),
+}
+
+/// Builds a module function by function, printing it through the pretty printer.
+///
+/// ```
+/// use ptx_parser::parser::{
+///     builder::{instruction::BinaryOp, ModuleBuilder},
+///     instruction::types::{ScalarType, StateSpace},
+/// };
+///
+/// let mut module = ModuleBuilder::new((8, 0), "sm_90");
+/// let mut kernel = module.kernel("increment");
+/// let data = kernel.param(ScalarType::U64);
+/// let mut body = kernel.body();
+/// let pointer = body.ld_param(&data);
+/// let pointer = body.cvta_to(StateSpace::Global, pointer);
+/// let value = body.ld(StateSpace::Global, ScalarType::U32, pointer);
+/// let value = body.binary(BinaryOp::Add, ScalarType::U32, value, 1);
+/// body.st(StateSpace::Global, ScalarType::U32, pointer, value);
+/// body.ret();
+/// module.push(kernel).unwrap();
+///
+/// let ptx = module.build().unwrap();
+/// assert_eq!(ptx.file().functions()[0].signature.name, "increment");
+/// assert!(ptx.source().contains("add.u32 %r2, %r1, 1;"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct ModuleBuilder {
+    version: (u32, u32),
+    target: String,
+    functions: Vec<FunctionBuilder>,
+    next_function: u32,
+}
+
+impl ModuleBuilder {
+    /// A 64-bit module for `.version major.minor` and `.target target`.
+    pub fn new(version: (u32, u32), target: &str) -> Self {
+        ModuleBuilder { version, target: target.into(), functions: Vec::new(), next_function: 0 }
+    }
+
+    /// Starts a `.visible .entry` kernel.
+    pub fn kernel(&mut self, name: &str) -> FunctionBuilder {
+        self.start(name, true)
+    }
+
+    /// Starts a `.func` device function.
+    pub fn function(&mut self, name: &str) -> FunctionBuilder {
+        self.start(name, false)
+    }
+
+    fn start(&mut self, name: &str, entry: bool) -> FunctionBuilder {
+        let id = self.next_function;
+        self.next_function += 1;
+        FunctionBuilder {
+            id,
+            name: name.into(),
+            entry,
+            params: Vec::new(),
+            registers: [0; RegisterClass::ALL.len()],
+            labels: 0,
+            lines: Vec::new(),
+        }
+    }
+
+    /// Adds a finished function, checking that its labels and registers are its own
+    /// and that every branch target has been placed.
+    pub fn push(&mut self, function: FunctionBuilder) -> Result<(), BuildError> {
+        if self.functions.iter().any(|other| other.name == function.name) {
+            return Err(BuildError::DuplicateFunction(function.name));
+        }
+        function.check()?;
+        self.functions.push(function);
+        Ok(())
+    }
+
+    /// The module laid out by `printer`.
+    pub fn print(&self, printer: &Printer) -> Result<String, BuildError> {
+        let source = self.source();
+        let file = PtxFile::try_from(source.as_str()).map_err(|err| BuildError::Parse(err.to_owned()))?;
+        Ok(printer.print(&file))
+    }
+
+    /// Parses the module, as printed by the default printer.
+    pub fn build(&self) -> Result<OwnedPtxFile, BuildError> {
+        OwnedPtxFile::try_from(self.print(&Printer::default())?).map_err(BuildError::Parse)
+    }
+
+    fn source(&self) -> String {
+        let (major, minor) = self.version;
+        let mut source = format!(".version {major}.{minor}\n.target {}\n.address_size 64\n", self.target);
+        for function in &self.functions {
+            source.push('\n');
+            // Writing to a `String` cannot fail.
+            let _ = function.write(&mut source);
+        }
+        source
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Line {
+    Label(Label),
+    Instruction(Instruction),
+}
+
+/// Builds a single kernel or function, allocating its registers and labels.
+#[derive(Debug, Clone)]
+pub struct FunctionBuilder {
+    id: u32,
+    name: String,
+    entry: bool,
+    params: Vec<Param>,
+    /// The number of registers allocated in each `RegisterClass`.
+    registers: [u32; RegisterClass::ALL.len()],
+    labels: u32,
+    lines: Vec<Line>,
+}
+
+impl FunctionBuilder {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Adds a parameter named `<function>_param_<N>`, as nvcc does.
+    pub fn param(&mut self, ty: ScalarType) -> Param {
+        let name = format!("{}_param_{}", self.name, self.params.len());
+        let param = Param { function: self.id, name, ty };
+        self.params.push(param.clone());
+        param
+    }
+
+    /// Allocates a fresh register able to hold a `ty`.
+    pub fn register(&mut self, ty: ScalarType) -> Reg {
+        let class = RegisterClass::of(ty);
+        let count = &mut self.registers[class.index()];
+        *count += 1;
+        Reg { function: self.id, class, index: *count }
+    }
+
+    /// Creates a label to branch to, placed later by `block`.
+    pub fn label(&mut self) -> Label {
+        self.labels += 1;
+        Label { function: self.id, index: self.labels }
+    }
+
+    /// Continues the current block.
+    pub fn body(&mut self) -> BlockBuilder<'_> {
+        BlockBuilder { function: self }
+    }
+
+    /// Places `label` and starts a block after it.
+    pub fn block(&mut self, label: Label) -> BlockBuilder<'_> {
+        self.lines.push(Line::Label(label));
+        BlockBuilder { function: self }
+    }
+
+    fn check(&self) -> Result<(), BuildError> {
+        let mut placed = HashSet::new();
+        let mut targets = Vec::new();
+        for line in &self.lines {
+            let instruction = match line {
+                Line::Label(label) => {
+                    self.check_label(*label)?;
+                    if !placed.insert(*label) {
+                        return Err(BuildError::DuplicateLabel(*label));
+                    }
+                    continue;
+                }
+                Line::Instruction(instruction) => instruction,
+            };
+            if let Some(reg) = instruction.registers().into_iter().find(|reg| reg.function != self.id) {
+                return Err(BuildError::ForeignRegister(reg));
+            }
+            match instruction {
+                Instruction::Binary { op, ty, .. } if !op.supports(*ty) => {
+                    return Err(BuildError::UnsupportedType(*op, *ty))
+                }
+                Instruction::Bra { target, .. } => {
+                    self.check_label(*target)?;
+                    targets.push(*target);
+                }
+                Instruction::Ld { address, .. } | Instruction::St { address, .. } => match address.base() {
+                    Base::Param(param) if param.function != self.id => {
+                        return Err(BuildError::ForeignParam(param.name.clone()))
+                    }
+                    _ => (),
+                },
+                _ => (),
+            }
+        }
+        match targets.into_iter().find(|target| !placed.contains(target)) {
+            Some(target) => Err(BuildError::UnplacedLabel(target)),
+            None => Ok(()),
+        }
+    }
+
+    fn check_label(&self, label: Label) -> Result<(), BuildError> {
+        match label.function == self.id {
+            true => Ok(()),
+            false => Err(BuildError::ForeignLabel(label)),
+        }
+    }
+
+    fn write(&self, out: &mut String) -> fmt::Result {
+        out.write_str(if self.entry { ".visible .entry " } else { ".func " })?;
+        out.write_str(&self.name)?;
+        for (index, param) in self.params.iter().enumerate() {
+            out.write_str(if index == 0 { "(\n" } else { ",\n" })?;
+            write!(out, "\t.param .{} {}", param.ty.modifier(), param.name)?;
+        }
+        if !self.params.is_empty() {
+            out.write_str("\n)")?;
+        }
+        out.write_str("\n{\n")?;
+        for class in RegisterClass::ALL {
+            match self.registers[class.index()] {
+                0 => (),
+                count => writeln!(out, "\t.reg .{} %{}<{}>;", class.ty().modifier(), class.prefix(), count + 1)?,
+            }
+        }
+        for line in &self.lines {
+            match line {
+                Line::Label(label) => writeln!(out, "{label}:")?,
+                Line::Instruction(instruction) => writeln!(out, "\t{instruction};")?,
+            }
+        }
+        out.write_str("}\n")
+    }
+}
+
+/// Appends instructions to a function, allocating the register each result is written to.
+pub struct BlockBuilder<'f> {
+    function: &'f mut FunctionBuilder,
+}
+
+impl BlockBuilder<'_> {
+    pub fn push(&mut self, instruction: Instruction) -> &mut Self {
+        self.function.lines.push(Line::Instruction(instruction));
+        self
+    }
+
+    pub fn mov(&mut self, ty: ScalarType, src: impl Into<Value>) -> Reg {
+        let dst = self.function.register(ty);
+        self.push(Instruction::Mov { ty, dst, src: src.into() });
+        dst
+    }
+
+    pub fn ld(&mut self, space: StateSpace, ty: ScalarType, address: impl Into<Address>) -> Reg {
+        let dst = self.function.register(ty);
+        self.push(Instruction::Ld { space, ty, dst, address: address.into() });
+        dst
+    }
+
+    pub fn ld_param(&mut self, param: &Param) -> Reg {
+        self.ld(StateSpace::Param, param.ty, param)
+    }
+
+    pub fn st(&mut self, space: StateSpace, ty: ScalarType, address: impl Into<Address>, src: impl Into<Value>) {
+        self.push(Instruction::St { space, ty, address: address.into(), src: src.into() });
+    }
+
+    pub fn cvta_to(&mut self, space: StateSpace, src: impl Into<Value>) -> Reg {
+        let dst = self.function.register(ScalarType::U64);
+        self.push(Instruction::CvtaTo { space, dst, src: src.into() });
+        dst
+    }
+
+    pub fn cvt(&mut self, to: ScalarType, from: ScalarType, src: impl Into<Value>) -> Reg {
+        let dst = self.function.register(to);
+        self.push(Instruction::Cvt { to, from, dst, src: src.into() });
+        dst
+    }
+
+    pub fn binary(&mut self, op: BinaryOp, ty: ScalarType, a: impl Into<Value>, b: impl Into<Value>) -> Reg {
+        let dst = self.function.register(if op == BinaryOp::MulWide { widened(ty) } else { ty });
+        self.push(Instruction::Binary { op, ty, dst, a: a.into(), b: b.into() });
+        dst
+    }
+
+    pub fn mad(&mut self, ty: ScalarType, a: impl Into<Value>, b: impl Into<Value>, c: impl Into<Value>) -> Reg {
+        let dst = self.function.register(ty);
+        self.push(Instruction::Mad { ty, dst, a: a.into(), b: b.into(), c: c.into() });
+        dst
+    }
+
+    /// Compares `a` with `b`, returning the predicate register holding the result.
+    pub fn setp(&mut self, comparison: Comparison, ty: ScalarType, a: impl Into<Value>, b: impl Into<Value>) -> Reg {
+        let dst = self.function.register(ScalarType::Pred);
+        self.push(Instruction::Setp { comparison, ty, dst, a: a.into(), b: b.into() });
+        dst
+    }
+
+    pub fn selp(&mut self, ty: ScalarType, a: impl Into<Value>, b: impl Into<Value>, predicate: Reg) -> Reg {
+        let dst = self.function.register(ty);
+        self.push(Instruction::Selp { ty, dst, a: a.into(), b: b.into(), predicate });
+        dst
+    }
+
+    pub fn bra(&mut self, target: Label) {
+        self.push(Instruction::Bra { target, guard: None });
+    }
+
+    /// Branches to `target` if `predicate` is true.
+    pub fn bra_if(&mut self, predicate: Reg, target: Label) {
+        self.push(Instruction::Bra { target, guard: Some((predicate, true)) });
+    }
+
+    /// Branches to `target` if `predicate` is false.
+    pub fn bra_unless(&mut self, predicate: Reg, target: Label) {
+        self.push(Instruction::Bra { target, guard: Some((predicate, false)) });
+    }
+
+    pub fn ret(&mut self) {
+        self.push(Instruction::Ret);
+    }
+}
+
+#[cfg(test)]
+mod test_builder {
+    use crate::parser::{
+        builder::{
+            instruction::{BinaryOp, Comparison, Dim, SpecialRegister},
+            BuildError, ModuleBuilder,
+        },
+        function::body::{BodyLine, Goto},
+        instruction::types::{ScalarType, StateSpace},
+        printer::{Indent, Printer},
+    };
+
+    /// `out[i] = in[i] * 2.0` for every `i < n`.
+    fn scale() -> ModuleBuilder {
+        let mut module = ModuleBuilder::new((8, 0), "sm_90");
+        let mut kernel = module.kernel("scale");
+        let input = kernel.param(ScalarType::U64);
+        let output = kernel.param(ScalarType::U64);
+        let n = kernel.param(ScalarType::U32);
+        let exit = kernel.label();
+        let mut body = kernel.body();
+        let tid = body.mov(ScalarType::U32, SpecialRegister::Tid(Dim::X));
+        let ntid = body.mov(ScalarType::U32, SpecialRegister::Ntid(Dim::X));
+        let ctaid = body.mov(ScalarType::U32, SpecialRegister::Ctaid(Dim::X));
+        let i = body.mad(ScalarType::S32, ctaid, ntid, tid);
+        let n = body.ld_param(&n);
+        let done = body.setp(Comparison::Ge, ScalarType::S32, i, n);
+        body.bra_if(done, exit);
+        let offset = body.binary(BinaryOp::MulWide, ScalarType::S32, i, 4);
+        let input = body.ld_param(&input);
+        let input = body.cvta_to(StateSpace::Global, input);
+        let address = body.binary(BinaryOp::Add, ScalarType::S64, input, offset);
+        let value = body.ld(StateSpace::Global, ScalarType::F32, address);
+        let value = body.binary(BinaryOp::Mul, ScalarType::F32, value, 2.0f32);
+        let output = body.ld_param(&output);
+        let output = body.cvta_to(StateSpace::Global, output);
+        let address = body.binary(BinaryOp::Add, ScalarType::S64, output, offset);
+        body.st(StateSpace::Global, ScalarType::F32, address, value);
+        kernel.block(exit).ret();
+        module.push(kernel).unwrap();
+        module
+    }
+
+    #[test]
+    fn registers_and_labels() {
+        let ptx = scale().build().unwrap();
+        let source = ptx.source();
+        assert!(source.contains(".reg .pred %p<2>;"));
+        assert!(source.contains(".reg .b32 %r<6>;"));
+        assert!(source.contains(".reg .b64 %rd<8>;"));
+        assert!(source.contains(".reg .f32 %f<3>;"));
+        assert!(source.contains("mad.lo.s32 %r4, %r3, %r2, %r1;"));
+        assert!(source.contains("mul.wide.s32 %rd1, %r4, 4;"));
+        assert!(source.contains("mul.f32 %f2, %f1, 0f40000000;"));
+        assert!(source.contains("mov.u32 %r1, %tid.x;"));
+        let file = ptx.file();
+        let function = &file.functions()[0];
+        let lines = function.body.clone().unwrap().flatten().map(|(_, line)| line).collect::<Vec<_>>();
+        assert!(lines.contains(&BodyLine::Label("L__BB0_1")));
+        let gotos = lines.iter().filter_map(|line| match line {
+            BodyLine::Goto(goto) => Some(goto.clone()),
+            _ => None,
+        });
+        assert_eq!(gotos.map(|goto: Goto| goto.label()).collect::<Vec<_>>(), ["L__BB0_1"]);
+        assert_eq!(lines.last(), Some(&BodyLine::Return));
+    }
+
+    #[test]
+    fn printed_with_layout() {
+        let printer = Printer::new().with_indent(Indent::Spaces(2)).with_operand_column(12);
+        let printed = scale().print(&printer).unwrap();
+        assert!(printed.contains("\n  ld.param.u64 %rd2, [scale_param_0];\n"));
+        assert!(printed.contains("\n  mad.lo.s32  %r4, %r3, %r2, %r1;\n"));
+        assert!(printed.contains("\n  @%p1 bra         $L__BB0_1;\n"));
+        assert!(printed.ends_with("\n$L__BB0_1:\n  ret;\n}\n"));
+    }
+
+    #[test]
+    fn labels_are_unique_across_functions() {
+        let mut module = ModuleBuilder::new((8, 0), "sm_90");
+        let mut first = module.function("first");
+        let mut second = module.function("second");
+        let (a, b) = (first.label(), second.label());
+        assert_ne!(a.to_string(), b.to_string());
+        first.block(a).ret();
+        second.block(b).ret();
+        module.push(first).unwrap();
+        module.push(second).unwrap();
+        let ptx = module.build().unwrap();
+        assert_eq!(ptx.file().functions().len(), 2);
+    }
+
+    #[test]
+    fn errors() {
+        let mut module = ModuleBuilder::new((8, 0), "sm_90");
+        let mut kernel = module.kernel("k");
+        let missing = kernel.label();
+        kernel.body().bra(missing);
+        assert_eq!(module.push(kernel), Err(BuildError::UnplacedLabel(missing)));
+
+        let mut other = module.kernel("other");
+        let reg = other.register(ScalarType::U32);
+        let mut kernel = module.kernel("k");
+        kernel.body().binary(BinaryOp::Add, ScalarType::U32, reg, 1);
+        assert_eq!(module.push(kernel), Err(BuildError::ForeignRegister(reg)));
+
+        let empty = module.kernel("k");
+        module.push(empty).unwrap();
+        let duplicate = module.kernel("k");
+        assert_eq!(module.push(duplicate), Err(BuildError::DuplicateFunction("k".into())));
+    }
+
+    #[test]
+    fn unsupported_types() {
+        let mut module = ModuleBuilder::new((8, 0), "sm_90");
+        for (op, ty) in [
+            (BinaryOp::Rem, ScalarType::F32),
+            (BinaryOp::MulWide, ScalarType::F32),
+            (BinaryOp::MulWide, ScalarType::S64),
+            (BinaryOp::And, ScalarType::F32),
+            (BinaryOp::Shl, ScalarType::F64),
+            (BinaryOp::Add, ScalarType::Pred),
+            (BinaryOp::Div, ScalarType::F16),
+            (BinaryOp::Max, ScalarType::B32),
+        ] {
+            let mut kernel = module.kernel("k");
+            let a = kernel.register(ty);
+            kernel.body().binary(op, ty, a, a);
+            assert_eq!(module.push(kernel), Err(BuildError::UnsupportedType(op, ty)));
+        }
+
+        let mut kernel = module.kernel("k");
+        let p = kernel.register(ScalarType::Pred);
+        let r = kernel.register(ScalarType::U32);
+        let w = kernel.register(ScalarType::S64);
+        let mut body = kernel.body();
+        body.binary(BinaryOp::And, ScalarType::Pred, p, p);
+        body.binary(BinaryOp::And, ScalarType::U32, r, 1);
+        body.binary(BinaryOp::MulWide, ScalarType::U32, r, r);
+        body.binary(BinaryOp::Rem, ScalarType::S64, w, w);
+        module.push(kernel).unwrap();
+        assert!(module.build().is_ok());
+    }
+}
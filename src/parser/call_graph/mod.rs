@@ -0,0 +1,448 @@
+//! Which functions of a module call which.
+//!
+//! ```
+//! use ptx_parser::prelude::*;
+//!
+//! let ptx: PtxFile = "
+//! .version 7.5
+//! .target sm_80
+//! .address_size 64
+//!
+//! .func helper(.param .b32 n)
+//! {
+//!     ret;
+//! }
+//! .visible .entry kernel(.param .b32 n)
+//! {
+//!     { // callseq 0, 0
+//!     .param .b32 param0;
+//!     call.uni
+//!     helper,
+//!     (
+//!     param0
+//!     );
+//!     } // callseq 0
+//!     ret;
+//! }
+//! "
+//! .try_into()
+//! .unwrap();
+//!
+//! let calls = ptx.call_graph();
+//! assert_eq!(calls.reachable_from("kernel"), ["helper"]);
+//! assert!(calls.recursive_cycles().is_empty());
+//! assert!(calls.uncalled().is_empty());
+//! ```
+
+use std::{collections::HashMap, vec, vec::Vec};
+
+use crate::parser::{
+    symbol::{Scope, SymbolKind, SymbolTable},
+    PtxFile,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CallKind {
+    /// A `call` naming the callee.
+    Direct,
+    /// The caller takes the address of the callee, or of a global whose initializer
+    /// holds it, such as a vtable; it may call it through the pointer. This includes an
+    /// address passed to a call sequence as an argument.
+    Indirect,
+}
+
+/// An edge of a `CallGraph`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Call<'a> {
+    pub caller: &'a str,
+    pub callee: &'a str,
+    pub kind: CallKind,
+    /// The name of the callee, or of the global holding its address, in the caller's body.
+    pub text: &'a str,
+}
+
+/// The functions of a module with the calls between them. A function declared before it
+/// is defined is one node.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CallGraph<'a> {
+    functions: Vec<&'a str>,
+    entries: Vec<bool>,
+    calls: Vec<Call<'a>>,
+    callees: Vec<Vec<usize>>,
+    callers: Vec<Vec<usize>>,
+    index: HashMap<&'a str, usize>,
+}
+
+impl<'a> CallGraph<'a> {
+    pub fn new(file: &PtxFile<'a>) -> Self {
+        let symbols = SymbolTable::resolve(file);
+        let mut graph = CallGraph {
+            functions: Vec::new(),
+            entries: Vec::new(),
+            calls: Vec::new(),
+            callees: Vec::new(),
+            callers: Vec::new(),
+            index: HashMap::new(),
+        };
+        for function in file.functions() {
+            let name = function.signature.name;
+            let node = *graph.index.entry(name).or_insert(graph.functions.len());
+            if node == graph.functions.len() {
+                graph.functions.push(name);
+                graph.entries.push(false);
+                graph.callees.push(Vec::new());
+                graph.callers.push(Vec::new());
+            }
+            graph.entries[node] |= function.signature.entry;
+        }
+
+        let tables = function_tables(file, &symbols);
+        for reference in symbols.references() {
+            let (Some(caller), Some(definition)) = (reference.scope().function(), symbols.definition(reference)) else {
+                continue;
+            };
+            let caller = file.functions()[caller].signature.name;
+            let callees = match definition.kind() {
+                SymbolKind::Function => vec![definition.name()],
+                SymbolKind::Global => tables.get(definition.name()).cloned().unwrap_or_default(),
+                _ => continue,
+            };
+            let kind = match (reference.kind(), definition.kind()) {
+                (SymbolKind::Function, SymbolKind::Function) => CallKind::Direct,
+                _ => CallKind::Indirect,
+            };
+            for callee in callees {
+                graph.add(Call { caller, callee, kind, text: reference.name() });
+            }
+        }
+        graph
+    }
+
+    fn add(&mut self, call: Call<'a>) {
+        let (caller, callee) = (self.index[call.caller], self.index[call.callee]);
+        if !self.callees[caller].contains(&callee) {
+            self.callees[caller].push(callee);
+            self.callers[callee].push(caller);
+        }
+        self.calls.push(call);
+    }
+
+    /// The name of every function, in the order of the module.
+    pub fn functions(&self) -> &[&'a str] {
+        &self.functions
+    }
+
+    /// The `.entry` functions.
+    pub fn entries(&self) -> impl Iterator<Item = &'a str> + '_ {
+        self.functions.iter().zip(&self.entries).filter(|(_, entry)| **entry).map(|(name, _)| *name)
+    }
+
+    /// Every call, in the order of the module; a function calling another twice gives two calls.
+    pub fn calls(&self) -> &[Call<'a>] {
+        &self.calls
+    }
+
+    pub fn callees(&self, function: &str) -> Vec<&'a str> {
+        self.names(self.index.get(function).map(|node| self.callees[*node].as_slice()))
+    }
+
+    pub fn callers(&self, function: &str) -> Vec<&'a str> {
+        self.names(self.index.get(function).map(|node| self.callers[*node].as_slice()))
+    }
+
+    fn names(&self, nodes: Option<&[usize]>) -> Vec<&'a str> {
+        nodes.unwrap_or_default().iter().map(|node| self.functions[*node]).collect()
+    }
+
+    /// The functions `function` may call, directly or through other functions, breadth first.
+    /// `function` itself is included only if it is recursive.
+    pub fn reachable_from(&self, function: &str) -> Vec<&'a str> {
+        let Some(&start) = self.index.get(function) else {
+            return Vec::new();
+        };
+        let mut seen = vec![false; self.functions.len()];
+        let mut order = self.callees[start].clone();
+        for node in &order {
+            seen[*node] = true;
+        }
+        let mut next = 0;
+        while let Some(&node) = order.get(next) {
+            next += 1;
+            for &callee in &self.callees[node] {
+                if !seen[callee] {
+                    seen[callee] = true;
+                    order.push(callee);
+                }
+            }
+        }
+        self.names(Some(&order))
+    }
+
+    /// The functions each `.entry` may call.
+    pub fn reachable_from_entries(&self) -> Vec<(&'a str, Vec<&'a str>)> {
+        self.entries().map(|entry| (entry, self.reachable_from(entry))).collect()
+    }
+
+    /// The groups of functions which call each other in a cycle, or a function calling
+    /// itself. Calls in a cycle need a stack whose depth `ptxas` cannot bound.
+    pub fn recursive_cycles(&self) -> Vec<Vec<&'a str>> {
+        let mut tarjan = Tarjan {
+            graph: self,
+            index: vec![None; self.functions.len()],
+            low: vec![0; self.functions.len()],
+            on_stack: vec![false; self.functions.len()],
+            stack: Vec::new(),
+            next: 0,
+            components: Vec::new(),
+        };
+        for node in 0..self.functions.len() {
+            if tarjan.index[node].is_none() {
+                tarjan.connect(node);
+            }
+        }
+        let mut cycles = tarjan
+            .components
+            .into_iter()
+            .filter(|component| component.len() > 1 || self.callees[component[0]].contains(&component[0]))
+            .map(|mut component| {
+                component.sort();
+                self.names(Some(&component))
+            })
+            .collect::<Vec<_>>();
+        cycles.sort_by_key(|cycle| self.index[cycle[0]]);
+        cycles
+    }
+
+    pub fn is_recursive(&self, function: &str) -> bool {
+        self.recursive_cycles().iter().any(|cycle| cycle.contains(&function))
+    }
+
+    /// The functions other than entries which no other function calls.
+    pub fn uncalled(&self) -> Vec<&'a str> {
+        (0..self.functions.len())
+            .filter(|node| !self.entries[*node] && self.callers[*node].iter().all(|caller| caller == node))
+            .map(|node| self.functions[node])
+            .collect()
+    }
+}
+
+/// Finds the strongly connected components of a `CallGraph`.
+struct Tarjan<'g, 'a> {
+    graph: &'g CallGraph<'a>,
+    index: Vec<Option<usize>>,
+    low: Vec<usize>,
+    on_stack: Vec<bool>,
+    stack: Vec<usize>,
+    next: usize,
+    components: Vec<Vec<usize>>,
+}
+
+impl Tarjan<'_, '_> {
+    fn connect(&mut self, node: usize) {
+        self.index[node] = Some(self.next);
+        self.low[node] = self.next;
+        self.next += 1;
+        self.stack.push(node);
+        self.on_stack[node] = true;
+        for &callee in &self.graph.callees[node] {
+            match self.index[callee] {
+                None => {
+                    self.connect(callee);
+                    self.low[node] = self.low[node].min(self.low[callee]);
+                }
+                Some(index) if self.on_stack[callee] => self.low[node] = self.low[node].min(index),
+                Some(_) => (),
+            }
+        }
+        if Some(self.low[node]) == self.index[node] {
+            let mut component = Vec::new();
+            while let Some(member) = self.stack.pop() {
+                self.on_stack[member] = false;
+                component.push(member);
+                if member == node {
+                    break;
+                }
+            }
+            self.components.push(component);
+        }
+    }
+}
+
+/// The functions whose address the initializer of each global holds, directly or through
+/// other globals, e.g. `{drop_in_place, 0, 1, type_id}` for a vtable.
+fn function_tables<'a>(file: &PtxFile<'a>, symbols: &SymbolTable<'a>) -> HashMap<&'a str, Vec<&'a str>> {
+    let initializers = file
+        .globals()
+        .iter()
+        .filter_map(|global| {
+            let (declaration, initializer) = global.raw_string().split_once('=')?;
+            let name = declaration.split_whitespace().last()?.split('[').next()?;
+            let names = initializer
+                .split(|c: char| !(c.is_alphanumeric() || c == '_' || c == '$'))
+                .filter(|name| name.starts_with(|c: char| !c.is_ascii_digit()) && *name != "generic")
+                .collect::<Vec<_>>();
+            Some((name, names))
+        })
+        .collect::<HashMap<_, _>>();
+    initializers
+        .keys()
+        .map(|global| {
+            let mut functions = Vec::new();
+            let mut pending = vec![*global];
+            let mut seen = vec![*global];
+            while let Some(global) = pending.pop() {
+                for name in initializers.get(global).into_iter().flatten() {
+                    let Some(id) = symbols.lookup(Scope::Module, name) else { continue };
+                    let symbol = symbols.symbol(id);
+                    match symbol.kind() {
+                        SymbolKind::Function if !functions.contains(&symbol.name()) => functions.push(symbol.name()),
+                        SymbolKind::Global if !seen.contains(&symbol.name()) => {
+                            seen.push(symbol.name());
+                            pending.push(symbol.name());
+                        }
+                        _ => (),
+                    }
+                }
+            }
+            (*global, functions)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test_call_graph {
+    use std::vec::Vec;
+
+    use crate::{parser::PtxFile, ptx_files};
+
+    use super::{CallGraph, CallKind};
+
+    const SOURCE: &str = "
+.version 7.5
+.target sm_80
+.address_size 64
+
+.func even(.param .b32 n);
+.func odd(.param .b32 n)
+{
+	{ // callseq 0, 0
+	.param .b32 param0;
+	call.uni
+	even,
+	(
+	param0
+	);
+	} // callseq 0
+	ret;
+}
+.func even(.param .b32 n)
+{
+	{ // callseq 1, 0
+	.param .b32 param0;
+	call.uni
+	odd,
+	(
+	param0
+	);
+	} // callseq 1
+	ret;
+}
+.func unused(.param .b32 n)
+{
+	ret;
+}
+.func handler(.param .b32 n)
+{
+	ret;
+}
+.global .align 8 .u64 table[2] = {handler, 0};
+.visible .entry kernel(.param .b32 n)
+{
+	.reg .b64 %rd<2>;
+	mov.u64 %rd1, table;
+	{ // callseq 2, 0
+	.param .b32 param0;
+	call.uni
+	odd,
+	(
+	param0
+	);
+	} // callseq 2
+	ret;
+}
+";
+
+    #[test]
+    fn calls() {
+        let file = PtxFile::try_from(SOURCE).unwrap();
+        let graph = CallGraph::new(&file);
+        assert_eq!(graph.functions(), ["even", "odd", "unused", "handler", "kernel"]);
+        assert_eq!(graph.entries().collect::<Vec<_>>(), ["kernel"]);
+        assert_eq!(graph.callees("kernel"), ["handler", "odd"]);
+        assert_eq!(graph.callers("odd"), ["even", "kernel"]);
+        let kinds = graph.calls().iter().filter(|call| call.caller == "kernel").map(|call| (call.callee, call.kind, call.text)).collect::<Vec<_>>();
+        assert_eq!(kinds, [("handler", CallKind::Indirect, "table"), ("odd", CallKind::Direct, "odd")]);
+        assert_eq!(graph.reachable_from("kernel"), ["handler", "odd", "even"]);
+        assert_eq!(graph.reachable_from("odd"), ["even", "odd"]);
+        assert_eq!(graph.reachable_from_entries(), [("kernel", graph.reachable_from("kernel"))]);
+        assert_eq!(graph.recursive_cycles(), [["even", "odd"]]);
+        assert!(graph.is_recursive("odd"));
+        assert!(!graph.is_recursive("kernel"));
+        assert_eq!(graph.uncalled(), ["unused"]);
+    }
+
+    #[test]
+    fn addresses_passed_to_calls() {
+        let file = PtxFile::try_from(
+            "
+.version 7.5
+.target sm_80
+.address_size 64
+
+.func handler(.param .b32 n)
+{
+	ret;
+}
+.func apply(.param .b64 f)
+{
+	ret;
+}
+.visible .entry kernel(.param .b32 n)
+{
+	.reg .b64 %rd<2>;
+	{ // callseq 0, 0
+	.param .b64 param0;
+	mov.u64 %rd1, handler;
+	st.param.b64 [param0+0], %rd1;
+	call.uni
+	apply,
+	(
+	param0
+	);
+	} // callseq 0
+	ret;
+}
+",
+        )
+        .unwrap();
+        let graph = CallGraph::new(&file);
+        let kinds = graph.calls().iter().map(|call| (call.caller, call.callee, call.kind)).collect::<Vec<_>>();
+        assert_eq!(kinds, [("kernel", "handler", CallKind::Indirect), ("kernel", "apply", CallKind::Direct)]);
+        assert_eq!(graph.callees("kernel"), ["handler", "apply"]);
+    }
+
+    #[test]
+    fn vtables() {
+        let file = PtxFile::try_from(ptx_files::b::_PTX).unwrap();
+        let graph = CallGraph::new(&file);
+        let panic_fmt = graph.functions().iter().copied().find(|name| name.contains("panic_fmt")).unwrap();
+        let indirect = graph.calls().iter().filter(|call| call.caller == panic_fmt && call.kind == CallKind::Indirect);
+        assert_eq!(indirect.count(), 2, "the two functions of the `PanicInfo` vtable");
+        assert!(graph.reachable_from("run_length").contains(&"rust_begin_unwind"));
+        // `From<SeedInitializer> for Random` calls itself to reseed.
+        let cycles = graph.recursive_cycles();
+        assert_eq!(cycles.len(), 1);
+        assert!(cycles[0].len() == 1 && cycles[0][0].contains("SeedInitializer"));
+        assert!(graph.uncalled().is_empty(), "{:?}", graph.uncalled());
+    }
+}
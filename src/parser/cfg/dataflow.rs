@@ -0,0 +1,335 @@
+//! A worklist solver for dataflow analyses over a `Cfg`.
+//!
+//! An analysis gives a `Lattice` of facts and how each line changes them; `Analysis::solve`
+//! then finds the facts holding before and after every line. `Liveness` and
+//! `ReachingDefinitions` are written this way.
+//!
+//! ```
+//! use std::collections::BTreeSet;
+//!
+//! use ptx_parser::prelude::*;
+//!
+//! /// The registers some path from the entry writes before each line.
+//! struct Written;
+//!
+//! impl<'a> Analysis<'a> for Written {
+//!     type Domain = BTreeSet<&'a str>;
+//!     const DIRECTION: Direction = Direction::Forward;
+//!
+//!     fn bottom(&self, _: &Cfg<'a>) -> Self::Domain {
+//!         BTreeSet::new()
+//!     }
+//!
+//!     fn transfer(&self, _: usize, line: &BodyLine<'a>, written: &mut Self::Domain) {
+//!         written.extend(Registers::of(line).defs);
+//!     }
+//! }
+//!
+//! let ptx: PtxParser = "
+//! .version 7.5
+//! .target sm_80
+//! .address_size 64
+//!
+//! .visible .entry kernel(
+//!     .param .u32 kernel_param_0
+//! )
+//! {
+//!     .reg .pred %p<2>;
+//!     .reg .b32 %r<3>;
+//!     ld.param.u32 %r1, [kernel_param_0];
+//! $L__BB0_1:
+//!     add.s32 %r1, %r1, -1;
+//!     setp.ne.s32 %p1, %r1, 0;
+//!     @%p1 bra $L__BB0_1;
+//!     ret;
+//! }
+//! "
+//! .try_into()
+//! .unwrap();
+//!
+//! let Some(Ok((_, FunctionOrGlobal::Function(function)))) = ptx.into_iter().next() else {
+//!     panic!()
+//! };
+//! let cfg = Cfg::new(&function).unwrap();
+//! let written = Written.solve(&cfg);
+//! let body = cfg.block_of_label("L__BB0_1").unwrap();
+//! assert_eq!(written.start(body).iter().copied().collect::<Vec<_>>(), ["%p1", "%r1"]);
+//! ```
+
+use std::{
+    collections::{BTreeMap, BTreeSet, VecDeque},
+    vec,
+    vec::Vec,
+};
+
+use super::{BlockId, Cfg};
+use crate::parser::function::body::BodyLine;
+
+/// The order facts flow in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// From the entry along the edges, as for reaching definitions.
+    Forward,
+    /// From the exits against the edges, as for liveness.
+    Backward,
+}
+
+/// Facts which can be merged where paths meet.
+///
+/// `join` must only ever grow a value, and a value may only grow finitely many times, or
+/// the solver does not stop.
+pub trait Lattice: Clone + PartialEq {
+    /// Merges `other` into `self`, giving the facts holding on either path.
+    fn join(&mut self, other: &Self);
+}
+
+/// The union of the sets.
+impl<T: Ord + Clone> Lattice for BTreeSet<T> {
+    fn join(&mut self, other: &Self) {
+        self.extend(other.iter().cloned());
+    }
+}
+
+/// The values of each key joined, keys missing from a map standing for the least value.
+impl<K: Ord + Clone, V: Lattice> Lattice for BTreeMap<K, V> {
+    fn join(&mut self, other: &Self) {
+        for (key, value) in other {
+            match self.get_mut(key) {
+                Some(joined) => joined.join(value),
+                None => {
+                    self.insert(key.clone(), value.clone());
+                }
+            }
+        }
+    }
+}
+
+/// A dataflow analysis: the facts it tracks and how each line changes them.
+pub trait Analysis<'a> {
+    type Domain: Lattice;
+    const DIRECTION: Direction;
+
+    /// The least value, which every block starts from.
+    fn bottom(&self, cfg: &Cfg<'a>) -> Self::Domain;
+
+    /// The facts holding where control enters the function going forward, or leaves it
+    /// going backward. The least value unless overridden.
+    fn boundary(&self, cfg: &Cfg<'a>) -> Self::Domain {
+        self.bottom(cfg)
+    }
+
+    /// Turns the facts holding before line `index` of `Cfg::lines` into those holding
+    /// after it, or the other way around going backward. Must be monotone.
+    fn transfer(&self, index: usize, line: &BodyLine<'a>, state: &mut Self::Domain);
+
+    /// Iterates the analysis over `cfg` until nothing changes.
+    fn solve(&self, cfg: &Cfg<'a>) -> Solution<Self::Domain>
+    where
+        Self: Sized,
+    {
+        solve(cfg, self)
+    }
+}
+
+/// The facts an `Analysis` found, always given in the order of the source: `before` a
+/// line is above it, whichever way the analysis runs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Solution<D> {
+    before: Vec<D>,
+    after: Vec<D>,
+    start: Vec<D>,
+    end: Vec<D>,
+}
+
+impl<D> Solution<D> {
+    /// The facts at the start of block `id`.
+    pub fn start(&self, id: BlockId) -> &D {
+        &self.start[id.0]
+    }
+
+    /// The facts at the end of block `id`.
+    pub fn end(&self, id: BlockId) -> &D {
+        &self.end[id.0]
+    }
+
+    /// The facts before line `index` of `Cfg::lines`.
+    pub fn before(&self, index: usize) -> &D {
+        &self.before[index]
+    }
+
+    /// The facts after line `index` of `Cfg::lines`.
+    pub fn after(&self, index: usize) -> &D {
+        &self.after[index]
+    }
+}
+
+/// Visits blocks in reverse post-order going forward and post-order going backward, then
+/// again whenever what flows into them changes. Blocks the entry does not reach are
+/// solved too, from the least value.
+fn solve<'a, A: Analysis<'a>>(cfg: &Cfg<'a>, analysis: &A) -> Solution<A::Domain> {
+    let bottom = analysis.bottom(cfg);
+    let mut solution = Solution {
+        before: vec![bottom.clone(); cfg.lines().len()],
+        after: vec![bottom.clone(); cfg.lines().len()],
+        start: vec![bottom.clone(); cfg.blocks().len()],
+        end: vec![bottom.clone(); cfg.blocks().len()],
+    };
+    let order = match A::DIRECTION {
+        Direction::Forward => cfg.reverse_post_order(),
+        Direction::Backward => cfg.post_order(),
+    };
+    let mut queued = vec![false; cfg.blocks().len()];
+    let mut pending = VecDeque::new();
+    for block in order.into_iter().chain(cfg.block_ids()) {
+        if !queued[block.0] {
+            queued[block.0] = true;
+            pending.push_back(block);
+        }
+    }
+
+    while let Some(block) = pending.pop_front() {
+        queued[block.0] = false;
+        let lines = cfg.block(block).lines();
+        let changed = match A::DIRECTION {
+            Direction::Forward => {
+                let mut state = if block == cfg.entry() { analysis.boundary(cfg) } else { bottom.clone() };
+                for predecessor in cfg.predecessors(block) {
+                    state.join(&solution.end[predecessor.0]);
+                }
+                solution.start[block.0] = state.clone();
+                for line in lines {
+                    solution.before[line] = state.clone();
+                    analysis.transfer(line, &cfg.lines()[line], &mut state);
+                    solution.after[line] = state.clone();
+                }
+                let changed = state != solution.end[block.0];
+                solution.end[block.0] = state;
+                changed
+            }
+            Direction::Backward => {
+                let successors = cfg.successors(block);
+                let mut state = if successors.is_empty() { analysis.boundary(cfg) } else { bottom.clone() };
+                for successor in successors {
+                    state.join(&solution.start[successor.0]);
+                }
+                solution.end[block.0] = state.clone();
+                for line in lines.rev() {
+                    solution.after[line] = state.clone();
+                    analysis.transfer(line, &cfg.lines()[line], &mut state);
+                    solution.before[line] = state.clone();
+                }
+                let changed = state != solution.start[block.0];
+                solution.start[block.0] = state;
+                changed
+            }
+        };
+        if changed {
+            let next = match A::DIRECTION {
+                Direction::Forward => cfg.successors(block),
+                Direction::Backward => cfg.predecessors(block),
+            };
+            for block in next {
+                if !queued[block.0] {
+                    queued[block.0] = true;
+                    pending.push_back(*block);
+                }
+            }
+        }
+    }
+    solution
+}
+
+#[cfg(test)]
+mod test_dataflow {
+    use std::collections::BTreeMap;
+
+    use crate::parser::{
+        cfg::{BlockId, Cfg, Registers},
+        function::{body::BodyLine, parse::parse_function},
+        instruction::Operand,
+    };
+
+    use super::{Analysis, Direction, Lattice};
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    enum Constant<'a> {
+        Value(&'a str),
+        Varying,
+    }
+
+    impl Lattice for Constant<'_> {
+        fn join(&mut self, other: &Self) {
+            if self != other {
+                *self = Constant::Varying;
+            }
+        }
+    }
+
+    /// The registers only ever set to one immediate by `mov`.
+    struct Constants;
+
+    impl<'a> Analysis<'a> for Constants {
+        type Domain = BTreeMap<&'a str, Constant<'a>>;
+        const DIRECTION: Direction = Direction::Forward;
+
+        fn bottom(&self, _: &Cfg<'a>) -> Self::Domain {
+            BTreeMap::new()
+        }
+
+        fn transfer(&self, _: usize, line: &BodyLine<'a>, constants: &mut Self::Domain) {
+            if let BodyLine::Operation(operation) = line {
+                if let (Ok([Operand::Register(register), Operand::Immediate(value)]), "mov") =
+                    (operation.operands().as_deref(), operation.opcode())
+                {
+                    constants.insert(register, Constant::Value(value));
+                    return;
+                }
+            }
+            let registers = Registers::of(line);
+            for register in registers.defs {
+                let constant = constants.entry(register).or_insert(Constant::Varying);
+                match registers.predicated {
+                    true => constant.join(&Constant::Varying),
+                    false => *constant = Constant::Varying,
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn constant_propagation() {
+        let (_, function) = parse_function(
+            ".func f(.param .b32 a)
+{
+	.reg .pred %p<2>;
+	.reg .b32 %r<5>;
+	ld.param.b32 %r1, [a];
+	mov.u32 %r2, 7;
+	setp.eq.s32 %p1, %r1, 0;
+	@%p1 bra $L__BB0_2;
+	mov.u32 %r3, 1;
+	mov.u32 %r4, 2;
+	bra.uni $L__BB0_3;
+$L__BB0_2:
+	mov.u32 %r3, 1;
+	mov.u32 %r4, 3;
+$L__BB0_3:
+	st.global.u32 [%r1], %r4;
+	ret;
+}",
+        )
+        .unwrap();
+        let cfg = Cfg::new(&function).unwrap();
+        let constants = Constants.solve(&cfg);
+        let join = constants.start(cfg.block_of_label("L__BB0_3").unwrap());
+        assert_eq!(join.get("%r2"), Some(&Constant::Value("7")));
+        assert_eq!(join.get("%r3"), Some(&Constant::Value("1")));
+        assert_eq!(join.get("%r4"), Some(&Constant::Varying));
+        assert_eq!(join.get("%r1"), Some(&Constant::Varying));
+        assert!(constants.start(cfg.entry()).is_empty());
+        assert_eq!(constants.end(BlockId::new(1)).get("%r4"), Some(&Constant::Value("2")));
+        let store = cfg.lines().len() - 2;
+        assert_eq!(constants.before(store), join);
+        assert_eq!(constants.after(store), join);
+    }
+}
@@ -0,0 +1,281 @@
+use std::{vec, vec::Vec};
+
+use super::{BlockId, Cfg};
+
+/// The edges of a `Cfg`, forwards for dominators or backwards from a virtual exit node
+/// joining every exit block for post-dominators.
+struct Graph {
+    root: usize,
+    successors: Vec<Vec<usize>>,
+    predecessors: Vec<Vec<usize>>,
+}
+
+impl Graph {
+    fn forward(cfg: &Cfg<'_>) -> Self {
+        let edges = |edges: &[BlockId]| edges.iter().map(BlockId::index).collect();
+        Graph {
+            root: cfg.entry().index(),
+            successors: cfg.block_ids().map(|id| edges(cfg.successors(id))).collect(),
+            predecessors: cfg.block_ids().map(|id| edges(cfg.predecessors(id))).collect(),
+        }
+    }
+
+    fn backward(cfg: &Cfg<'_>) -> Self {
+        let exit = cfg.blocks().len();
+        let exits = cfg.exits().iter().map(BlockId::index).collect::<Vec<_>>();
+        let mut graph = Graph::forward(cfg);
+        core::mem::swap(&mut graph.successors, &mut graph.predecessors);
+        for block in &exits {
+            graph.predecessors[*block].push(exit);
+        }
+        graph.successors.push(exits);
+        graph.predecessors.push(Vec::new());
+        graph.root = exit;
+        graph
+    }
+
+    /// The nodes reachable from the root, each before its successors except along back edges.
+    fn reverse_post_order(&self) -> Vec<usize> {
+        let mut visited = vec![false; self.successors.len()];
+        let mut order = Vec::with_capacity(self.successors.len());
+        let mut stack = vec![(self.root, 0)];
+        visited[self.root] = true;
+        while let Some((node, next)) = stack.last_mut() {
+            match self.successors[*node].get(*next) {
+                Some(&successor) => {
+                    *next += 1;
+                    if !visited[successor] {
+                        visited[successor] = true;
+                        stack.push((successor, 0));
+                    }
+                }
+                None => {
+                    order.push(*node);
+                    stack.pop();
+                }
+            }
+        }
+        order.reverse();
+        order
+    }
+}
+
+/// The dominator or post-dominator tree of a `Cfg`, with the dominance frontier of each block.
+///
+/// A block dominates another when every path from the entry to the other passes through
+/// it, and post-dominates it when every path from the other to an exit does. Blocks the
+/// entry does not reach, or which reach no exit for post-dominators, are left out.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DominatorTree {
+    root: usize,
+    /// The immediate dominator of each node; the post-dominator tree of a function with
+    /// several exits has a virtual root after the last block.
+    idom: Vec<Option<usize>>,
+    children: Vec<Vec<BlockId>>,
+    frontiers: Vec<Vec<BlockId>>,
+}
+
+impl DominatorTree {
+    pub fn dominators(cfg: &Cfg<'_>) -> Self {
+        DominatorTree::new(Graph::forward(cfg), cfg.blocks().len())
+    }
+
+    pub fn post_dominators(cfg: &Cfg<'_>) -> Self {
+        DominatorTree::new(Graph::backward(cfg), cfg.blocks().len())
+    }
+
+    /// Finds the immediate dominators as in "A Simple, Fast Dominance Algorithm" by Cooper,
+    /// Harvey and Kennedy, then the frontiers from the joins of the graph.
+    fn new(graph: Graph, blocks: usize) -> Self {
+        let order = graph.reverse_post_order();
+        let mut position = vec![usize::MAX; graph.successors.len()];
+        for (index, node) in order.iter().enumerate() {
+            position[*node] = index;
+        }
+        let mut idom = vec![None; graph.successors.len()];
+        idom[graph.root] = Some(graph.root);
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for &node in order.iter().skip(1) {
+                let new_idom = graph.predecessors[node]
+                    .iter()
+                    .copied()
+                    .filter(|predecessor| idom[*predecessor].is_some())
+                    .reduce(|a, b| intersect(&idom, &position, a, b));
+                if new_idom.is_some() && idom[node] != new_idom {
+                    idom[node] = new_idom;
+                    changed = true;
+                }
+            }
+        }
+        idom[graph.root] = None;
+
+        let mut children = vec![Vec::new(); blocks];
+        for &node in &order {
+            if let Some(parent) = idom[node].filter(|parent| *parent < blocks) {
+                children[parent].push(BlockId(node));
+            }
+        }
+        let mut frontiers = vec![Vec::<BlockId>::new(); blocks];
+        for &node in &order {
+            for &predecessor in &graph.predecessors[node] {
+                let mut runner = Some(predecessor).filter(|runner| position[*runner] != usize::MAX);
+                while let Some(current) = runner.filter(|current| Some(*current) != idom[node]) {
+                    if current < blocks && !frontiers[current].contains(&BlockId(node)) {
+                        frontiers[current].push(BlockId(node));
+                    }
+                    runner = idom[current];
+                }
+            }
+        }
+        DominatorTree { root: graph.root, idom, children, frontiers }
+    }
+
+    /// The closest block which strictly dominates `id`, or `None` for the root of the tree
+    /// and for blocks left out of it.
+    pub fn immediate_dominator(&self, id: BlockId) -> Option<BlockId> {
+        self.idom[id.0].filter(|idom| *idom < self.children.len()).map(BlockId)
+    }
+
+    /// The blocks whose immediate dominator is `id`.
+    pub fn children(&self, id: BlockId) -> &[BlockId] {
+        &self.children[id.0]
+    }
+
+    /// The blocks without an immediate dominator in the tree: the entry for dominators, and
+    /// the exits which no other block post-dominates for post-dominators.
+    pub fn roots(&self) -> Vec<BlockId> {
+        (0..self.children.len())
+            .filter(|node| self.contains(BlockId(*node)) && self.immediate_dominator(BlockId(*node)).is_none())
+            .map(BlockId)
+            .collect()
+    }
+
+    /// Whether `id` is in the tree, i.e. reachable from the entry, or for post-dominators
+    /// reaching an exit.
+    pub fn contains(&self, id: BlockId) -> bool {
+        id.0 == self.root || self.idom[id.0].is_some()
+    }
+
+    /// Whether `a` dominates `b`; every block in the tree dominates itself.
+    pub fn dominates(&self, a: BlockId, b: BlockId) -> bool {
+        if !self.contains(b) {
+            return false;
+        }
+        let mut node = Some(b.0);
+        while let Some(current) = node {
+            if current == a.0 {
+                return true;
+            }
+            node = self.idom[current];
+        }
+        false
+    }
+
+    pub fn strictly_dominates(&self, a: BlockId, b: BlockId) -> bool {
+        a != b && self.dominates(a, b)
+    }
+
+    /// The blocks where the dominance of `id` ends: those with a predecessor `id` dominates
+    /// without strictly dominating the block itself. For post-dominators these are the
+    /// blocks whose branch decides whether `id` runs, i.e. those `id` is control-dependent on.
+    pub fn frontier(&self, id: BlockId) -> &[BlockId] {
+        &self.frontiers[id.0]
+    }
+}
+
+/// The closest common dominator of `a` and `b`, walking up the tree by reverse post-order.
+fn intersect(idom: &[Option<usize>], position: &[usize], mut a: usize, mut b: usize) -> usize {
+    while a != b {
+        while position[a] > position[b] {
+            a = idom[a].unwrap_or(a);
+        }
+        while position[b] > position[a] {
+            b = idom[b].unwrap_or(b);
+        }
+    }
+    a
+}
+
+#[cfg(test)]
+mod test_dominance {
+    use std::vec::Vec;
+
+    use crate::parser::{cfg::Cfg, function::parse::parse_function};
+
+    use super::{BlockId, DominatorTree};
+
+    const DIAMOND: &str = "
+.func f(.param .b32 a)
+{
+	.reg .pred %p<3>;
+	.reg .b32 %r<2>;
+	ld.param.b32 %r1, [a];
+	setp.eq.s32 %p1, %r1, 0;
+	@%p1 bra $L__BB0_2;
+	add.s32 %r1, %r1, 1;
+	bra.uni $L__BB0_3;
+$L__BB0_2:
+	add.s32 %r1, %r1, 2;
+$L__BB0_3:
+	setp.eq.s32 %p2, %r1, 3;
+	@%p2 bra $L__BB0_5;
+	ret;
+$L__BB0_5:
+	exit;
+}";
+
+    fn ids(ids: &[usize]) -> Vec<BlockId> {
+        ids.iter().copied().map(BlockId::new).collect()
+    }
+
+    #[test]
+    fn dominators() {
+        let (_, function) = parse_function(DIAMOND.trim()).unwrap();
+        let cfg = Cfg::new(&function).unwrap();
+        assert_eq!(cfg.blocks().len(), 6);
+        let tree = DominatorTree::dominators(&cfg);
+        let idoms = cfg.block_ids().map(|id| tree.immediate_dominator(id).map(|idom| idom.index())).collect::<Vec<_>>();
+        assert_eq!(idoms, [None, Some(0), Some(0), Some(0), Some(3), Some(3)]);
+        assert_eq!(tree.roots(), ids(&[0]));
+        assert_eq!(tree.children(BlockId::new(0)), ids(&[1, 2, 3]));
+        assert!(tree.dominates(BlockId::new(0), BlockId::new(5)));
+        assert!(tree.dominates(BlockId::new(3), BlockId::new(3)));
+        assert!(!tree.strictly_dominates(BlockId::new(3), BlockId::new(3)));
+        assert!(!tree.dominates(BlockId::new(1), BlockId::new(3)));
+        assert_eq!(tree.frontier(BlockId::new(1)), ids(&[3]));
+        assert_eq!(tree.frontier(BlockId::new(2)), ids(&[3]));
+        assert!(tree.frontier(BlockId::new(0)).is_empty());
+    }
+
+    #[test]
+    fn post_dominators() {
+        let (_, function) = parse_function(DIAMOND.trim()).unwrap();
+        let cfg = Cfg::new(&function).unwrap();
+        let tree = DominatorTree::post_dominators(&cfg);
+        let idoms = cfg.block_ids().map(|id| tree.immediate_dominator(id).map(|idom| idom.index())).collect::<Vec<_>>();
+        assert_eq!(idoms, [Some(3), Some(3), Some(3), None, None, None]);
+        assert_eq!(tree.roots(), ids(&[3, 4, 5]));
+        assert!(tree.dominates(BlockId::new(3), BlockId::new(0)));
+        assert!(!tree.dominates(BlockId::new(4), BlockId::new(3)));
+        // Which side of the diamond runs depends on the branch ending block 0, and which
+        // exit is taken on the one ending block 3.
+        assert_eq!(tree.frontier(BlockId::new(1)), ids(&[0]));
+        assert_eq!(tree.frontier(BlockId::new(2)), ids(&[0]));
+        assert_eq!(tree.frontier(BlockId::new(4)), ids(&[3]));
+        assert!(tree.frontier(BlockId::new(3)).is_empty());
+    }
+
+    #[test]
+    fn infinite_loop() {
+        let (_, function) = parse_function(".func f(.param .b32 a)\n{\n$L__BB0_1:\n\tbra.uni $L__BB0_1;\n}").unwrap();
+        let cfg = Cfg::new(&function).unwrap();
+        let post = DominatorTree::post_dominators(&cfg);
+        assert!(!post.contains(cfg.entry()));
+        assert!(post.roots().is_empty());
+        let tree = DominatorTree::dominators(&cfg);
+        assert_eq!(tree.roots(), [cfg.entry()]);
+        assert!(tree.frontier(cfg.entry()).contains(&cfg.entry()));
+    }
+}
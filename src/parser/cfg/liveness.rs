@@ -0,0 +1,230 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    vec::Vec,
+};
+
+use super::{
+    dataflow::{Analysis, Direction, Solution},
+    BlockId, Cfg, Registers,
+};
+use crate::parser::function::{body::BodyLine, Function};
+
+/// The registers live before and after each line of a `Cfg`: those holding a value some
+/// later line may read.
+///
+/// A predicated line does not end the life of the registers it writes, since the write
+/// may not happen.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Liveness<'a> {
+    solution: Solution<BTreeSet<&'a str>>,
+}
+
+impl<'a> Liveness<'a> {
+    pub fn new(cfg: &Cfg<'a>) -> Self {
+        let registers = cfg.lines().iter().map(Registers::of).collect();
+        Liveness { solution: LiveRegisters { registers }.solve(cfg) }
+    }
+
+    /// The registers live at the start of block `id`.
+    pub fn live_in(&self, id: BlockId) -> &BTreeSet<&'a str> {
+        self.solution.start(id)
+    }
+
+    /// The registers live at the end of block `id`.
+    pub fn live_out(&self, id: BlockId) -> &BTreeSet<&'a str> {
+        self.solution.end(id)
+    }
+
+    /// The registers live before line `index` of `Cfg::lines`.
+    pub fn live_before(&self, index: usize) -> &BTreeSet<&'a str> {
+        self.solution.before(index)
+    }
+
+    /// The registers live after line `index` of `Cfg::lines`.
+    pub fn live_after(&self, index: usize) -> &BTreeSet<&'a str> {
+        self.solution.after(index)
+    }
+}
+
+/// The analysis behind `Liveness`, with the registers of each line.
+struct LiveRegisters<'a> {
+    registers: Vec<Registers<'a>>,
+}
+
+impl<'a> Analysis<'a> for LiveRegisters<'a> {
+    type Domain = BTreeSet<&'a str>;
+    const DIRECTION: Direction = Direction::Backward;
+
+    fn bottom(&self, _: &Cfg<'a>) -> Self::Domain {
+        BTreeSet::new()
+    }
+
+    fn transfer(&self, index: usize, _: &BodyLine<'a>, live: &mut Self::Domain) {
+        let registers = &self.registers[index];
+        if !registers.predicated {
+            for def in &registers.defs {
+                live.remove(def);
+            }
+        }
+        live.extend(registers.uses.iter().copied());
+    }
+}
+
+/// The most registers of a function live at once, an estimate of the physical registers
+/// `ptxas` needs before it reuses or spills any.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RegisterPressure<'a> {
+    pub function: &'a str,
+    /// The most registers of each class live at once, by their declared type such as
+    /// `.pred`, `.b32` or `.f64`. Classes may peak at different lines.
+    pub peaks: BTreeMap<&'a str, usize>,
+    /// The most registers of any class live at once.
+    pub peak: usize,
+}
+
+impl<'a> RegisterPressure<'a> {
+    /// The pressure of the body of `function`, or `None` for a declaration. Registers
+    /// without a `.reg` declaration are left out.
+    pub fn new(function: &Function<'a>) -> Option<Self> {
+        let cfg = Cfg::new(function)?;
+        let liveness = Liveness::new(&cfg);
+        let classes = RegisterClasses::new(cfg.lines());
+        let mut pressure = RegisterPressure { function: function.signature.name, peaks: BTreeMap::new(), peak: 0 };
+        for index in 0..cfg.lines().len() {
+            for live in [liveness.live_before(index), liveness.live_after(index)] {
+                let mut counts = BTreeMap::new();
+                for class in live.iter().filter_map(|register| classes.class(register)) {
+                    *counts.entry(class).or_insert(0) += 1;
+                }
+                pressure.peak = pressure.peak.max(counts.values().sum());
+                for (class, count) in counts {
+                    let peak = pressure.peaks.entry(class).or_insert(0);
+                    *peak = (*peak).max(count);
+                }
+            }
+        }
+        Some(pressure)
+    }
+}
+
+/// The declared type of each register of a body, from its `.reg` declarations.
+struct RegisterClasses<'a> {
+    /// By name, or by prefix for parameterized declarations such as `%r<5>`.
+    names: Vec<(&'a str, bool, &'a str)>,
+}
+
+impl<'a> RegisterClasses<'a> {
+    fn new(lines: &[BodyLine<'a>]) -> Self {
+        let names = lines
+            .iter()
+            .flat_map(|line| match line {
+                BodyLine::Register(register) => register
+                    .names()
+                    .filter_map(|(name, count)| Some((name, count.is_some(), register.ty()?)))
+                    .collect(),
+                _ => Vec::new(),
+            })
+            .collect();
+        RegisterClasses { names }
+    }
+
+    fn class(&self, register: &str) -> Option<&'a str> {
+        self.names
+            .iter()
+            .find(|(name, parameterized, _)| match parameterized {
+                true => register
+                    .strip_prefix(name)
+                    .is_some_and(|index| !index.is_empty() && index.bytes().all(|byte| byte.is_ascii_digit())),
+                false => register == *name,
+            })
+            .map(|(_, _, class)| *class)
+    }
+}
+
+#[cfg(test)]
+mod test_liveness {
+    use std::{collections::BTreeSet, vec::Vec};
+
+    use crate::parser::{
+        cfg::{test_cfg::ACCUMULATE, BlockId, Cfg, Registers},
+        function::{body::BodyLine, parse::parse_function},
+    };
+
+    use super::{Liveness, RegisterPressure};
+
+    fn set<'a>(registers: &[&'a str]) -> BTreeSet<&'a str> {
+        registers.iter().copied().collect()
+    }
+
+    #[test]
+    fn registers() {
+        let of = |line: &'static str| {
+            let (_, line) = crate::parser::function::body::parse_body_line(line).unwrap();
+            let Registers { defs, uses, predicated } = Registers::of(&line);
+            (defs, uses, predicated)
+        };
+        assert_eq!(of("add.s32 %r1, %r2, 1;"), (vec!["%r1"], vec!["%r2"], false));
+        assert_eq!(of("st.global.v2.f32 [%rd1+8], {%f1, %f2};"), (vec![], vec!["%rd1", "%f1", "%f2"], false));
+        assert_eq!(of("ld.global.v2.f32 {%f1, %f2}, [%rd1];"), (vec!["%f1", "%f2"], vec!["%rd1"], false));
+        assert_eq!(of("setp.lt.s32 %p1|%p2, %r2, %r1;"), (vec!["%p1", "%p2"], vec!["%r2", "%r1"], false));
+        assert_eq!(of("mov.u32 %r1, %tid.x;"), (vec!["%r1"], vec![], false));
+        assert_eq!(of("@!%p1 mov.u32 %r1, %r2;"), (vec!["%r1"], vec!["%p1", "%r2"], true));
+        assert_eq!(of("@%p1 bra $L__BB0_1;"), (vec![], vec!["%p1"], false));
+        assert_eq!(of("bar.sync %r1;"), (vec![], vec!["%r1"], false));
+        assert_eq!(of("barrier.cta.arrive %r1, %r2;"), (vec![], vec!["%r1", "%r2"], false));
+        assert_eq!(of("bar.red.popc.u32 %r1, 0, %p1;"), (vec!["%r1"], vec!["%p1"], false));
+        assert_eq!(of("barrier.cta.red.and.pred %p2, 1, %p1;"), (vec!["%p2"], vec!["%p1"], false));
+        assert_eq!(of("$L__BB0_1:"), (vec![], vec![], false));
+        let (_, call) = crate::parser::function::body::parse_body_line(
+            "{ // callseq 0, 0\n\t.param .b64 param0;\n\tst.param.b64 [param0+0], %rd1;\n\t.param .b32 retval0;\n\tcall.uni (retval0), \n\tnext, \n\t(\n\tparam0\n\t);\n\tld.param.b32 %r1, [retval0+0];\n\t} // callseq 0",
+        )
+        .unwrap();
+        assert!(matches!(call, BodyLine::FunctionCall(_)));
+        assert_eq!(Registers::of(&call).defs, ["%r1"]);
+        assert_eq!(Registers::of(&call).uses, ["%rd1"]);
+    }
+
+    #[test]
+    fn live_registers() {
+        let (_, function) = parse_function(ACCUMULATE).unwrap();
+        let cfg = Cfg::new(&function).unwrap();
+        let liveness = Liveness::new(&cfg);
+        let body = cfg.block_of_label("L__BB0_1").unwrap();
+        assert_eq!(liveness.live_in(cfg.entry()), &set(&[]));
+        assert_eq!(liveness.live_in(body), &set(&["%f4", "%r1", "%r2"]));
+        assert_eq!(liveness.live_out(body), &set(&["%f3", "%f4", "%p1", "%r1", "%r2"]));
+        assert!(liveness.live_in(BlockId::new(2)).contains("%f4"), "the guarded `mov` may not write `%f4`");
+        assert_eq!(liveness.live_out(BlockId::new(2)), &set(&[]));
+        let cvt = cfg.block(body).lines().start + 1;
+        assert_eq!(liveness.live_before(cvt), &set(&["%f4", "%r1", "%r2"]));
+        assert_eq!(liveness.live_after(cvt), &set(&["%f3", "%f4", "%r1", "%r2"]));
+    }
+
+    #[test]
+    fn register_pressure() {
+        let (_, function) = parse_function(ACCUMULATE).unwrap();
+        let pressure = RegisterPressure::new(&function).unwrap();
+        assert_eq!(pressure.function, "f");
+        let peaks = pressure.peaks.into_iter().collect::<Vec<_>>();
+        assert_eq!(peaks, [(".b32", 2), (".f32", 2), (".pred", 1)]);
+        assert_eq!(pressure.peak, 5);
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg(test)]
+mod test_register_pressure_files {
+    use crate::{parser::PtxFile, ptx_files};
+
+    #[test]
+    fn kernels() {
+        let file = PtxFile::try_from(ptx_files::kernel::_PTX).unwrap();
+        let pressures = file.register_pressure();
+        assert!(!pressures.is_empty());
+        for pressure in pressures {
+            assert!(pressure.peak > 0);
+            assert!(pressure.peaks.values().all(|peak| *peak <= pressure.peak));
+            assert!(pressure.peaks.values().sum::<usize>() >= pressure.peak);
+        }
+    }
+}
@@ -0,0 +1,216 @@
+use std::{vec, vec::Vec};
+
+use super::{dominance::DominatorTree, BlockId, Cfg};
+
+/// The index of a loop in its `LoopForest`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct LoopId(usize);
+
+impl LoopId {
+    pub fn index(&self) -> usize {
+        self.0
+    }
+}
+
+/// A natural loop: a header dominating every block of the loop, entered only through the
+/// header, and the latches jumping back to it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Loop {
+    header: BlockId,
+    latches: Vec<BlockId>,
+    blocks: Vec<BlockId>,
+    exits: Vec<BlockId>,
+    parent: Option<LoopId>,
+    children: Vec<LoopId>,
+    depth: usize,
+}
+
+impl Loop {
+    pub fn header(&self) -> BlockId {
+        self.header
+    }
+
+    /// The blocks with a back edge to the header.
+    pub fn latches(&self) -> &[BlockId] {
+        &self.latches
+    }
+
+    /// The blocks of the loop, including those of nested loops, in the order of the source.
+    pub fn blocks(&self) -> &[BlockId] {
+        &self.blocks
+    }
+
+    pub fn contains(&self, id: BlockId) -> bool {
+        self.blocks.binary_search(&id).is_ok()
+    }
+
+    /// The blocks outside the loop which control reaches when leaving it.
+    pub fn exits(&self) -> &[BlockId] {
+        &self.exits
+    }
+
+    /// The innermost loop containing this one.
+    pub fn parent(&self) -> Option<LoopId> {
+        self.parent
+    }
+
+    /// The loops nested directly inside this one.
+    pub fn children(&self) -> &[LoopId] {
+        &self.children
+    }
+
+    /// 1 for an outermost loop, 2 for a loop nested in it, and so on.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+}
+
+/// The natural loops of a `Cfg`, nested into trees.
+///
+/// Loops are found from back edges, edges to a block which dominates their source; the
+/// loops sharing a header are merged into one. Cycles entered other than through a single
+/// header, which `nvcc` and `rustc` never emit, are not loops.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoopForest {
+    loops: Vec<Loop>,
+    innermost: Vec<Option<LoopId>>,
+}
+
+impl LoopForest {
+    pub fn new(cfg: &Cfg<'_>, dominators: &DominatorTree) -> Self {
+        // Headers come before the headers of the loops nested in them in reverse post-order.
+        let mut loops = cfg
+            .reverse_post_order()
+            .into_iter()
+            .filter_map(|header| {
+                let latches = cfg
+                    .predecessors(header)
+                    .iter()
+                    .copied()
+                    .filter(|latch| dominators.dominates(header, *latch))
+                    .collect::<Vec<_>>();
+                (!latches.is_empty()).then(|| natural_loop(cfg, header, latches))
+            })
+            .collect::<Vec<_>>();
+
+        let mut innermost = vec![None; cfg.blocks().len()];
+        for index in 0..loops.len() {
+            let parent = (0..index).rev().find(|outer| loops[*outer].contains(loops[index].header));
+            if let Some(parent) = parent {
+                loops[parent].children.push(LoopId(index));
+                loops[index].depth = loops[parent].depth + 1;
+            }
+            loops[index].parent = parent.map(LoopId);
+            for block in &loops[index].blocks {
+                innermost[block.0] = Some(LoopId(index));
+            }
+        }
+        LoopForest { loops, innermost }
+    }
+
+    /// Every loop, each before the loops nested in it.
+    pub fn loops(&self) -> &[Loop] {
+        &self.loops
+    }
+
+    pub fn get(&self, id: LoopId) -> &Loop {
+        &self.loops[id.0]
+    }
+
+    /// The outermost loops.
+    pub fn roots(&self) -> impl Iterator<Item = LoopId> + '_ {
+        (0..self.loops.len()).map(LoopId).filter(|id| self.get(*id).parent.is_none())
+    }
+
+    /// The innermost loop containing block `id`.
+    pub fn innermost_loop(&self, id: BlockId) -> Option<LoopId> {
+        self.innermost[id.0]
+    }
+
+    /// The number of loops containing block `id`, 0 outside of loops.
+    pub fn depth(&self, id: BlockId) -> usize {
+        self.innermost_loop(id).map_or(0, |innermost| self.get(innermost).depth)
+    }
+}
+
+/// The blocks reaching a latch without passing through the header, and the header itself.
+fn natural_loop(cfg: &Cfg<'_>, header: BlockId, latches: Vec<BlockId>) -> Loop {
+    let mut in_loop = vec![false; cfg.blocks().len()];
+    in_loop[header.0] = true;
+    let mut stack = latches.clone();
+    while let Some(block) = stack.pop() {
+        if !in_loop[block.0] {
+            in_loop[block.0] = true;
+            stack.extend_from_slice(cfg.predecessors(block));
+        }
+    }
+    let blocks = cfg.block_ids().filter(|id| in_loop[id.0]).collect::<Vec<_>>();
+    let mut exits = Vec::new();
+    for successor in blocks.iter().flat_map(|block| cfg.successors(*block)) {
+        if !in_loop[successor.0] && !exits.contains(successor) {
+            exits.push(*successor);
+        }
+    }
+    exits.sort();
+    Loop { header, latches, blocks, exits, parent: None, children: Vec::new(), depth: 1 }
+}
+
+#[cfg(test)]
+mod test_loops {
+    use std::vec::Vec;
+
+    use crate::parser::{cfg::Cfg, function::parse::parse_function};
+
+    use super::BlockId;
+
+    fn ids(ids: &[usize]) -> Vec<BlockId> {
+        ids.iter().copied().map(BlockId::new).collect()
+    }
+
+    #[test]
+    fn nested_loops() {
+        let (_, function) = parse_function(
+            ".func f(.param .b32 a)
+{
+	.reg .pred %p<4>;
+	.reg .b32 %r<3>;
+	ld.param.b32 %r1, [a];
+$L__BB0_1:
+	mov.u32 %r2, 0;
+$L__BB0_2:
+	add.s32 %r2, %r2, 1;
+	setp.lt.s32 %p1, %r2, %r1;
+	@%p1 bra $L__BB0_2;
+	setp.eq.s32 %p2, %r2, 7;
+	@%p2 bra $L__BB0_5;
+	add.s32 %r1, %r1, -1;
+	setp.ne.s32 %p3, %r1, 0;
+	@%p3 bra $L__BB0_1;
+	ret;
+$L__BB0_5:
+	exit;
+}",
+        )
+        .unwrap();
+        let cfg = Cfg::new(&function).unwrap();
+        let forest = cfg.loops();
+        assert_eq!(forest.loops().len(), 2);
+        let roots = forest.roots().collect::<Vec<_>>();
+        assert_eq!(roots.len(), 1);
+        let outer = forest.get(roots[0]);
+        assert_eq!(outer.header(), cfg.block_of_label("L__BB0_1").unwrap());
+        assert_eq!(outer.blocks(), ids(&[1, 2, 3, 4]));
+        assert_eq!(outer.latches(), ids(&[4]));
+        assert_eq!(outer.exits(), ids(&[5, 6]));
+        assert_eq!(outer.depth(), 1);
+        let inner = forest.get(outer.children()[0]);
+        assert_eq!(inner.header(), cfg.block_of_label("L__BB0_2").unwrap());
+        assert_eq!(inner.latches(), [inner.header()]);
+        assert_eq!(inner.exits(), ids(&[3]));
+        assert_eq!(inner.parent(), roots.first().copied());
+        assert_eq!(forest.depth(BlockId::new(2)), 2);
+        assert_eq!(forest.depth(BlockId::new(3)), 1);
+        assert_eq!(forest.depth(BlockId::new(0)), 0);
+        assert_eq!(forest.innermost_loop(BlockId::new(6)), None);
+    }
+}
@@ -0,0 +1,610 @@
+//! The control flow graph of a function body, split into basic blocks.
+//!
+//! ```
+//! use ptx_parser::prelude::*;
+//!
+//! let ptx: PtxParser = "
+//! .version 7.5
+//! .target sm_80
+//! .address_size 64
+//!
+//! .visible .entry kernel(
+//!     .param .u32 kernel_param_0
+//! )
+//! {
+//!     .reg .pred %p<2>;
+//!     .reg .b32 %r<3>;
+//!     ld.param.u32 %r1, [kernel_param_0];
+//! $L__BB0_1:
+//!     add.s32 %r1, %r1, -1;
+//!     setp.ne.s32 %p1, %r1, 0;
+//!     @%p1 bra $L__BB0_1;
+//!     ret;
+//! }
+//! "
+//! .try_into()
+//! .unwrap();
+//!
+//! let Some(Ok((_, FunctionOrGlobal::Function(function)))) = ptx.into_iter().next() else {
+//!     panic!()
+//! };
+//! let cfg = Cfg::new(&function).unwrap();
+//! assert_eq!(cfg.blocks().len(), 3);
+//! let body = cfg.block_of_label("L__BB0_1").unwrap();
+//! assert_eq!(cfg.successors(body), [body, BlockId::new(2)]);
+//! assert_eq!(cfg.exits(), [BlockId::new(2)]);
+//! ```
+
+pub mod dataflow;
+pub mod dominance;
+pub mod liveness;
+pub mod loops;
+pub mod reaching;
+pub mod ssa;
+
+use core::ops::Range;
+use std::{collections::HashMap, vec, vec::Vec};
+
+use crate::parser::{
+    function::{
+        body::{BodyLine, Operation, Predicate},
+        Function,
+    },
+    instruction::Operand,
+    is_special_register,
+};
+
+use dominance::DominatorTree;
+use loops::LoopForest;
+
+/// The index of a basic block in its `Cfg`; the entry block is `BlockId::new(0)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct BlockId(usize);
+
+impl BlockId {
+    pub fn new(index: usize) -> Self {
+        BlockId(index)
+    }
+
+    pub fn index(&self) -> usize {
+        self.0
+    }
+}
+
+/// Where control goes at the end of a block, other than falling through.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Jump<'a> {
+    /// `bra`, to the label without its leading `$`.
+    Branch(&'a str),
+    /// `brx.idx`, to one of the labels of its `.branchtargets` table.
+    Indirect(Vec<&'a str>),
+    /// `ret`
+    Return,
+    /// `exit` or `trap`
+    Exit,
+}
+
+/// The instruction ending a block. A guarded jump falls through when its guard is false.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Terminator<'a> {
+    pub predicate: Option<Predicate<'a>>,
+    pub jump: Jump<'a>,
+}
+
+impl Terminator<'_> {
+    /// Whether control may continue with the next block.
+    pub fn falls_through(&self) -> bool {
+        self.predicate.is_some()
+    }
+
+    /// Whether control may leave the function.
+    pub fn leaves(&self) -> bool {
+        matches!(self.jump, Jump::Return | Jump::Exit)
+    }
+}
+
+/// A run of lines entered only at the top and left only at the bottom.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BasicBlock<'a> {
+    label: Option<&'a str>,
+    lines: Range<usize>,
+    terminator: Option<Terminator<'a>>,
+    successors: Vec<BlockId>,
+    predecessors: Vec<BlockId>,
+}
+
+impl<'a> BasicBlock<'a> {
+    /// The label the block starts with, without its leading `$`.
+    pub fn label(&self) -> Option<&'a str> {
+        self.label
+    }
+
+    /// The indices of the lines of the block in `Cfg::lines`.
+    pub fn lines(&self) -> Range<usize> {
+        self.lines.clone()
+    }
+
+    /// The jump ending the block, or `None` if it falls through into the next one.
+    pub fn terminator(&self) -> Option<&Terminator<'a>> {
+        self.terminator.as_ref()
+    }
+
+    pub fn successors(&self) -> &[BlockId] {
+        &self.successors
+    }
+
+    pub fn predecessors(&self) -> &[BlockId] {
+        &self.predecessors
+    }
+}
+
+/// The basic blocks of a function body and the edges between them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cfg<'a> {
+    lines: Vec<BodyLine<'a>>,
+    blocks: Vec<BasicBlock<'a>>,
+    labels: HashMap<&'a str, BlockId>,
+}
+
+impl<'a> Cfg<'a> {
+    /// The graph of the body of `function`, or `None` for a declaration without a body.
+    /// Lines after the first one which fails to parse are left out.
+    pub fn new(function: &Function<'a>) -> Option<Self> {
+        let (lines, _) = function.body.as_ref()?.parsed();
+        Some(Cfg::from_lines(lines))
+    }
+
+    pub fn from_lines(lines: Vec<BodyLine<'a>>) -> Self {
+        let tables = branch_tables(&lines);
+        let mut blocks = Vec::new();
+        let mut start = 0;
+        let mut label = None;
+        let mut lines_iter = lines.iter().enumerate().peekable();
+        while let Some((index, line)) = lines_iter.next() {
+            if let BodyLine::Label(name) = line {
+                if tables.contains_key(name) {
+                    continue;
+                }
+                if index > start {
+                    blocks.push(BasicBlock::new(label, start..index, None));
+                    start = index;
+                }
+                label = Some(*name);
+                continue;
+            }
+            let Some(terminator) = terminator(line, &tables) else { continue };
+            blocks.push(BasicBlock::new(label.take(), start..index + 1, Some(terminator)));
+            start = index + 1;
+            // The fall through of a guarded jump is a block even when it is empty.
+            let falls_through = blocks.last().and_then(BasicBlock::terminator).is_some_and(Terminator::falls_through);
+            if lines_iter.peek().is_none() && falls_through {
+                blocks.push(BasicBlock::new(None, start..start, None));
+            }
+        }
+        if start < lines.len() || blocks.is_empty() {
+            blocks.push(BasicBlock::new(label, start..lines.len(), None));
+        }
+
+        let labels = blocks
+            .iter()
+            .enumerate()
+            .filter_map(|(index, block)| Some((block.label?, BlockId(index))))
+            .collect::<HashMap<_, _>>();
+        for index in 0..blocks.len() {
+            let successors = successors(&blocks, index, &labels);
+            for successor in &successors {
+                let predecessors = &mut blocks[successor.0].predecessors;
+                if !predecessors.contains(&BlockId(index)) {
+                    predecessors.push(BlockId(index));
+                }
+            }
+            blocks[index].successors = successors;
+        }
+        Cfg { lines, blocks, labels }
+    }
+
+    /// Every line of the body, in order.
+    pub fn lines(&self) -> &[BodyLine<'a>] {
+        &self.lines
+    }
+
+    /// The blocks in the order of the source.
+    pub fn blocks(&self) -> &[BasicBlock<'a>] {
+        &self.blocks
+    }
+
+    pub fn block(&self, id: BlockId) -> &BasicBlock<'a> {
+        &self.blocks[id.0]
+    }
+
+    pub fn block_ids(&self) -> impl Iterator<Item = BlockId> {
+        (0..self.blocks.len()).map(BlockId)
+    }
+
+    /// The lines of block `id`, starting with its label if it has one.
+    pub fn block_lines(&self, id: BlockId) -> &[BodyLine<'a>] {
+        &self.lines[self.block(id).lines()]
+    }
+
+    /// The block starting with `label`, given without its leading `$`.
+    pub fn block_of_label(&self, label: &str) -> Option<BlockId> {
+        self.labels.get(label).copied()
+    }
+
+    pub fn entry(&self) -> BlockId {
+        BlockId(0)
+    }
+
+    /// The blocks control may leave the function from: those ending in `ret` or `exit`,
+    /// and the last block if it runs off the end of the body.
+    pub fn exits(&self) -> Vec<BlockId> {
+        let last = self.blocks.len() - 1;
+        self.block_ids()
+            .filter(|id| match self.block(*id).terminator() {
+                Some(terminator) => terminator.leaves(),
+                None => id.0 == last,
+            })
+            .collect()
+    }
+
+    pub fn successors(&self, id: BlockId) -> &[BlockId] {
+        self.block(id).successors()
+    }
+
+    pub fn predecessors(&self, id: BlockId) -> &[BlockId] {
+        self.block(id).predecessors()
+    }
+
+    pub fn dominators(&self) -> DominatorTree {
+        DominatorTree::dominators(self)
+    }
+
+    pub fn post_dominators(&self) -> DominatorTree {
+        DominatorTree::post_dominators(self)
+    }
+
+    pub fn loops(&self) -> LoopForest {
+        LoopForest::new(self, &self.dominators())
+    }
+
+    /// The blocks reachable from the entry, each before its successors except along
+    /// back edges.
+    pub fn reverse_post_order(&self) -> Vec<BlockId> {
+        let mut order = self.post_order();
+        order.reverse();
+        order
+    }
+
+    /// The blocks reachable from the entry, each after all its successors except along
+    /// back edges.
+    pub fn post_order(&self) -> Vec<BlockId> {
+        let mut visited = vec![false; self.blocks.len()];
+        let mut order = Vec::with_capacity(self.blocks.len());
+        let mut stack = vec![(self.entry(), 0)];
+        visited[0] = true;
+        while let Some((id, next)) = stack.last_mut() {
+            match self.successors(*id).get(*next) {
+                Some(successor) => {
+                    *next += 1;
+                    if !visited[successor.0] {
+                        visited[successor.0] = true;
+                        stack.push((*successor, 0));
+                    }
+                }
+                None => {
+                    order.push(*id);
+                    stack.pop();
+                }
+            }
+        }
+        order
+    }
+}
+
+impl<'a> BasicBlock<'a> {
+    fn new(label: Option<&'a str>, lines: Range<usize>, terminator: Option<Terminator<'a>>) -> Self {
+        BasicBlock { label, lines, terminator, successors: Vec::new(), predecessors: Vec::new() }
+    }
+}
+
+/// The registers a line writes and reads, leaving out special registers such as `%tid.x`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Registers<'a> {
+    pub defs: Vec<&'a str>,
+    pub uses: Vec<&'a str>,
+    /// Whether the line is guarded by a predicate, so its definitions may not happen.
+    pub predicated: bool,
+}
+
+/// Instructions whose first operand is read even when it is a register, as an opcode and
+/// a modifier the instruction must have, if any. `bar.red` and `barrier.red` write their
+/// first operand.
+const NO_DESTINATION: [(&str, Option<&str>); 11] = [
+    ("bar", Some("sync")),
+    ("bar", Some("arrive")),
+    ("bar", Some("warp")),
+    ("barrier", Some("sync")),
+    ("barrier", Some("arrive")),
+    ("bra", None),
+    ("brx", None),
+    ("nanosleep", None),
+    ("pmevent", None),
+    ("red", None),
+    ("st", None),
+];
+
+impl<'a> Registers<'a> {
+    /// The registers of `line`; those of a call sequence are the arguments it stores and
+    /// the return values it loads.
+    pub fn of(line: &BodyLine<'a>) -> Self {
+        match line {
+            BodyLine::Operation(operation) => Registers::of_operation(operation),
+            BodyLine::Goto(goto) => Registers {
+                uses: goto.predicate().map(Predicate::register).into_iter().collect(),
+                ..Registers::default()
+            },
+            BodyLine::Guarded(predicate, operation) => {
+                let mut registers = Registers::of_operation(operation);
+                registers.uses.insert(0, predicate.register());
+                registers.predicated = true;
+                registers
+            }
+            BodyLine::FunctionCall(call) => {
+                let mut registers = Registers::default();
+                for (_, line) in call.lines().map_while(Result::ok) {
+                    let Registers { defs, uses, .. } = Registers::of(&line);
+                    registers.defs.extend(defs);
+                    registers.uses.extend(uses);
+                }
+                registers
+            }
+            _ => Registers::default(),
+        }
+    }
+
+    fn of_operation(operation: &Operation<'a>) -> Self {
+        let mut registers = Registers::default();
+        if operation.operation().starts_with('.') {
+            return registers;
+        }
+        let writes = !NO_DESTINATION.iter().any(|(opcode, modifier)| {
+            *opcode == operation.opcode() && modifier.is_none_or(|modifier| operation.modifiers().any(|m| m == modifier))
+        });
+        for (index, operand) in operation.operands().unwrap_or_default().iter().enumerate() {
+            match operand {
+                Operand::Register(_) | Operand::Vector(_) | Operand::Pair(..) if index == 0 && writes => {
+                    operand_registers(operand, &mut registers.defs)
+                }
+                _ => operand_registers(operand, &mut registers.uses),
+            }
+        }
+        registers
+    }
+}
+
+/// Pushes the registers named by `operand`, including the base of an address.
+fn operand_registers<'a>(operand: &Operand<'a>, registers: &mut Vec<&'a str>) {
+    let names = match operand {
+        Operand::Register(name) => vec![*name],
+        Operand::Pair(first, second) => vec![*first, *second],
+        Operand::Vector(names) => names.clone(),
+        Operand::Address(address) if address.contains([',', '{']) => {
+            for operand in operand.address_operands().unwrap_or_default() {
+                operand_registers(&operand, registers);
+            }
+            return;
+        }
+        Operand::Address(address) => address.split(['+', '-']).next().into_iter().collect(),
+        _ => Vec::new(),
+    };
+    registers.extend(
+        names
+            .into_iter()
+            .map(|name| name.trim().trim_start_matches('!'))
+            .filter(|name| name.starts_with('%') && !is_special_register(name)),
+    );
+}
+
+/// The targets of the jump ending block `index`, then the next block if control falls
+/// through; labels which do not start a block are left out.
+fn successors(blocks: &[BasicBlock<'_>], index: usize, labels: &HashMap<&str, BlockId>) -> Vec<BlockId> {
+    let next = (index + 1 < blocks.len()).then_some(BlockId(index + 1));
+    let Some(terminator) = &blocks[index].terminator else {
+        return next.into_iter().collect();
+    };
+    let targets = match &terminator.jump {
+        Jump::Branch(label) => vec![*label],
+        Jump::Indirect(targets) => targets.clone(),
+        Jump::Return | Jump::Exit => Vec::new(),
+    };
+    let mut successors = Vec::new();
+    let fall_through = next.filter(|_| terminator.falls_through());
+    for id in targets.into_iter().filter_map(|label| labels.get(label).copied()).chain(fall_through) {
+        if !successors.contains(&id) {
+            successors.push(id);
+        }
+    }
+    successors
+}
+
+/// The `.branchtargets` tables of a body by label, e.g. `$ts: .branchtargets $L0, $L1;`.
+fn branch_tables<'a>(lines: &[BodyLine<'a>]) -> HashMap<&'a str, Vec<&'a str>> {
+    lines
+        .windows(2)
+        .filter_map(|pair| match pair {
+            [BodyLine::Label(label), BodyLine::Operation(operation)] if operation.operation() == ".branchtargets" => {
+                Some((*label, labels(operation)))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// The labels among the operands of `operation`, without their leading `$`.
+fn labels<'a>(operation: &Operation<'a>) -> Vec<&'a str> {
+    operation
+        .operands()
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|operand| match operand {
+            Operand::Symbol(symbol) => symbol.strip_prefix('$'),
+            _ => None,
+        })
+        .collect()
+}
+
+/// The jump `line` ends its block with, if any.
+fn terminator<'a>(line: &BodyLine<'a>, tables: &HashMap<&'a str, Vec<&'a str>>) -> Option<Terminator<'a>> {
+    let (predicate, operation) = match line {
+        BodyLine::Goto(goto) => {
+            return Some(Terminator { predicate: goto.predicate().cloned(), jump: Jump::Branch(goto.label()) });
+        }
+        BodyLine::Return => return Some(Terminator { predicate: None, jump: Jump::Return }),
+        BodyLine::Operation(operation) => (None, operation.clone()),
+        BodyLine::Guarded(predicate, operation) => (Some(predicate.clone()), operation.clone()),
+        _ => return None,
+    };
+    let jump = match operation.opcode() {
+        "bra" => Jump::Branch(labels(&operation).first()?),
+        "brx" => {
+            let table = labels(&operation);
+            Jump::Indirect(table.last().and_then(|table| tables.get(table)).cloned().unwrap_or_default())
+        }
+        "ret" => Jump::Return,
+        "exit" | "trap" => Jump::Exit,
+        _ => return None,
+    };
+    Some(Terminator { predicate, jump })
+}
+
+#[cfg(test)]
+mod test_cfg {
+    use std::vec::Vec;
+
+    use crate::parser::function::{body::BodyLine, parse::parse_function};
+
+    use super::{BlockId, Cfg, Jump};
+
+    /// A loop summing into `%f4`, which is reused as an accumulator as `rustc` emits it,
+    /// and which a predicated `mov` may overwrite after the loop. Shared by the tests of
+    /// the analyses.
+    pub(super) const ACCUMULATE: &str = ".func f(.param .b32 a)
+{
+	.reg .pred %p<2>;
+	.reg .b32 %r<3>;
+	.reg .f32 %f<5>;
+	ld.param.b32 %r1, [a];
+	mov.f32 %f4, 0f00000000;
+	mov.u32 %r2, 0;
+$L__BB0_1:
+	cvt.rn.f32.s32 %f3, %r2;
+	add.f32 %f4, %f4, %f3;
+	add.s32 %r2, %r2, 1;
+	setp.lt.s32 %p1, %r2, %r1;
+	@%p1 bra $L__BB0_1;
+	@%p1 mov.f32 %f4, %f3;
+	st.global.f32 [%r1], %f4;
+	ret;
+}";
+
+    fn cfg(body: &str) -> Cfg<'_> {
+        let (_, function) = parse_function(body.trim()).unwrap();
+        Cfg::new(&function).unwrap()
+    }
+
+    fn edges(cfg: &Cfg<'_>) -> Vec<Vec<usize>> {
+        cfg.block_ids()
+            .map(|id| cfg.successors(id).iter().map(BlockId::index).collect())
+            .collect()
+    }
+
+    #[test]
+    fn straight_line() {
+        let cfg = cfg(".func f(.param .b32 a)\n{\n\t.reg .b32 %r<2>;\n\tld.param.b32 %r1, [a];\n\tret;\n}");
+        assert_eq!(cfg.blocks().len(), 1);
+        assert_eq!(cfg.block_lines(cfg.entry()).len(), 3);
+        assert_eq!(cfg.exits(), [cfg.entry()]);
+        assert_eq!(cfg.reverse_post_order(), [cfg.entry()]);
+    }
+
+    #[test]
+    fn branches() {
+        let cfg = cfg("
+.func f(.param .b32 a)
+{
+	.reg .pred %p<3>;
+	.reg .b32 %r<2>;
+	ld.param.b32 %r1, [a];
+	setp.eq.s32 %p1, %r1, 0;
+	@%p1 bra $L__BB0_3;
+$L__BB0_1:
+	setp.eq.s32 %p2, %r1, 1;
+	@!%p2 bra.uni $L__BB0_4;
+	bra $L__BB0_1;
+$L__BB0_3:
+	@%p1 exit;
+	bra.uni $L__BB0_1;
+$L__BB0_4:
+	ret;
+}");
+        assert_eq!(edges(&cfg), [vec![3, 1], vec![5, 2], vec![1], vec![4], vec![1], vec![]]);
+        assert_eq!(cfg.block(BlockId::new(1)).label(), Some("L__BB0_1"));
+        assert_eq!(cfg.block(BlockId::new(2)).terminator().map(|terminator| &terminator.jump), Some(&Jump::Branch("L__BB0_1")));
+        assert_eq!(cfg.predecessors(BlockId::new(1)), [BlockId::new(0), BlockId::new(2), BlockId::new(4)]);
+        assert_eq!(cfg.exits(), [BlockId::new(3), BlockId::new(5)]);
+        let order = cfg.reverse_post_order();
+        assert_eq!(order[0], cfg.entry());
+        assert_eq!(order.len(), 6);
+        assert!(matches!(cfg.block_lines(BlockId::new(3))[0], BodyLine::Label("L__BB0_3")));
+    }
+
+    #[test]
+    fn indirect_branches() {
+        let cfg = cfg("
+.func f(.param .b32 a)
+{
+	.reg .b32 %r<2>;
+	ld.param.b32 %r1, [a];
+$ts: .branchtargets $L0, $L1;
+	brx.idx %r1, $ts;
+$L0:
+	exit;
+$L1:
+	ret;
+}");
+        assert_eq!(edges(&cfg), [vec![1, 2], vec![], vec![]]);
+        assert_eq!(cfg.block_of_label("ts"), None);
+        assert_eq!(cfg.exits(), [BlockId::new(1), BlockId::new(2)]);
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg(test)]
+mod test_cfg_files {
+    use crate::{parser::PtxFile, ptx_files};
+
+    use super::Cfg;
+
+    #[test]
+    fn every_block_is_reachable() {
+        for source in [ptx_files::a::_PTX, ptx_files::b::_PTX, ptx_files::c::_PTX, ptx_files::kernel::_PTX, ptx_files::atomics::_PTX] {
+            let file = PtxFile::try_from(source).unwrap();
+            for function in file.functions() {
+                let Some(cfg) = Cfg::new(function) else { continue };
+                assert_eq!(cfg.reverse_post_order().len(), cfg.blocks().len());
+                for id in cfg.block_ids() {
+                    for successor in cfg.successors(id) {
+                        assert!(cfg.predecessors(*successor).contains(&id));
+                    }
+                }
+                if function.signature.entry {
+                    assert!(!cfg.exits().is_empty());
+                }
+                let dominators = cfg.dominators();
+                for found in cfg.loops().loops() {
+                    assert!(found.blocks().iter().all(|block| dominators.dominates(found.header(), *block)));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,223 @@
+use std::{collections::BTreeSet, vec, vec::Vec};
+
+use super::{
+    dataflow::{Analysis, Direction, Solution},
+    BlockId, Cfg, Registers,
+};
+use crate::parser::function::body::BodyLine;
+
+/// Identifies a definition of a `ReachingDefinitions`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DefinitionId(usize);
+
+impl DefinitionId {
+    pub fn index(&self) -> usize {
+        self.0
+    }
+}
+
+/// A write of a register by a line of a `Cfg`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Definition<'a> {
+    pub register: &'a str,
+    /// The index of the line in `Cfg::lines`.
+    pub line: usize,
+    /// Whether the line is guarded by a predicate, so the write may not happen.
+    pub predicated: bool,
+}
+
+/// The definitions which may reach each line of a `Cfg`: those followed by a path to
+/// the line on which the register is not written again.
+///
+/// A predicated definition does not hide the definitions before it, since the write
+/// may not happen.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReachingDefinitions<'a> {
+    definitions: Vec<Definition<'a>>,
+    solution: Solution<BTreeSet<DefinitionId>>,
+}
+
+impl<'a> ReachingDefinitions<'a> {
+    pub fn new(cfg: &Cfg<'a>) -> Self {
+        let mut definitions = Vec::new();
+        let mut line_definitions = vec![Vec::new(); cfg.lines().len()];
+        for (line, registers) in cfg.lines().iter().map(Registers::of).enumerate() {
+            for register in registers.defs {
+                line_definitions[line].push(DefinitionId(definitions.len()));
+                definitions.push(Definition { register, line, predicated: registers.predicated });
+            }
+        }
+        let solution = Reaching { definitions: &definitions, line_definitions }.solve(cfg);
+        ReachingDefinitions { definitions, solution }
+    }
+
+    /// Every definition, in the order of the lines.
+    pub fn definitions(&self) -> &[Definition<'a>] {
+        &self.definitions
+    }
+
+    pub fn definition(&self, id: DefinitionId) -> &Definition<'a> {
+        &self.definitions[id.0]
+    }
+
+    /// The definitions reaching the start of block `id`.
+    pub fn reaching_in(&self, id: BlockId) -> &BTreeSet<DefinitionId> {
+        self.solution.start(id)
+    }
+
+    /// The definitions reaching the end of block `id`.
+    pub fn reaching_out(&self, id: BlockId) -> &BTreeSet<DefinitionId> {
+        self.solution.end(id)
+    }
+
+    /// The definitions reaching line `index` of `Cfg::lines`, before it writes anything.
+    pub fn reaching_before(&self, index: usize) -> &BTreeSet<DefinitionId> {
+        self.solution.before(index)
+    }
+
+    /// The definitions of `register` reaching line `index` of `Cfg::lines`.
+    pub fn reaching<'s>(&'s self, index: usize, register: &'s str) -> impl Iterator<Item = DefinitionId> + 's {
+        self.reaching_before(index).iter().copied().filter(move |id| self.definitions[id.0].register == register)
+    }
+}
+
+/// The analysis behind `ReachingDefinitions`, with the definitions of each line.
+struct Reaching<'r, 'a> {
+    definitions: &'r [Definition<'a>],
+    line_definitions: Vec<Vec<DefinitionId>>,
+}
+
+impl<'a> Analysis<'a> for Reaching<'_, 'a> {
+    type Domain = BTreeSet<DefinitionId>;
+    const DIRECTION: Direction = Direction::Forward;
+
+    fn bottom(&self, _: &Cfg<'a>) -> Self::Domain {
+        BTreeSet::new()
+    }
+
+    fn transfer(&self, index: usize, _: &BodyLine<'a>, reaching: &mut Self::Domain) {
+        for id in &self.line_definitions[index] {
+            let definition = self.definitions[id.0];
+            if !definition.predicated {
+                reaching.retain(|other| self.definitions[other.0].register != definition.register);
+            }
+            reaching.insert(*id);
+        }
+    }
+}
+
+/// Identifies a use of a `DefUseChains`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct UseId(usize);
+
+impl UseId {
+    pub fn index(&self) -> usize {
+        self.0
+    }
+}
+
+/// A read of a register by a line of a `Cfg`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Use<'a> {
+    pub register: &'a str,
+    /// The index of the line in `Cfg::lines`.
+    pub line: usize,
+}
+
+/// Links every use of a register to the definitions it may read, and every definition to
+/// the uses which may read it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DefUseChains<'a> {
+    reaching: ReachingDefinitions<'a>,
+    uses: Vec<Use<'a>>,
+    use_definitions: Vec<Vec<DefinitionId>>,
+    definition_uses: Vec<Vec<UseId>>,
+}
+
+impl<'a> DefUseChains<'a> {
+    pub fn new(cfg: &Cfg<'a>) -> Self {
+        let reaching = ReachingDefinitions::new(cfg);
+        let mut chains = DefUseChains {
+            uses: Vec::new(),
+            use_definitions: Vec::new(),
+            definition_uses: vec![Vec::new(); reaching.definitions.len()],
+            reaching,
+        };
+        for (line, registers) in cfg.lines().iter().map(Registers::of).enumerate() {
+            for register in registers.uses {
+                let id = UseId(chains.uses.len());
+                let definitions = chains.reaching.reaching(line, register).collect::<Vec<_>>();
+                for definition in &definitions {
+                    chains.definition_uses[definition.0].push(id);
+                }
+                chains.uses.push(Use { register, line });
+                chains.use_definitions.push(definitions);
+            }
+        }
+        chains
+    }
+
+    pub fn reaching_definitions(&self) -> &ReachingDefinitions<'a> {
+        &self.reaching
+    }
+
+    /// Every use, in the order of the lines.
+    pub fn uses(&self) -> &[Use<'a>] {
+        &self.uses
+    }
+
+    pub fn get(&self, id: UseId) -> &Use<'a> {
+        &self.uses[id.0]
+    }
+
+    /// The definitions use `id` may read; none if no write of the register reaches it.
+    pub fn definitions_of(&self, id: UseId) -> &[DefinitionId] {
+        &self.use_definitions[id.0]
+    }
+
+    /// The uses which may read definition `id`; none if the value is dead.
+    pub fn uses_of(&self, id: DefinitionId) -> &[UseId] {
+        &self.definition_uses[id.0]
+    }
+}
+
+#[cfg(test)]
+mod test_reaching {
+    use std::vec::Vec;
+
+    use crate::parser::{
+        cfg::{test_cfg::ACCUMULATE, Cfg},
+        function::parse::parse_function,
+    };
+
+    use super::{DefUseChains, ReachingDefinitions};
+
+    #[test]
+    fn reaching_definitions() {
+        let (_, function) = parse_function(ACCUMULATE).unwrap();
+        let cfg = Cfg::new(&function).unwrap();
+        let reaching = ReachingDefinitions::new(&cfg);
+        let lines = |ids: Vec<_>| ids.into_iter().map(|id| reaching.definition(id).line).collect::<Vec<_>>();
+        let add = cfg.block_of_label("L__BB0_1").map(|body| cfg.block(body).lines().start + 2).unwrap();
+        assert_eq!(lines(reaching.reaching(add, "%f4").collect()), [4, add]);
+        let store = cfg.lines().len() - 2;
+        assert_eq!(lines(reaching.reaching(store, "%f4").collect()), [add, store - 1]);
+        assert!(reaching.definitions()[..3].iter().all(|definition| !definition.predicated));
+    }
+
+    #[test]
+    fn chains() {
+        let (_, function) = parse_function(ACCUMULATE).unwrap();
+        let cfg = Cfg::new(&function).unwrap();
+        let chains = DefUseChains::new(&cfg);
+        let definitions = chains.reaching_definitions().definitions();
+        let accumulate = definitions.iter().position(|definition| definition.register == "%f4" && definition.line > 4).unwrap();
+        let readers = chains.uses_of(super::DefinitionId(accumulate)).iter().map(|id| chains.get(*id).line).collect::<Vec<_>>();
+        let add = definitions[accumulate].line;
+        assert_eq!(readers, [add, cfg.lines().len() - 2], "the next iteration and the store");
+        let store = chains.uses().iter().position(|used| used.register == "%f4" && used.line > add).unwrap();
+        assert_eq!(chains.definitions_of(super::UseId(store)).len(), 2);
+        let unset = chains.uses().iter().position(|used| used.register == "%r1").unwrap();
+        assert_eq!(chains.definitions_of(super::UseId(unset)).len(), 1);
+    }
+}
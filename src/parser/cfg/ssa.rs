@@ -0,0 +1,248 @@
+use core::fmt;
+use std::{collections::HashMap, vec, vec::Vec};
+
+use super::{dominance::DominatorTree, liveness::Liveness, BlockId, Cfg, Registers};
+
+/// A version of a register in an `Ssa` view. Version 0 is the value the register holds
+/// when the function starts, which no line of the body wrote.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SsaName<'a> {
+    pub register: &'a str,
+    pub version: usize,
+}
+
+/// Formats as `%f4.2`.
+impl fmt::Display for SsaName<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.register, self.version)
+    }
+}
+
+/// A join of the versions of a register reaching a block from its predecessors.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Phi<'a> {
+    pub target: SsaName<'a>,
+    /// The version coming from each predecessor, in the order of `Cfg::predecessors`.
+    /// Predecessors the entry does not reach give version 0.
+    pub arguments: Vec<(BlockId, SsaName<'a>)>,
+}
+
+/// The lines of a `Cfg` in static single assignment form: every write of a register gets
+/// a version of its own, and a `Phi` joins the versions reaching a block where they differ.
+///
+/// The lines themselves are left as they are; the view gives the versions each line reads
+/// and writes. Phis are only placed where the register is live. A predicated line also
+/// reads the versions it may overwrite, as they survive when its guard is false. Blocks the
+/// entry does not reach have no versions, and version 0 flows out of them into phis.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ssa<'a> {
+    phis: Vec<Vec<Phi<'a>>>,
+    defs: Vec<Vec<SsaName<'a>>>,
+    uses: Vec<Vec<SsaName<'a>>>,
+}
+
+impl<'a> Ssa<'a> {
+    pub fn new(cfg: &Cfg<'a>) -> Self {
+        let registers = cfg.lines().iter().map(Registers::of).collect::<Vec<_>>();
+        let dominators = DominatorTree::dominators(cfg);
+        let liveness = Liveness::new(cfg);
+
+        // The blocks writing each register, in the order of the source.
+        let mut definition_blocks = HashMap::<&'a str, Vec<BlockId>>::new();
+        let mut order = Vec::new();
+        for id in cfg.block_ids() {
+            for register in cfg.block(id).lines().flat_map(|line| registers[line].defs.iter()) {
+                let blocks = definition_blocks.entry(register).or_insert_with(|| {
+                    order.push(*register);
+                    Vec::new()
+                });
+                if blocks.last() != Some(&id) {
+                    blocks.push(id);
+                }
+            }
+        }
+
+        // Phis go at the iterated dominance frontier of the writes.
+        let mut ssa = Ssa {
+            phis: vec![Vec::new(); cfg.blocks().len()],
+            defs: vec![Vec::new(); cfg.lines().len()],
+            uses: vec![Vec::new(); cfg.lines().len()],
+        };
+        for register in order {
+            let mut pending = definition_blocks[register].clone();
+            let mut placed = vec![false; cfg.blocks().len()];
+            while let Some(block) = pending.pop() {
+                for &frontier in dominators.frontier(block) {
+                    if !placed[frontier.0] && liveness.live_in(frontier).contains(register) {
+                        placed[frontier.0] = true;
+                        let target = SsaName { register, version: 0 };
+                        ssa.phis[frontier.0].push(Phi { target, arguments: Vec::new() });
+                        pending.push(frontier);
+                    }
+                }
+            }
+        }
+
+        let mut renamer = Renamer { cfg, registers: &registers, dominators: &dominators, versions: HashMap::new(), stacks: HashMap::new() };
+        renamer.rename(cfg.entry(), &mut ssa);
+        for (block, phis) in ssa.phis.iter_mut().enumerate() {
+            for phi in phis {
+                let register = phi.target.register;
+                phi.arguments = cfg
+                    .predecessors(BlockId(block))
+                    .iter()
+                    .map(|&predecessor| match phi.arguments.iter().find(|(from, _)| *from == predecessor) {
+                        Some(&argument) => argument,
+                        None => (predecessor, SsaName { register, version: 0 }),
+                    })
+                    .collect();
+            }
+        }
+        ssa
+    }
+
+    /// The phis at the start of block `id`.
+    pub fn phis(&self, id: BlockId) -> &[Phi<'a>] {
+        &self.phis[id.0]
+    }
+
+    /// The versions line `index` of `Cfg::lines` writes, in the order of `Registers::defs`.
+    pub fn defs(&self, index: usize) -> &[SsaName<'a>] {
+        &self.defs[index]
+    }
+
+    /// The versions line `index` of `Cfg::lines` reads, in the order of `Registers::uses`,
+    /// followed for a predicated line by those it may overwrite.
+    pub fn uses(&self, index: usize) -> &[SsaName<'a>] {
+        &self.uses[index]
+    }
+}
+
+/// Numbers the versions by walking the dominator tree, as in "Efficiently Computing Static
+/// Single Assignment Form and the Control Dependence Graph" by Cytron et al.
+struct Renamer<'c, 'a> {
+    cfg: &'c Cfg<'a>,
+    registers: &'c [Registers<'a>],
+    dominators: &'c DominatorTree,
+    /// The last version given to each register.
+    versions: HashMap<&'a str, usize>,
+    /// The versions in scope, innermost last.
+    stacks: HashMap<&'a str, Vec<usize>>,
+}
+
+impl<'a> Renamer<'_, 'a> {
+    fn current(&self, register: &'a str) -> SsaName<'a> {
+        let version = self.stacks.get(register).and_then(|stack| stack.last()).copied().unwrap_or(0);
+        SsaName { register, version }
+    }
+
+    fn define(&mut self, register: &'a str, defined: &mut Vec<&'a str>) -> SsaName<'a> {
+        let version = self.versions.entry(register).or_insert(0);
+        *version += 1;
+        self.stacks.entry(register).or_default().push(*version);
+        defined.push(register);
+        SsaName { register, version: *version }
+    }
+
+    fn rename(&mut self, block: BlockId, ssa: &mut Ssa<'a>) {
+        let mut defined = Vec::new();
+        for phi in 0..ssa.phis[block.0].len() {
+            let register = ssa.phis[block.0][phi].target.register;
+            ssa.phis[block.0][phi].target = self.define(register, &mut defined);
+        }
+        for line in self.cfg.block(block).lines() {
+            let registers = &self.registers[line];
+            let mut uses = registers.uses.iter().map(|register| self.current(register)).collect::<Vec<_>>();
+            if registers.predicated {
+                uses.extend(registers.defs.iter().map(|register| self.current(register)));
+            }
+            ssa.uses[line] = uses;
+            ssa.defs[line] = registers.defs.iter().map(|register| self.define(register, &mut defined)).collect();
+        }
+        for &successor in self.cfg.successors(block) {
+            for phi in &mut ssa.phis[successor.0] {
+                let argument = self.current(phi.target.register);
+                phi.arguments.push((block, argument));
+            }
+        }
+        for &child in self.dominators.children(block) {
+            self.rename(child, ssa);
+        }
+        for register in defined {
+            self.stacks.get_mut(register).and_then(Vec::pop);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_ssa {
+    use std::{string::ToString, vec::Vec};
+
+    use crate::parser::{
+        cfg::{test_cfg::ACCUMULATE, Cfg},
+        function::parse::parse_function,
+    };
+
+    use super::Ssa;
+
+    #[test]
+    fn versions_and_phis() {
+        let (_, function) = parse_function(ACCUMULATE).unwrap();
+        let cfg = Cfg::new(&function).unwrap();
+        let ssa = Ssa::new(&cfg);
+        let names = |names: &[super::SsaName<'_>]| names.iter().map(ToString::to_string).collect::<Vec<_>>();
+        let body = cfg.block_of_label("L__BB0_1").unwrap();
+        let phis = ssa.phis(body).iter().map(|phi| (phi.target.to_string(), names(&phi.arguments.iter().map(|(_, name)| *name).collect::<Vec<_>>()))).collect::<Vec<_>>();
+        assert_eq!(
+            phis,
+            [
+                ("%f4.2".to_string(), vec!["%f4.1".to_string(), "%f4.3".to_string()]),
+                ("%r2.2".to_string(), vec!["%r2.1".to_string(), "%r2.3".to_string()]),
+            ]
+        );
+        assert!(ssa.phis(cfg.entry()).is_empty());
+        let add = cfg.block(body).lines().start + 2;
+        assert_eq!(names(ssa.defs(add)), ["%f4.3"]);
+        assert_eq!(names(ssa.uses(add)), ["%f4.2", "%f3.1"]);
+        let guarded = cfg.lines().len() - 3;
+        assert_eq!(names(ssa.defs(guarded)), ["%f4.4"]);
+        assert_eq!(names(ssa.uses(guarded)), ["%p1.1", "%f3.1", "%f4.3"]);
+        assert_eq!(names(ssa.uses(guarded + 1)), ["%r1.1", "%f4.4"]);
+    }
+
+    #[test]
+    fn unreached_predecessors() {
+        let (_, function) = parse_function(
+            ".func f(.param .b32 a)
+{
+	.reg .pred %p<2>;
+	.reg .b32 %r<3>;
+	ld.param.b32 %r1, [a];
+	setp.eq.s32 %p1, %r1, 0;
+	@%p1 bra $L__BB0_2;
+	mov.u32 %r2, 1;
+	bra.uni $L__BB0_3;
+$L__BB0_1:
+	mov.u32 %r2, 2;
+	bra.uni $L__BB0_3;
+$L__BB0_2:
+	mov.u32 %r2, 3;
+$L__BB0_3:
+	st.global.u32 [%r1], %r2;
+	ret;
+}",
+        )
+        .unwrap();
+        let cfg = Cfg::new(&function).unwrap();
+        let ssa = Ssa::new(&cfg);
+        let join = cfg.block_of_label("L__BB0_3").unwrap();
+        let unreached = cfg.block_of_label("L__BB0_1").unwrap();
+        let [phi] = ssa.phis(join) else { panic!("{:?}", ssa.phis(join)) };
+        let from = phi.arguments.iter().map(|(from, _)| *from).collect::<Vec<_>>();
+        assert_eq!(from, cfg.predecessors(join));
+        let arguments = phi.arguments.iter().map(|(from, name)| (*from == unreached, name.to_string())).collect::<Vec<_>>();
+        assert_eq!(arguments, [(false, "%r2.1".to_string()), (true, "%r2.0".to_string()), (false, "%r2.2".to_string())]);
+        assert_eq!(phi.target.to_string(), "%r2.3");
+        assert!(ssa.defs(cfg.block(unreached).lines().start + 1).is_empty());
+    }
+}
@@ -1,11 +1,20 @@
 pub(crate) mod parse;
 
-#[derive(Debug, PartialEq)]
-pub(crate) enum Comment<'a> {
+/// A `// line` or `/* block */` comment, without its delimiters.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Comment<'a> {
     Line(&'a str),
     Block(&'a str),
 }
 
+impl<'a> Comment<'a> {
+    pub fn text(&self) -> &'a str {
+        match self {
+            Comment::Line(text) | Comment::Block(text) => text,
+        }
+    }
+}
+
 #[cfg(test)]
 mod test_parse_line_comment {
     use crate::parser::comment::{Comment, parse::parse_line_comment};
@@ -24,7 +24,7 @@ pub(crate) fn comment_or_whitespace(input: &str) -> IResult<&str, &str> {
     ))(input)
 }
 
-pub(crate) fn parse_line_comment(input: &str) -> IResult<&str, Comment> {
+pub(crate) fn parse_line_comment(input: &str) -> IResult<&str, Comment<'_>> {
     preceded(
         char('/'),
         alt((
@@ -0,0 +1,406 @@
+use core::{fmt, mem};
+
+use std::borrow::Cow;
+
+use nom::IResult;
+
+use super::{
+    function::{body::{parse_body_line, BodyLine}, parse::parse_function, Function},
+    global::{parse::parse_global, Global},
+};
+
+pub(crate) mod parse;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum NodeKind {
+    /// The whole file.
+    Module,
+    Version,
+    Target,
+    AddressSize,
+    /// A function or kernel, made of a `Signature` and either a `Body` or a `Semicolon`.
+    Function,
+    Signature,
+    /// The braces of a function and the `Line`s between them.
+    Body,
+    OpenBrace,
+    CloseBrace,
+    /// The `;` ending a function declaration or an instruction.
+    Semicolon,
+    /// A line of a function body, as parsed by `FunctionBody`. An instruction is split
+    /// into `Predicate`, `Opcode`, `Operand`, `Comma` and `Semicolon` tokens.
+    Line,
+    /// The guard of an instruction, such as `@%p1` or `@!%p1`.
+    Predicate,
+    /// The name of an instruction with its modifiers, such as `ld.param.u64`.
+    Opcode,
+    Operand,
+    Comma,
+    Global,
+    File,
+    Section,
+    /// Text which none of the parsers recognized.
+    Unknown,
+}
+
+/// Text between nodes which does not affect the meaning of the file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Trivia<'a> {
+    /// A run of whitespace, including newlines.
+    Whitespace(&'a str),
+    /// A `// line` or `/* block */` comment, with its delimiters.
+    Comment(&'a str),
+}
+
+impl<'a> Trivia<'a> {
+    pub fn text(&self) -> &'a str {
+        match self {
+            Trivia::Whitespace(text) | Trivia::Comment(text) => text,
+        }
+    }
+}
+
+/// A node of the lossless syntax tree.
+///
+/// A node owns the trivia before it on its own lines as `leading_trivia`, and the
+/// whitespace and comments after it up to the end of its last line as `trailing_trivia`.
+/// The tokens of an instruction own the whitespace and comments after them instead.
+/// The text of a node with children is exactly the concatenation of its children, and
+/// is kept so by the methods which edit them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Node<'a> {
+    kind: NodeKind,
+    leading: Vec<Trivia<'a>>,
+    text: Cow<'a, str>,
+    children: Vec<Node<'a>>,
+    trailing: Vec<Trivia<'a>>,
+}
+
+impl<'a> Node<'a> {
+    /// A node without children or trivia, such as an `Operand` to replace another with.
+    pub fn new(kind: NodeKind, text: &'a str) -> Self {
+        Node { kind, leading: Vec::new(), text: Cow::Borrowed(text), children: Vec::new(), trailing: Vec::new() }
+    }
+
+    /// Parses `text` as one line of a function body, without trivia around it.
+    pub fn line(text: &'a str) -> Option<Self> {
+        parse::line_node(text.trim())
+    }
+
+    /// Gives the node the trivia before and after it.
+    pub fn with_trivia(mut self, leading: Vec<Trivia<'a>>, trailing: Vec<Trivia<'a>>) -> Self {
+        self.leading = leading;
+        self.trailing = trailing;
+        self
+    }
+
+    pub fn kind(&self) -> NodeKind {
+        self.kind
+    }
+
+    /// The source of the node without its leading and trailing trivia.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn children(&self) -> &[Node<'a>] {
+        &self.children
+    }
+
+    pub fn leading_trivia(&self) -> &[Trivia<'a>] {
+        &self.leading
+    }
+
+    pub fn trailing_trivia(&self) -> &[Trivia<'a>] {
+        &self.trailing
+    }
+
+    /// This node and every node below it, in source order.
+    pub fn descendants(&self) -> Descendants<'_, 'a> {
+        Descendants { stack: Vec::from([self]) }
+    }
+
+    /// Every piece of trivia in this node and its descendants, in source order.
+    pub fn trivia(&self) -> Vec<Trivia<'a>> {
+        let mut trivia = self.leading.clone();
+        for child in &self.children {
+            trivia.extend(child.trivia());
+        }
+        trivia.extend(&self.trailing);
+        trivia
+    }
+
+    /// Every comment in this node and its descendants, in source order.
+    pub fn comments(&self) -> impl Iterator<Item = &'a str> {
+        self.trivia().into_iter().filter_map(|trivia| match trivia {
+            Trivia::Comment(comment) => Some(comment),
+            Trivia::Whitespace(_) => None,
+        })
+    }
+
+    /// Parses a `Line` node into the typed body line.
+    pub fn body_line(&self) -> Option<BodyLine<'_>> {
+        self.parse_as(NodeKind::Line, parse_body_line)
+    }
+
+    /// Parses a `Function` node into the typed function.
+    pub fn function(&self) -> Option<Function<'_>> {
+        self.parse_as(NodeKind::Function, parse_function)
+    }
+
+    /// Parses a `Global` node into the typed global.
+    pub fn global(&self) -> Option<Global<'_>> {
+        self.parse_as(NodeKind::Global, parse_global)
+    }
+
+    fn parse_as<'n, T>(&'n self, kind: NodeKind, parser: fn(&'n str) -> IResult<&'n str, T>) -> Option<T> {
+        if self.kind != kind {
+            return None;
+        }
+        parser(&self.text).ok().map(|(_, value)| value)
+    }
+
+    /// Replaces the child at `index` with `node`, which takes over the trivia around the
+    /// replaced child, and returns the replaced child without it.
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn replace_child(&mut self, index: usize, mut node: Node<'a>) -> Node<'a> {
+        let child = &mut self.children[index];
+        mem::swap(&mut node.leading, &mut child.leading);
+        mem::swap(&mut node.trailing, &mut child.trailing);
+        let replaced = mem::replace(child, node);
+        self.update_text();
+        replaced
+    }
+
+    /// Inserts `node` before the child at `index`. A node without leading trivia gets
+    /// the line break and indentation before the nearest sibling which has one, so that
+    /// an inserted line goes on a line of its own.
+    ///
+    /// Panics if `index` is greater than the number of children.
+    pub fn insert_child(&mut self, index: usize, mut node: Node<'a>) {
+        if node.leading.is_empty() {
+            let siblings = self.children[..index].iter().rev().chain(&self.children[index..]);
+            node.leading = siblings
+                .filter_map(|sibling| sibling.leading.last())
+                .find(|trivia| matches!(trivia, Trivia::Whitespace(_)))
+                .into_iter()
+                .copied()
+                .collect();
+        }
+        self.children.insert(index, node);
+        self.update_text();
+    }
+
+    /// Removes the child at `index` together with its trivia and returns it.
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn remove_child(&mut self, index: usize) -> Node<'a> {
+        let removed = self.children.remove(index);
+        self.update_text();
+        removed
+    }
+
+    /// Edits the child at `index` with `edit`, such as to replace one of its own children,
+    /// or returns `None` if there is no such child.
+    pub fn edit_child<R>(&mut self, index: usize, edit: impl FnOnce(&mut Node<'a>) -> R) -> Option<R> {
+        let result = edit(self.children.get_mut(index)?);
+        self.update_text();
+        Some(result)
+    }
+
+    fn update_text(&mut self) {
+        let mut text = String::new();
+        for child in &self.children {
+            // Writing to a `String` cannot fail.
+            let _ = fmt::write(&mut text, format_args!("{child}"));
+        }
+        self.text = Cow::Owned(text);
+    }
+}
+
+/// Writes the node back out exactly as it was parsed, trivia included.
+impl fmt::Display for Node<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for trivia in &self.leading {
+            f.write_str(trivia.text())?;
+        }
+        if self.children.is_empty() {
+            f.write_str(&self.text)?;
+        } else {
+            for child in &self.children {
+                write!(f, "{child}")?;
+            }
+        }
+        for trivia in &self.trailing {
+            f.write_str(trivia.text())?;
+        }
+        Ok(())
+    }
+}
+
+pub struct Descendants<'n, 'a> {
+    stack: Vec<&'n Node<'a>>,
+}
+
+impl<'n, 'a> Iterator for Descendants<'n, 'a> {
+    type Item = &'n Node<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        self.stack.extend(node.children.iter().rev());
+        Some(node)
+    }
+}
+
+/// A lossless syntax tree: every directive, instruction, comment and whitespace run
+/// of the source is kept, so `to_string()` reproduces the input byte for byte. Once
+/// nodes are edited, it reproduces the input with only those nodes changed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyntaxTree<'a> {
+    root: Node<'a>,
+}
+
+impl<'a> SyntaxTree<'a> {
+    /// The `Module` node spanning the whole file.
+    pub fn root(&self) -> &Node<'a> {
+        &self.root
+    }
+
+    /// The `Module` node, to edit the tree with.
+    pub fn root_mut(&mut self) -> &mut Node<'a> {
+        &mut self.root
+    }
+}
+
+impl<'a> TryFrom<&'a str> for SyntaxTree<'a> {
+    type Error = nom::Err<nom::error::Error<&'a str>>;
+
+    /// Fails only if the preamble cannot be parsed; any other text which is not
+    /// understood becomes an `Unknown` node.
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        parse::parse_module(value).map(|(_, root)| SyntaxTree { root })
+    }
+}
+
+impl fmt::Display for SyntaxTree<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.root)
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg(test)]
+mod test_syntax_tree {
+    use std::string::ToString;
+
+    use crate::{
+        parser::{
+            cst::{Node, NodeKind, SyntaxTree, Trivia},
+            function::body::BodyLine,
+        },
+        ptx_files::{self, atomics, _EXAMPLE_FILE},
+    };
+
+    #[test]
+    fn round_trip() {
+        for input in ptx_files::ALL.into_iter().chain([_EXAMPLE_FILE]) {
+            let tree = SyntaxTree::try_from(input).unwrap();
+            assert_eq!(tree.to_string(), input);
+            assert!(tree.root().descendants().all(|node| node.kind() != NodeKind::Unknown));
+            assert!(tree
+                .root()
+                .descendants()
+                .filter(|node| node.kind() == NodeKind::Function)
+                .all(|function| function.children().len() == 2));
+        }
+    }
+
+    #[test]
+    fn comments_are_trivia() {
+        let tree = SyntaxTree::try_from(atomics::_PTX).unwrap();
+        let functions = tree
+            .root()
+            .children()
+            .iter()
+            .filter(|node| node.kind() == NodeKind::Function)
+            .collect::<Vec<_>>();
+        assert_eq!(functions.len(), 2);
+        assert!(functions[0].leading_trivia().contains(&Trivia::Comment("// .globl\tpublish")));
+        assert_eq!(tree.root().comments().collect::<Vec<_>>(), ["// .globl\tpublish", "// .globl\thistogram"]);
+    }
+
+    #[test]
+    fn trailing_comment() {
+        let input = ".version 8.0\n.target sm_90\n.address_size 64\n\n.visible .entry k(.param .u64 k_param_0)\n{\n\tret; // done\n\t/* tail */\n}\n";
+        let tree = SyntaxTree::try_from(input).unwrap();
+        assert_eq!(tree.to_string(), input);
+        let body = &tree.root().children()[3].children()[1];
+        assert_eq!(body.kind(), NodeKind::Body);
+        let ret = &body.children()[1];
+        assert_eq!(ret.text(), "ret;");
+        assert_eq!(ret.trailing_trivia(), [Trivia::Whitespace(" "), Trivia::Comment("// done")]);
+        assert_eq!(ret.body_line(), Some(BodyLine::Return));
+        let close = &body.children()[2];
+        assert_eq!(close.kind(), NodeKind::CloseBrace);
+        assert_eq!(
+            close.leading_trivia(),
+            [Trivia::Whitespace("\n\t"), Trivia::Comment("/* tail */"), Trivia::Whitespace("\n")]
+        );
+    }
+
+    #[test]
+    fn instruction_tokens() {
+        let input = ".version 8.0\n.target sm_90\n.address_size 64\n\n.visible .entry k(.param .u64 k_param_0)\n{\n\t@%p1  st.global.u32 \t[%rd1+4], {%r1, %r2}; // store\n\t.reg .b32 %r<3>;\n}\n";
+        let tree = SyntaxTree::try_from(input).unwrap();
+        let body = &tree.root().children()[3].children()[1];
+        let store = &body.children()[1];
+        let kinds = store.children().iter().map(|token| token.kind()).collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            [NodeKind::Predicate, NodeKind::Opcode, NodeKind::Operand, NodeKind::Comma, NodeKind::Operand, NodeKind::Semicolon]
+        );
+        let texts = store.children().iter().map(|token| token.text()).collect::<Vec<_>>();
+        assert_eq!(texts, ["@%p1", "st.global.u32", "[%rd1+4]", ",", "{%r1, %r2}", ";"]);
+        assert_eq!(store.children()[1].trailing_trivia(), [Trivia::Whitespace(" \t")]);
+        assert_eq!(store.trailing_trivia(), [Trivia::Whitespace(" "), Trivia::Comment("// store")]);
+        assert!(body.children()[2].children().is_empty());
+    }
+
+    #[test]
+    fn edits_keep_trivia() {
+        let input = ".version 8.0\n.target sm_90\n.address_size 64\n\n.visible .entry k(.param .u64 k_param_0)\n{\n\t// load the pointer\n\tld.param.u64 \t%rd1, [k_param_0]; // load\n\tret;\n}\n";
+        let mut tree = SyntaxTree::try_from(input).unwrap();
+        tree.root_mut().edit_child(3, |function| {
+            function.edit_child(1, |body| {
+                body.edit_child(1, |load| load.replace_child(1, Node::new(NodeKind::Operand, "%rd9")));
+                body.insert_child(2, Node::line("mov.u64 %rd2, %rd9;").unwrap());
+                let replaced = body.replace_child(3, Node::line("exit;").unwrap());
+                assert_eq!(replaced.text(), "ret;");
+            })
+        });
+        assert_eq!(
+            tree.to_string(),
+            ".version 8.0\n.target sm_90\n.address_size 64\n\n.visible .entry k(.param .u64 k_param_0)\n{\n\t// load the pointer\n\tld.param.u64 \t%rd9, [k_param_0]; // load\n\tmov.u64 %rd2, %rd9;\n\texit;\n}\n"
+        );
+        let function = &tree.root().children()[3];
+        assert_eq!(function.to_string().trim(), function.text());
+        let lines = function.function().unwrap().body.unwrap().map(|line| line.unwrap().1).collect::<Vec<_>>();
+        assert_eq!(lines.len(), 5);
+        assert_eq!(function.children()[1].children()[1].body_line().unwrap().to_string(), "ld.param.u64 %rd9, [k_param_0];");
+    }
+
+    #[test]
+    fn unknown_text_is_kept() {
+        let input = ".version 8.0\n.target sm_90\n.address_size 64\n.weird thing;\n.global .u32 x;\n";
+        let tree = SyntaxTree::try_from(input).unwrap();
+        assert_eq!(tree.to_string(), input);
+        let kinds = tree.root().children().iter().map(|node| node.kind()).collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            [NodeKind::Version, NodeKind::Target, NodeKind::AddressSize, NodeKind::Unknown, NodeKind::Global]
+        );
+        assert!(tree.root().children()[4].global().is_some());
+    }
+}
@@ -0,0 +1,224 @@
+use nom::{
+    branch::alt,
+    character::complete::{multispace1, space1},
+    combinator::recognize,
+    IResult, Parser,
+};
+
+use std::borrow::Cow;
+
+use crate::parser::{
+    comment::parse::parse_line_comment,
+    function::{body::{parse_body_line, BodyLine}, parse::{parse_function, parse_function_signature}},
+    global::parse::parse_global,
+    location::parse::parse_file,
+    parse_braced_balanced,
+    preamble::parse::{parse_address_size, parse_target, parse_version},
+    section::parse::parse_section,
+};
+
+use super::{Node, NodeKind, Trivia};
+
+/// The part of `input` which was consumed to leave `rest`.
+fn consumed<'a>(input: &'a str, rest: &'a str) -> &'a str {
+    &input[..input.len() - rest.len()]
+}
+
+fn parse_comment(input: &str) -> IResult<&str, Trivia<'_>> {
+    recognize(parse_line_comment).map(Trivia::Comment).parse(input)
+}
+
+/// The whitespace and comments before a node, across any number of lines.
+fn leading_trivia(mut input: &str) -> (&str, Vec<Trivia<'_>>) {
+    let mut trivia = Vec::new();
+    while let Ok((rest, piece)) = alt((multispace1.map(Trivia::Whitespace), parse_comment))(input) {
+        trivia.push(piece);
+        input = rest;
+    }
+    (input, trivia)
+}
+
+/// The spaces and comments after a node, up to the end of its line.
+pub(crate) fn trailing_trivia(mut input: &str) -> (&str, Vec<Trivia<'_>>) {
+    let mut trivia = Vec::new();
+    while let Ok((rest, piece)) = alt((space1.map(Trivia::Whitespace), parse_comment))(input) {
+        trivia.push(piece);
+        input = rest;
+    }
+    (input, trivia)
+}
+
+/// A node spanning `input` up to `rest`, followed by its trailing trivia.
+fn leaf<'a>(kind: NodeKind, leading: Vec<Trivia<'a>>, input: &'a str, rest: &'a str) -> (&'a str, Node<'a>) {
+    let text = consumed(input, rest);
+    let (rest, trailing) = trailing_trivia(rest);
+    (rest, Node { kind, leading, text: Cow::Borrowed(text), children: Vec::new(), trailing })
+}
+
+/// Skips text no parser understands, up to and including the next `;`.
+fn skip_unknown(input: &str) -> &str {
+    input.find(';').map_or("", |end| &input[end + 1..])
+}
+
+type Recognizer = fn(&str) -> IResult<&str, &str>;
+
+pub(crate) fn parse_module(input: &str) -> IResult<&str, Node<'_>> {
+    let preamble: [(NodeKind, Recognizer); 3] = [
+        (NodeKind::Version, |input| recognize(parse_version)(input)),
+        (NodeKind::Target, |input| recognize(parse_target)(input)),
+        (NodeKind::AddressSize, |input| recognize(parse_address_size)(input)),
+    ];
+    let mut children = Vec::new();
+    let mut rest = input;
+    for (kind, parser) in preamble {
+        let (after_trivia, leading) = leading_trivia(rest);
+        let (after_node, _) = parser(after_trivia)?;
+        let (after, node) = leaf(kind, leading, after_trivia, after_node);
+        children.push(node);
+        rest = after;
+    }
+    let trailing = loop {
+        let (after_trivia, leading) = leading_trivia(rest);
+        if after_trivia.is_empty() {
+            break leading;
+        }
+        let (after, node) = parse_item(leading, after_trivia);
+        children.push(node);
+        rest = after;
+    };
+    let text = Cow::Borrowed(consumed(input, rest));
+    Ok(("", Node { kind: NodeKind::Module, leading: Vec::new(), text, children, trailing }))
+}
+
+fn parse_item<'a>(leading: Vec<Trivia<'a>>, input: &'a str) -> (&'a str, Node<'a>) {
+    if let Ok((rest, _)) = parse_function(input) {
+        let text = consumed(input, rest);
+        let (rest, trailing) = trailing_trivia(rest);
+        let children = function_children(text).unwrap_or_default();
+        return (rest, Node { kind: NodeKind::Function, leading, text: Cow::Borrowed(text), children, trailing });
+    }
+    let (kind, rest) = if let Ok((rest, _)) = parse_global(input) {
+        (NodeKind::Global, rest)
+    } else if let Ok((rest, _)) = parse_file(input) {
+        (NodeKind::File, rest)
+    } else if let Ok((rest, _)) = parse_section(input) {
+        (NodeKind::Section, rest)
+    } else {
+        (NodeKind::Unknown, skip_unknown(input))
+    };
+    leaf(kind, leading, input, rest)
+}
+
+/// Splits the text of a function into its signature and its body or `;`.
+fn function_children(text: &str) -> Option<Vec<Node<'_>>> {
+    let (rest, _) = parse_function_signature(text).ok()?;
+    let signature = consumed(text, rest).trim_end();
+    let (rest, signature) = leaf(NodeKind::Signature, Vec::new(), text, &text[signature.len()..]);
+    let (rest, leading) = leading_trivia(rest);
+    let end = if let Some(after) = rest.strip_prefix(';') {
+        leaf(NodeKind::Semicolon, leading, rest, after)
+    } else {
+        let (after, _) = parse_braced_balanced(rest).ok()?;
+        let body = consumed(rest, after);
+        let node = Node {
+            kind: NodeKind::Body,
+            leading,
+            text: Cow::Borrowed(body),
+            children: body_children(body),
+            trailing: Vec::new(),
+        };
+        (after, node)
+    };
+    end.0.is_empty().then(|| Vec::from([signature, end.1]))
+}
+
+/// Splits `{ ... }` into its braces and the lines between them.
+fn body_children(body: &str) -> Vec<Node<'_>> {
+    let (rest, open) = leaf(NodeKind::OpenBrace, Vec::new(), body, &body[1..]);
+    let (lines, close_leading) = line_nodes(&rest[..rest.len() - 1]);
+    let mut children = Vec::from([open]);
+    children.extend(lines);
+    let close = &body[body.len() - 1..];
+    children.push(leaf(NodeKind::CloseBrace, close_leading, close, "").1);
+    children
+}
+
+/// Splits the text of a body into `Line` nodes, returning the trivia after the last one.
+pub(crate) fn line_nodes(mut rest: &str) -> (Vec<Node<'_>>, Vec<Trivia<'_>>) {
+    let mut lines = Vec::new();
+    loop {
+        let (after_trivia, leading) = leading_trivia(rest);
+        if after_trivia.is_empty() {
+            return (lines, leading);
+        }
+        let (kind, after_line, tokens) = match parse_body_line(after_trivia) {
+            Ok((after_line, line)) => (NodeKind::Line, after_line, has_tokens(&line)),
+            Err(_) => (NodeKind::Unknown, skip_unknown(after_trivia), false),
+        };
+        let (after, mut line) = leaf(kind, leading, after_trivia, after_line);
+        if tokens {
+            line.children = token_nodes(consumed(after_trivia, after_line));
+        }
+        lines.push(line);
+        rest = after;
+    }
+}
+
+/// A `Line` node spanning all of `text`, which has no trivia around it.
+pub(super) fn line_node(text: &str) -> Option<Node<'_>> {
+    let (rest, line) = parse_body_line(text).ok()?;
+    let mut node = Node::new(NodeKind::Line, text);
+    if has_tokens(&line) {
+        node.children = token_nodes(text);
+    }
+    rest.is_empty().then_some(node)
+}
+
+/// Whether a line is an instruction, which is split into tokens.
+fn has_tokens(line: &BodyLine<'_>) -> bool {
+    line.is_instruction() && !matches!(line, BodyLine::FunctionCall(_) | BodyLine::Unknown(_))
+}
+
+/// Splits an instruction into its tokens, each followed by the whitespace and comments
+/// after it.
+fn token_nodes(mut text: &str) -> Vec<Node<'_>> {
+    let mut tokens = Vec::new();
+    let mut opcode = true;
+    while let Some(first) = text.chars().next() {
+        let (kind, length) = match first {
+            '@' if tokens.is_empty() => (NodeKind::Predicate, word_length(text)),
+            ',' => (NodeKind::Comma, 1),
+            ';' => (NodeKind::Semicolon, 1),
+            _ if opcode => (NodeKind::Opcode, word_length(text)),
+            _ => (NodeKind::Operand, operand_length(text)),
+        };
+        opcode &= kind == NodeKind::Predicate;
+        let (rest, trailing) = leading_trivia(&text[length..]);
+        tokens.push(Node::new(kind, &text[..length]).with_trivia(Vec::new(), trailing));
+        text = rest;
+    }
+    tokens
+}
+
+/// The length of the predicate or instruction name at the start of `text`.
+fn word_length(text: &str) -> usize {
+    text.find(|c: char| c.is_whitespace() || c == ';').unwrap_or(text.len())
+}
+
+/// The length of the operand at the start of `text`, up to the next `,` or `;` outside
+/// of brackets, braces and parentheses.
+fn operand_length(text: &str) -> usize {
+    let mut depth = 0usize;
+    let end = text
+        .char_indices()
+        .find(|&(_, c)| {
+            match c {
+                '[' | '{' | '(' => depth += 1,
+                ']' | '}' | ')' => depth = depth.saturating_sub(1),
+                _ => (),
+            }
+            depth == 0 && (c == ',' || c == ';')
+        })
+        .map_or(text.len(), |(end, _)| end);
+    text[..end].trim_end().len()
+}
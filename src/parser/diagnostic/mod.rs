@@ -0,0 +1,132 @@
+use core::{fmt, ops::Range};
+use std::string::String;
+
+/// How serious a diagnostic is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Severity {
+    /// The module would be rejected by `ptxas`.
+    Error,
+    /// The module is accepted but probably does not do what was meant.
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        })
+    }
+}
+
+/// What a diagnostic is about. Each code has a stable identifier, e.g. `E0001`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Code {
+    /// A register, label, parameter, variable or function which is never declared.
+    UndefinedSymbol,
+    /// A name declared twice in the same scope.
+    DuplicateSymbol,
+    /// A register whose declared type does not fit the operand it is used as.
+    TypeMismatch,
+    /// Modifiers which cannot be combined, such as a rounding mode on an integer `add`.
+    InvalidModifiers,
+    /// Too many or too few operands for the instruction.
+    OperandCount,
+    /// Memory accessed in the wrong state space, such as a store to `.const`.
+    StateSpaceMisuse,
+    /// An address whose width does not match the module's `.address_size`.
+    PointerWidth,
+    /// An instruction or directive the module's `.version` or `.target` does not allow.
+    Unavailable,
+}
+
+impl Code {
+    /// The stable identifier of the code.
+    pub fn id(&self) -> &'static str {
+        match self {
+            Code::UndefinedSymbol => "E0001",
+            Code::DuplicateSymbol => "E0002",
+            Code::TypeMismatch => "E0003",
+            Code::InvalidModifiers => "E0004",
+            Code::OperandCount => "E0005",
+            Code::StateSpaceMisuse => "E0006",
+            Code::PointerWidth => "E0007",
+            Code::Unavailable => "E0008",
+        }
+    }
+
+    pub fn severity(&self) -> Severity {
+        match self {
+            Code::UndefinedSymbol
+            | Code::DuplicateSymbol
+            | Code::TypeMismatch
+            | Code::InvalidModifiers
+            | Code::OperandCount
+            | Code::StateSpaceMisuse
+            | Code::PointerWidth
+            | Code::Unavailable => Severity::Error,
+        }
+    }
+}
+
+/// A problem found in a module, pointing at the text it is about.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic<'a> {
+    code: Code,
+    message: String,
+    text: &'a str,
+}
+
+impl<'a> Diagnostic<'a> {
+    /// A diagnostic pointing at `text`, which should be sliced from the module's source.
+    pub fn new(code: Code, message: String, text: &'a str) -> Self {
+        Diagnostic { code, message, text }
+    }
+
+    pub fn code(&self) -> Code {
+        self.code
+    }
+
+    pub fn severity(&self) -> Severity {
+        self.code.severity()
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// The text the diagnostic points at, e.g. the undefined register.
+    pub fn text(&self) -> &'a str {
+        self.text
+    }
+
+    /// The byte range of `text` within `source`, the text the module was parsed from.
+    pub fn span_in(&self, source: &str) -> Option<Range<usize>> {
+        crate::parser::span_in(self.text, source)
+    }
+}
+
+/// Formats as ``error[E0001]: undefined register `%r9` in `kernel` ``.
+impl fmt::Display for Diagnostic<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}[{}]: {}", self.severity(), self.code.id(), self.message)
+    }
+}
+
+#[cfg(test)]
+mod test_diagnostic {
+    use std::string::ToString;
+
+    use super::{Code, Diagnostic, Severity};
+
+    #[test]
+    fn display_and_span() {
+        let source = "mov.u32 %r9, 1;";
+        let diagnostic = Diagnostic::new(Code::UndefinedSymbol, "undefined register `%r9`".to_string(), &source[8..11]);
+        assert_eq!(diagnostic.severity(), Severity::Error);
+        assert_eq!(diagnostic.to_string(), "error[E0001]: undefined register `%r9`");
+        assert_eq!(diagnostic.span_in(source), Some(8..11));
+        assert_eq!(diagnostic.span_in("elsewhere"), None);
+    }
+}
@@ -1,55 +1,160 @@
+use std::collections::VecDeque;
+
 use nom::{
     branch::alt,
     bytes::complete::{tag, take_until1, take_while1},
-    character::complete::{char, multispace1, space0, space1},
-    combinator::opt,
-    sequence::{delimited, preceded, terminated, Tuple},
+    character::complete::{char, multispace0, multispace1, space0, space1},
+    combinator::{eof, opt},
+    sequence::{delimited, preceded, separated_pair, terminated, Tuple},
     IResult, Parser,
 };
 
 use crate::parser::{
     is_special,
-    parse_braced_balanced, comment::{parse::{many1_comments_or_whitespace, parse_line_comment}, Comment}
+    parse_braced_balanced, comment::{parse::parse_line_comment, Comment},
+    location::{parse::parse_location, Location},
+    instruction::{parse::parse_operands, Operand},
 };
 
-#[derive(Debug, PartialEq)]
-pub(super) struct FunctionBody<'a> {
-    pub(super) body: Option<&'a str>,
+/// The lines between the braces of a function, parsed up to the first one which fails to
+/// parse. Only the lines and the text from there on are kept, so bodies which differ in
+/// layout alone compare equal.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FunctionBody<'a> {
+    lines: VecDeque<BodyLine<'a>>,
+    /// The trimmed text from the first line which fails to parse on, yielded after `lines`.
+    body: Option<&'a str>,
+}
+
+impl<'a> FunctionBody<'a> {
+    pub(crate) fn new(body: Option<&'a str>) -> Self {
+        let mut lines = VecDeque::new();
+        let mut rest = body;
+        while let Some(text) = rest {
+            match parse_line(text) {
+                Ok((text, line)) => {
+                    lines.push_back(line);
+                    rest = Some(text);
+                }
+                Err(_) => break,
+            }
+        }
+        FunctionBody::from_lines(lines).with_rest(rest)
+    }
+
+    /// A body made of `lines` rather than parsed from text, as left by a `VisitorMut`
+    /// which changed a line.
+    pub fn from_lines(lines: impl IntoIterator<Item = BodyLine<'a>>) -> Self {
+        FunctionBody { lines: lines.into_iter().collect(), body: None }
+    }
+
+    /// Followed by the unparsed text `rest`.
+    pub(crate) fn with_rest(mut self, rest: Option<&'a str>) -> Self {
+        self.body = rest.map(str::trim).filter(|rest| !rest.is_empty());
+        self
+    }
+
+    /// The text from the first line which fails to parse on, which has not been
+    /// iterated over yet.
+    pub fn raw_string(&self) -> Option<&'a str> {
+        self.body
+    }
+
+    /// The lines up to the first one which fails to parse, and the text from there on.
+    pub(crate) fn parsed(&self) -> (Vec<BodyLine<'a>>, Option<&'a str>) {
+        (self.lines.iter().cloned().collect(), self.body)
+    }
+
+    /// Pairs every line with the source location of the most recent `.loc` directive.
+    pub fn located_lines(self) -> LocatedLines<'a> {
+        LocatedLines { lines: self, location: None }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LocatedLines<'a> {
+    lines: FunctionBody<'a>,
+    location: Option<Location>,
 }
 
+impl<'a> Iterator for LocatedLines<'a> {
+    type Item = IResult<&'a str, (Option<Location>, BodyLine<'a>)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.lines.next()?.map(|(body, line)| {
+            if let BodyLine::Location(location) = line {
+                self.location = Some(location);
+            }
+            (body, (self.location, line))
+        }))
+    }
+}
+
+/// Yields every line together with the text which failed to parse after the lines, then
+/// the error that text fails to parse with.
 impl<'a> Iterator for FunctionBody<'a> {
     type Item = IResult<&'a str, BodyLine<'a>>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let body = self.body?;
-        Some(match preceded(
-            opt(many1_comments_or_whitespace),
-            parse_body_line,
-        )(body) {
-                Ok((body, value)) => {
-                    self.body = Some(body);
-                    Ok((body, value))
-            },
-                err => {
-                    let body = self.body?.trim();
-                    if body.is_empty() {
-                        self.body = None;
-                        return None
-                    } else {
-                        self.body = Some(body);
-                        err
-                    }
-            },
-        })
+        if let Some(line) = self.lines.pop_front() {
+            return Some(Ok((self.body.unwrap_or_default(), line)));
+        }
+        parse_line(self.body.take()?).into()
     }
 }
 
-#[derive(Debug, PartialEq)]
-pub(crate) struct Register<'a> {
+/// One line of a body, with the whitespace before it: a comment or a statement.
+fn parse_line(input: &str) -> IResult<&str, BodyLine<'_>> {
+    preceded(
+        multispace0,
+        alt((parse_line_comment.map(BodyLine::Comment), parse_body_line)),
+    )(input)
+}
+
+/// A `.reg` declaration such as `.reg .b32 %r<5>`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Register<'a> {
     raw_string: &'a str,
 }
 
-fn parse_register(input: &str) -> IResult<&str, Register> {
+impl<'a> Register<'a> {
+    /// The declaration after `.reg`, e.g. `.b32 %r<5>`.
+    pub fn raw_string(&self) -> &'a str {
+        self.raw_string
+    }
+
+    /// The register type, e.g. `.b32`.
+    pub fn ty(&self) -> Option<&'a str> {
+        self.raw_string.split_whitespace().next()
+    }
+
+    /// The first register name, or the prefix of a parameterized declaration, e.g. `%r`.
+    pub fn name(&self) -> Option<&'a str> {
+        self.names().next().map(|(name, _)| name)
+    }
+
+    /// The `N` of a parameterized first declaration `%r<N>`, declaring `%r0` to `%r{N-1}`.
+    pub fn count(&self) -> Option<u32> {
+        self.names().next().and_then(|(_, count)| count)
+    }
+
+    /// Every name of a comma separated list such as `.b32 %r<5>, %temp`, with its count
+    /// when parameterized.
+    pub fn names(&self) -> impl Iterator<Item = (&'a str, Option<u32>)> {
+        let names = self.raw_string.trim_start().split_once(char::is_whitespace).map(|(_, names)| names);
+        names.unwrap_or_default().split(',').map(str::trim).filter(|name| !name.is_empty()).map(|name| {
+            match name.split_once('<') {
+                Some((prefix, count)) => {
+                    let count = count.trim().strip_suffix('>').and_then(|count| count.trim().parse().ok());
+                    (prefix.trim_end(), count)
+                }
+                None => (name, None),
+            }
+        })
+    }
+}
+
+fn parse_register(input: &str) -> IResult<&str, Register<'_>> {
     preceded(
         tag(".reg").and(space1),
         take_while1(|_| true)
@@ -57,55 +162,140 @@ fn parse_register(input: &str) -> IResult<&str, Register> {
     )(input)
 }
 
-#[derive(Debug, PartialEq)]
-pub(crate) struct Operation<'a> {
+#[derive(Debug, Clone, PartialEq)]
+pub struct Operation<'a> {
     operation: &'a str,
-    arguments: &'a str,
+    arguments: Arguments<'a>,
+}
+
+/// What follows the instruction name.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Arguments<'a> {
+    Operands(Vec<Operand<'a>>),
+    /// Text which does not parse as operands, such as the declaration after `.local`.
+    Text(&'a str),
+}
+
+impl<'a> Operation<'a> {
+    pub(crate) fn new(operation: &'a str, arguments: &'a str) -> Self {
+        let arguments = match arguments.trim() {
+            "" => Arguments::Operands(Vec::new()),
+            text => parse_operands(text).map_or(Arguments::Text(text), |(_, operands)| Arguments::Operands(operands)),
+        };
+        Operation { operation, arguments }
+    }
+
+    /// Replaces the full instruction name, e.g. to turn `ld.global.u32` into `ld.global.nc.u32`.
+    pub fn set_operation(&mut self, operation: &'a str) {
+        self.operation = operation;
+    }
+
+    /// Replaces the operands, or the text of arguments which are not operands.
+    pub fn set_operands(&mut self, operands: Vec<Operand<'a>>) {
+        self.arguments = Arguments::Operands(operands);
+    }
+
+    pub(crate) fn raw_arguments(&self) -> &Arguments<'a> {
+        &self.arguments
+    }
+
+    /// The full instruction name with its modifiers, e.g. `ld.param.u64`.
+    pub fn operation(&self) -> &'a str {
+        self.operation
+    }
+
+    /// The text after the instruction name when it does not parse as operands, such as
+    /// `.align 8 .b8 __local_depot0[16]` after `.local`.
+    pub fn arguments(&self) -> Option<&'a str> {
+        match self.arguments {
+            Arguments::Text(text) => Some(text),
+            Arguments::Operands(_) => None,
+        }
+    }
+
+    /// The instruction name without modifiers, e.g. `ld`.
+    pub fn opcode(&self) -> &'a str {
+        self.operation.split('.').next().unwrap_or(self.operation)
+    }
+
+    /// The modifiers following the opcode without their leading `.`, e.g. `param` and `u64`.
+    pub fn modifiers(&self) -> impl Iterator<Item = &'a str> {
+        self.operation.split('.').skip(1)
+    }
+
+    /// The operands, or the error parsing the text of arguments which are not operands.
+    pub fn operands(&self) -> Result<Vec<Operand<'a>>, nom::Err<nom::error::Error<&'a str>>> {
+        match &self.arguments {
+            Arguments::Operands(operands) => Ok(operands.clone()),
+            Arguments::Text(text) => parse_operands(text).map(|(_, operands)| operands),
+        }
+    }
 }
 
-#[derive(Debug, PartialEq)]
-pub(crate) struct Goto<'a> {
+/// A branch such as `@%p1 bra $L__BB0_2` or `bra.uni $L__BB0_1`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Goto<'a> {
     predicate: Option<Predicate<'a>>,
     label: &'a str,
 }
 
+impl<'a> Goto<'a> {
+    /// The guard of a conditional branch.
+    pub fn predicate(&self) -> Option<&Predicate<'a>> {
+        self.predicate.as_ref()
+    }
+
+    /// The target label without its leading `$`.
+    pub fn label(&self) -> &'a str {
+        self.label
+    }
+
+    /// Replaces the guard; `None` makes the branch unconditional.
+    pub fn set_predicate(&mut self, predicate: Option<Predicate<'a>>) {
+        self.predicate = predicate;
+    }
+}
+
 fn parse_unknown_line(input: &str) -> IResult<&str, &str> {
     take_while1(|_| true)(input)
 }
 
-fn parse_operation(input: &str) -> IResult<&str, Operation> {
+fn parse_operation(input: &str) -> IResult<&str, Operation<'_>> {
     let (input, (operation, arguments)) = (
         take_while1(|c: char|
-            !c.is_whitespace() && (c == '.' || !is_special(c))
-        ),
-        preceded(
-            space1,
-            take_while1(|_| true)
+            !c.is_whitespace() && (c == '.' || c == ':' || !is_special(c))
         ),
+        alt((
+            preceded(
+                space1,
+                take_while1(|_| true)
+            ),
+            preceded(space0, eof),
+        )),
     )
     .parse(input)?;
-    Ok((
-        input,
-        Operation {
-            operation,
-            arguments,
-        },
-    ))
+    Ok((input, Operation::new(operation, arguments)))
 }
 
-fn parse_goto(input: &str) -> IResult<&str, Goto> {
+fn parse_predicate(input: &str) -> IResult<&str, Predicate<'_>> {
+    preceded(
+        char('@'),
+        opt(char('!'))
+            .and(take_while1(|c: char| !c.is_whitespace()))
+            .map(|(negation, raw_string)| {
+                if negation.is_none() {
+                    Predicate::True(raw_string)
+                } else {
+                    Predicate::False(raw_string)
+                }
+            }),
+    )(input)
+}
+
+fn parse_goto(input: &str) -> IResult<&str, Goto<'_>> {
     let (label, predicate) = alt((
-        delimited(
-            char('@'),
-            opt(char('!'))
-                .and(take_while1(|c: char| !c.is_whitespace()))
-                .map(|(negation, raw_string)| {
-                    Some(if negation.is_none() {
-                        Predicate::True(raw_string)
-                    } else {
-                        Predicate::False(raw_string)
-                    })
-                }),
+        terminated(
+            parse_predicate.map(Some),
             space1.and(tag("bra")).and(space1).and(char('$')),
         ),
         tag("bra.uni").and(space1).and(char('$'))
@@ -115,15 +305,114 @@ fn parse_goto(input: &str) -> IResult<&str, Goto> {
     Ok((input, Goto { predicate, label }))
 }
 
-#[derive(Debug, PartialEq)]
-pub(crate) struct FunctionCall<'a> {
+#[derive(Debug, Clone, PartialEq)]
+pub struct FunctionCall<'a> {
     setup: &'a str,
     function: &'a str,
     arguments: &'a str,
     comment: Comment<'a>,
+    /// Lines given by `set_lines`, which replace those before and after `call.uni`.
+    lines: Option<(Vec<BodyLine<'a>>, Vec<BodyLine<'a>>)>,
+}
+
+impl<'a> FunctionCall<'a> {
+    /// The text of the call sequence before `call.uni`.
+    pub fn setup(&self) -> &'a str {
+        self.setup
+    }
+
+    /// The first operand of `call.uni`.
+    pub fn function(&self) -> &'a str {
+        self.function
+    }
+
+    /// The text after the first operand of `call.uni`, up to the closing brace.
+    pub fn arguments(&self) -> &'a str {
+        self.arguments
+    }
+
+    /// The called function. This is `function` unless the call has return values, in
+    /// which case `function` holds them and the callee follows.
+    pub fn callee(&self) -> &'a str {
+        match self.returns_values() {
+            true => self.arguments.split(',').next().unwrap_or_default().trim(),
+            false => self.function.trim(),
+        }
+    }
+
+    /// The parameters receiving the return values, e.g. `retval0` in
+    /// `call.uni (retval0), f, (param0);`.
+    pub fn return_parameters(&self) -> Vec<&'a str> {
+        match self.returns_values() {
+            true => parenthesized_names(self.function),
+            false => Vec::new(),
+        }
+    }
+
+    /// The parameters passed to the callee, e.g. `param0` in `call.uni (retval0), f, (param0);`.
+    pub fn argument_parameters(&self) -> Vec<&'a str> {
+        let after_callee = match self.returns_values() {
+            true => self.arguments.split_once(',').map_or("", |(_, rest)| rest),
+            false => self.arguments,
+        };
+        parenthesized_names(after_callee.split(';').next().unwrap_or_default())
+    }
+
+    fn returns_values(&self) -> bool {
+        self.function.trim_start().starts_with('(')
+    }
+
+    /// The comment following the closing brace, such as `// callseq 0`.
+    pub fn comment(&self) -> &Comment<'a> {
+        &self.comment
+    }
+
+    /// The lines of the call sequence around the `call.uni` statement, such as the
+    /// `.param` declarations and the loads of the return value.
+    pub fn lines(&self) -> impl Iterator<Item = IResult<&'a str, BodyLine<'a>>> {
+        let (before, after) = match &self.lines {
+            Some((before, after)) => (FunctionBody::from_lines(before.clone()), FunctionBody::from_lines(after.clone())),
+            None => {
+                let after_call = self.arguments.split_once(';').map(|(_, rest)| rest);
+                (FunctionBody::new(Some(self.setup)), FunctionBody::new(after_call))
+            }
+        };
+        before.chain(after)
+    }
+
+    /// Replaces the lines before and after the `call.uni` statement. `setup` and
+    /// `arguments` keep the text the call sequence was parsed from.
+    pub fn set_lines(&mut self, before: Vec<BodyLine<'a>>, after: Vec<BodyLine<'a>>) {
+        self.lines = Some((before, after));
+    }
+
+    /// The lines before and after the `call.uni` statement, up to the first line of
+    /// either part which fails to parse.
+    pub(crate) fn split_lines(&self) -> (Vec<BodyLine<'a>>, Vec<BodyLine<'a>>) {
+        if let Some((before, after)) = &self.lines {
+            return (before.clone(), after.clone());
+        }
+        let after_call = self.arguments.split_once(';').map(|(_, rest)| rest);
+        let lines = |text| FunctionBody::new(text).map_while(Result::ok).map(|(_, line)| line).collect();
+        (lines(Some(self.setup)), lines(after_call))
+    }
+
+    /// The lines given by `set_lines`, if any.
+    pub(crate) fn edited_lines(&self) -> Option<(&[BodyLine<'a>], &[BodyLine<'a>])> {
+        self.lines.as_ref().map(|(before, after)| (before.as_slice(), after.as_slice()))
+    }
+}
+
+/// The comma separated names in the first pair of parentheses of `text`.
+fn parenthesized_names(text: &str) -> Vec<&str> {
+    let Some((_, rest)) = text.split_once('(') else {
+        return Vec::new();
+    };
+    let inside = rest.split(')').next().unwrap_or_default();
+    inside.split(',').map(str::trim).filter(|name| !name.is_empty()).collect()
 }
 
-fn parse_function_call(input: &str) -> IResult<&str, FunctionCall> {
+fn parse_function_call(input: &str) -> IResult<&str, FunctionCall<'_>> {
     let (input, (body, comment)) = (
         parse_braced_balanced,
         preceded(space0, parse_line_comment)
@@ -147,12 +436,13 @@ fn parse_function_call(input: &str) -> IResult<&str, FunctionCall> {
                     function,
                     arguments,
                     comment,
+                    lines: None,
                 },
             )
         })
 }
 
-fn parse_body_line(input: &str) -> IResult<&str, BodyLine> {
+pub(crate) fn parse_body_line(input: &str) -> IResult<&str, BodyLine<'_>> {
     let body_line = alt((
         delimited(
             char('$'),
@@ -160,8 +450,12 @@ fn parse_body_line(input: &str) -> IResult<&str, BodyLine> {
             char(':'),
         )
         .map(BodyLine::Label),
+        parse_location
+        .map(BodyLine::Location),
         parse_function_call
         .map(BodyLine::FunctionCall),
+        char('{').map(|_| BodyLine::OpenBrace),
+        char('}').map(|_| BodyLine::CloseBrace),
         terminated(
             alt((
                 take_while1(|c: char| c != ';'),
@@ -176,6 +470,8 @@ fn parse_body_line(input: &str) -> IResult<&str, BodyLine> {
             let (_, body_line) = alt((
                 tag("ret").map(|_| BodyLine::Return),
                 parse_goto.map(BodyLine::Goto),
+                separated_pair(parse_predicate, space1, parse_operation)
+                .map(|(predicate, operation)| BodyLine::Guarded(predicate, operation)),
                 parse_register.map(BodyLine::Register),
                 parse_operation.map(BodyLine::Operation),
                 parse_unknown_line.map(BodyLine::Unknown),
@@ -187,112 +483,210 @@ fn parse_body_line(input: &str) -> IResult<&str, BodyLine> {
     })
 }
 
-#[derive(Debug, PartialEq)]
-pub(crate) enum BodyLine<'a> {
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum BodyLine<'a> {
     Register(Register<'a>),
     Operation(Operation<'a>),
     Label(&'a str),
+    Location(Location),
     Goto(Goto<'a>),
     Return,
     FunctionCall(FunctionCall<'a>),
     Unknown(&'a str),
+    /// A predicated instruction other than a branch, such as `@%p1 st.global.f32 [%rd1], %f1`.
+    Guarded(Predicate<'a>, Operation<'a>),
+    /// A comment on a line of its own or after an instruction, such as `// begin inline asm`.
+    Comment(Comment<'a>),
+    /// The `{` opening a nested scope, as inline assembly often does.
+    OpenBrace,
+    /// The `}` closing a nested scope.
+    CloseBrace,
 }
 
-#[derive(Debug, PartialEq)]
-pub(crate) enum Predicate<'a> {
+impl<'a> BodyLine<'a> {
+    /// Whether the line is an instruction, rather than a label, a `.reg` declaration or
+    /// another directive such as `.loc`.
+    pub fn is_instruction(&self) -> bool {
+        match self {
+            BodyLine::Operation(operation) => !operation.operation().starts_with('.'),
+            BodyLine::Goto(_) | BodyLine::Return | BodyLine::FunctionCall(_) | BodyLine::Guarded(..) => true,
+            BodyLine::Unknown(line) => !line.trim_start().starts_with('.'),
+            BodyLine::Register(_) | BodyLine::Label(_) | BodyLine::Location(_) | BodyLine::Comment(_) => false,
+            BodyLine::OpenBrace | BodyLine::CloseBrace => false,
+        }
+    }
+}
+
+/// The guard of a branch: `@%p1` is `True("%p1")` and `@!%p1` is `False("%p1")`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Predicate<'a> {
     True(&'a str),
     False(&'a str),
 }
 
+impl<'a> Predicate<'a> {
+    pub fn register(&self) -> &'a str {
+        match self {
+            Predicate::True(register) | Predicate::False(register) => register,
+        }
+    }
+
+    pub fn is_negated(&self) -> bool {
+        matches!(self, Predicate::False(_))
+    }
+}
+
+#[cfg(test)]
+mod test_parse_operation {
+    use super::{parse_body_line, parse_function_call, parse_goto, parse_operation, BodyLine, Operation, Predicate, Register};
+
+    #[test]
+    fn with_arguments() {
+        assert_eq!(
+            parse_operation("add.s64 \t%rd1, %rd5, %rd7"),
+            Ok(("", Operation::new("add.s64", "%rd1, %rd5, %rd7")))
+        );
+    }
+
+    #[test]
+    fn without_arguments() {
+        let (_, operation) = parse_operation("wgmma.fence.sync.aligned").unwrap();
+        assert_eq!(operation, Operation::new("wgmma.fence.sync.aligned", ""));
+        assert_eq!(operation.operands(), Ok(vec![]));
+    }
+
+    #[test]
+    fn sub_qualified_state_space() {
+        assert_eq!(
+            parse_operation("mbarrier.init.shared::cta.b64 [%r2], 1"),
+            Ok(("", Operation::new("mbarrier.init.shared::cta.b64", "[%r2], 1")))
+        );
+    }
+
+    #[test]
+    fn instructions() {
+        let is_instruction = |line| parse_body_line(line).unwrap().1.is_instruction();
+        assert!(is_instruction("add.s32 %r1, %r2, 1;"));
+        assert!(is_instruction("@%p1 st.global.f32 [%rd1], %f1;"));
+        assert!(is_instruction("@%p1 bra $L__BB0_2;"));
+        assert!(is_instruction("ret;"));
+        assert!(!is_instruction("$L__BB0_2:"));
+        assert!(!is_instruction(".reg .b32 %r<5>;"));
+        assert!(!is_instruction(".loc 1 4 5"));
+        assert!(!is_instruction(".pragma \"nounroll\";"));
+    }
+
+    #[test]
+    fn accessors() {
+        let register = Register { raw_string: ".b32 \t%r<5>" };
+        assert_eq!(register.ty(), Some(".b32"));
+        assert_eq!(register.name(), Some("%r"));
+        assert_eq!(register.count(), Some(5));
+
+        let list = Register { raw_string: ".b32 %r1, %r2,%temp" };
+        assert_eq!(list.name(), Some("%r1"));
+        assert_eq!(list.count(), None);
+        assert_eq!(list.names().collect::<Vec<_>>(), [("%r1", None), ("%r2", None), ("%temp", None)]);
+        let mixed = Register { raw_string: ".pred %p<3>, %q" };
+        assert_eq!(mixed.names().collect::<Vec<_>>(), [("%p", Some(3)), ("%q", None)]);
+
+        let (_, goto) = parse_goto("@!%p1 bra $L__BB0_2").unwrap();
+        assert_eq!(goto.label(), "L__BB0_2");
+        assert_eq!(goto.predicate().map(Predicate::register), Some("%p1"));
+        assert!(goto.predicate().is_some_and(Predicate::is_negated));
+    }
+
+    #[test]
+    fn guarded() {
+        assert!(parse_operation("@%p1 st.global.f32 [%rd1], %f1").is_err());
+    }
+
+    #[test]
+    fn guarded_line() {
+        assert_eq!(
+            parse_body_line("@!%p1 st.global.f32 [%rd1], %f1;"),
+            Ok(("", BodyLine::Guarded(Predicate::False("%p1"), Operation::new("st.global.f32", "[%rd1], %f1"))))
+        );
+        assert_eq!(
+            parse_body_line("st.global.f32 [%rd1], %f1;"),
+            Ok(("", BodyLine::Operation(Operation::new("st.global.f32", "[%rd1], %f1"))))
+        );
+    }
+
+    #[test]
+    fn call_parameters() {
+        let input = "{ // callseq 1, 0\n\t.param .b64 param0;\n\t.param .b64 retval0;\n\tcall.uni (retval0), \n\tnext, \n\t(\n\tparam0\n\t);\n\tld.param.b64 \t%rd11, [retval0+0];\n\t} // callseq 1";
+        let (_, call) = parse_function_call(input).unwrap();
+        assert_eq!(call.callee(), "next");
+        assert_eq!(call.return_parameters(), ["retval0"]);
+        assert_eq!(call.argument_parameters(), ["param0"]);
+
+        let (_, call) = parse_function_call("{ // callseq 0, 0\n\tcall.uni \n\tpanic, \n\t(\n\tparam0, \n\tparam1\n\t);\n\t} // callseq 0").unwrap();
+        assert_eq!(call.callee(), "panic");
+        assert!(call.return_parameters().is_empty());
+        assert_eq!(call.argument_parameters(), ["param0", "param1"]);
+    }
+}
+
 #[cfg(test)]
 mod test_iterator {
     use crate::{
-        parser::{PtxParser, ptx_file::FunctionOrGlobal},
+        parser::{
+            function::Function,
+            visit::{walk_function, walk_parser, Visitor},
+            PtxParser,
+        },
         ptx_files::{a, kernel, _EXAMPLE_FILE},
     };
 
-    use super::{BodyLine, Operation};
+    use super::{BodyLine, FunctionCall, Operation};
+
+    struct ShowBodyLines;
+
+    impl<'a> Visitor<'a> for ShowBodyLines {
+        fn visit_function(&mut self, function: &Function<'a>) {
+            dbg!(function);
+            walk_function(self, function);
+        }
+
+        fn visit_line(&mut self, line: &BodyLine<'a>) {
+            dbg!(line);
+        }
+    }
+
+    struct ShowUnknownBodyLines;
+
+    impl<'a> Visitor<'a> for ShowUnknownBodyLines {
+        fn visit_unknown(&mut self, raw_string: &'a str) {
+            dbg!("Unknown line", raw_string);
+        }
+    }
+
+    struct ShowOperations;
+
+    impl<'a> Visitor<'a> for ShowOperations {
+        fn visit_operation(&mut self, operation: &Operation<'a>) {
+            dbg!(operation);
+        }
+
+        /// Only the operations of the body itself, not those inside call sequences.
+        fn visit_call(&mut self, _call: &FunctionCall<'a>) {}
+    }
 
     fn show_body_lines(input: &str) {
         let ptx: PtxParser = input.try_into().unwrap();
-        ptx
-        .into_iter()
-            .filter_map(|line| line.ok())
-        .filter_map(|(_, function)| {
-            if let FunctionOrGlobal::Function(function) = function {
-                Some(function)
-            } else {
-                None
-            }
-        })
-        .for_each(|function| {
-            dbg!(&function);
-            if let Some(body) = function.body {
-                for line in body {
-                    if let Ok(line) = line {
-                        dbg!(line.1);
-                    }
-                }
-            }
-        });
+        walk_parser(&mut ShowBodyLines, ptx);
     }
 
     fn show_unknown_body_lines(input: &str) {
         let ptx: PtxParser = input.try_into().unwrap();
-        ptx
-        .into_iter()
-            .filter_map(|line| line.ok())
-        .filter_map(|(_, function)| {
-            if let FunctionOrGlobal::Function(function) = function {
-                Some(function)
-            } else {
-                None
-            }
-        })
-        .for_each(|function| {
-            if let Some(body) = function.body {
-                body.filter_map(Result::ok)
-                    .map(|(_, line)| line)
-                    .for_each(|line| {
-                        if let BodyLine::Unknown(raw_string) = line {
-                            dbg!("Unknown line", raw_string);
-                        }
-                    })
-            }
-        });
-    }
-
-    impl<'a> BodyLine<'a> {
-        pub(crate) fn operation(self) -> Option<Operation<'a>> {
-            match self {
-                BodyLine::Operation(operation) => Some(operation),
-                _ => None,
-            }
-        }
+        walk_parser(&mut ShowUnknownBodyLines, ptx);
     }
 
     fn show_operations(input: &str) {
         let ptx: PtxParser = input.try_into().unwrap();
-        ptx
-        .into_iter()
-        .filter_map(|line| line.ok())
-        .filter_map(|(_, function)| {
-            if let FunctionOrGlobal::Function(function) = function {
-                Some(function)
-            } else {
-                None
-            }
-        })
-        .filter_map(|function| function.body)
-        .for_each(|body| {
-            body
-            .filter_map(Result::ok)
-            .map(|(_, line)| line)
-            .filter_map(|line| line.operation())
-            .for_each(|operation| {
-                dbg!(operation);
-            })
-        })
+        walk_parser(&mut ShowOperations, ptx);
     }
 
     #[test]
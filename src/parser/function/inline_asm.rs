@@ -0,0 +1,164 @@
+use core::ops::Range;
+
+use super::body::{BodyLine, Operation};
+use crate::parser::comment::Comment;
+
+const BEGIN: &str = "begin inline asm";
+const END: &str = "end inline asm";
+
+/// User-written PTX which nvcc or rustc wrapped in `// begin inline asm` and
+/// `// end inline asm` comments.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InlineAsm<'a> {
+    lines: Vec<BodyLine<'a>>,
+    /// The text of the two marker comments.
+    markers: (&'a str, &'a str),
+}
+
+impl<'a> InlineAsm<'a> {
+    /// The lines between the two marker comments.
+    pub fn lines(&self) -> &[BodyLine<'a>] {
+        &self.lines
+    }
+
+    /// The instructions of the region, including predicated ones without their guard.
+    pub fn operations(&self) -> impl Iterator<Item = &Operation<'a>> {
+        self.lines.iter().filter_map(|line| match line {
+            BodyLine::Operation(operation) | BodyLine::Guarded(_, operation) => Some(operation),
+            _ => None,
+        })
+    }
+
+    /// The byte range within `source`, the text the module was parsed from, from the
+    /// line after the begin marker up to the end marker.
+    pub fn span_in(&self, source: &str) -> Option<Range<usize>> {
+        let begin = crate::parser::span_in(self.markers.0, source)?.end;
+        let end = crate::parser::span_in(self.markers.1, source)?.start.checked_sub("//".len())?;
+        let start = begin + source[begin..end].find('\n').map_or(0, |newline| newline + 1);
+        Some(start..end)
+    }
+}
+
+/// Finds every run of `lines` enclosed by inline assembly markers. A region whose end
+/// marker is missing is ignored.
+pub(super) fn find_inline_asm<'a>(lines: &[BodyLine<'a>]) -> Vec<InlineAsm<'a>> {
+    let mut regions = Vec::new();
+    let mut open = None;
+    for (index, line) in lines.iter().enumerate() {
+        let BodyLine::Comment(Comment::Line(text)) = line else {
+            continue;
+        };
+        match text.trim() {
+            BEGIN => open = Some((index, *text)),
+            END => {
+                if let Some((begin, marker)) = open.take() {
+                    let lines = lines[begin + 1..index].to_vec();
+                    regions.push(InlineAsm { lines, markers: (marker, text) });
+                }
+            }
+            _ => (),
+        }
+    }
+    regions
+}
+
+#[cfg(test)]
+mod test_inline_asm {
+    #[cfg(feature = "std")]
+    use crate::{
+        parser::{
+            function::{body::FunctionBody, Function},
+            instruction::Operand,
+            visit::VisitorMut,
+            PtxFile,
+        },
+        ptx_files::inline_asm,
+    };
+    use crate::parser::function::body::{BodyLine, FunctionBody as Body, Operation};
+
+    use super::find_inline_asm;
+
+    const BODY: &str = "
+\tmov.u32 \t%r1, %tid.x;
+\t// begin inline asm
+\tmov.u32 %r2, %laneid;
+\t// end inline asm
+\tadd.s32 \t%r3, %r1, %r2;
+\t// begin inline asm
+\t{ .reg .pred p; setp.ne.b32 p, %r3, 0; selp.u32 %r4, 1, 0, p; }
+\t// end inline asm
+\t// begin inline asm
+\tbrkpt;
+";
+
+    fn lines(body: &str) -> Vec<BodyLine<'_>> {
+        Body::new(Some(body)).parsed().0
+    }
+
+    #[test]
+    fn regions() {
+        let lines = lines(BODY);
+        let regions = find_inline_asm(&lines);
+        assert_eq!(regions.len(), 2);
+        assert_eq!(regions[0].lines(), [BodyLine::Operation(Operation::new("mov.u32", "%r2, %laneid"))]);
+        assert_eq!(regions[0].span_in(BODY).map(|span| &BODY[span]), Some("\tmov.u32 %r2, %laneid;\n\t"));
+        assert_eq!(regions[1].lines().first(), Some(&BodyLine::OpenBrace));
+        assert_eq!(regions[1].lines().last(), Some(&BodyLine::CloseBrace));
+        let opcodes = regions[1].operations().map(Operation::opcode).collect::<Vec<_>>();
+        assert_eq!(opcodes, ["setp", "selp"]);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn kernels_with_inline_asm() {
+        let ptx: PtxFile = inline_asm::_PTX.try_into().unwrap();
+        let regions = ptx.inline_asm();
+        assert!(regions.iter().all(|(function, _)| *function == "lane_ids"));
+        let registers = regions
+            .iter()
+            .flat_map(|(_, region)| region.operations())
+            .filter_map(|operation| operation.operands().ok()?.get(1)?.name())
+            .collect::<Vec<_>>();
+        assert_eq!(registers, ["%laneid", "%smid"]);
+        let span = regions[0].1.span_in(inline_asm::_PTX).unwrap();
+        assert_eq!(inline_asm::_PTX[span].trim(), "mov.u32 %r2, %laneid;");
+    }
+
+    /// Renames one register wherever it is an operand.
+    #[cfg(feature = "std")]
+    struct Rename(&'static str, &'static str);
+
+    #[cfg(feature = "std")]
+    impl<'a> VisitorMut<'a> for Rename {
+        fn visit_operand_mut(&mut self, operand: &mut Operand<'a>) {
+            if *operand == Operand::Register(self.0) {
+                *operand = Operand::Register(self.1);
+            }
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn rewritten_bodies() {
+        let mut ptx: PtxFile = inline_asm::_PTX.try_into().unwrap();
+        Rename("%r1", "%r9").visit_module_mut(&mut ptx);
+        assert_eq!(ptx.inline_asm().len(), 2);
+        Rename("%r2", "%r8").visit_module_mut(&mut ptx);
+        let regions = ptx.inline_asm();
+        assert_eq!(regions.len(), 2);
+        assert_eq!(regions[0].1.operations().next(), Some(&Operation::new("mov.u32", "%r8, %laneid")));
+        assert!(regions[1].1.operations().any(|operation| operation.to_string().contains("%smid")));
+
+        let printed = ptx.to_string();
+        let reparsed = PtxFile::try_from(printed.as_str()).unwrap();
+        assert_eq!(reparsed.inline_asm().len(), 2);
+        let built = Function { signature: ptx.functions()[0].signature.clone(), body: Some(FunctionBody::from_lines([])) };
+        assert_eq!(built.inline_asm().count(), 0);
+    }
+
+    #[test]
+    fn foreign_source() {
+        let lines = lines(BODY);
+        assert_eq!(find_inline_asm(&lines)[0].span_in("unrelated"), None);
+    }
+}
@@ -1,27 +1,127 @@
-pub(super) mod body;
+use super::instruction::types::ScalarType;
+
+pub mod body;
+pub mod inline_asm;
 pub(crate) mod parse;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Function<'a> {
     pub signature: FunctionSignature<'a>,
     pub body: Option<body::FunctionBody<'a>>,
 }
 
-#[derive(Debug, PartialEq)]
+impl<'a> Function<'a> {
+    /// The operations of the body, including predicated ones without their guard and
+    /// those inside call sequences, skipping lines which fail to parse.
+    pub fn operations(&self) -> impl Iterator<Item = body::Operation<'a>> {
+        self.body
+            .clone()
+            .into_iter()
+            .flatten()
+            .filter_map(Result::ok)
+            .flat_map(|(_, line)| -> Vec<body::Operation<'a>> {
+                match line {
+                    body::BodyLine::Operation(operation) => vec![operation],
+                    body::BodyLine::FunctionCall(call) => call
+                        .lines()
+                        .filter_map(Result::ok)
+                        .filter_map(|(_, line)| match line {
+                            body::BodyLine::Operation(operation) | body::BodyLine::Guarded(_, operation) => Some(operation),
+                            _ => None,
+                        })
+                        .collect(),
+                    body::BodyLine::Guarded(_, operation) => vec![operation],
+                    _ => Vec::new(),
+                }
+            })
+    }
+
+    /// The regions of the body between `// begin inline asm` and `// end inline asm`,
+    /// found on its parsed lines so that they survive a `VisitorMut` changing them.
+    pub fn inline_asm(&self) -> impl Iterator<Item = inline_asm::InlineAsm<'a>> {
+        let lines = self.body.as_ref().map(|body| body.parsed().0).unwrap_or_default();
+        inline_asm::find_inline_asm(&lines).into_iter()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
 pub struct FunctionSignature<'a> {
     pub visible: bool,
     pub entry: bool,
     pub return_value: Option<ReturnValue<'a>>,
     pub name: &'a str,
     pub parameters: Option<Parameters<'a>>,
+    pub directives: Option<Directives<'a>>,
 }
 
-#[derive(Debug, PartialEq)]
+/// The performance tuning directives between the parameters and the body,
+/// e.g. `.maxntid 256, 1, 1` or `.reqnctapercluster 2, 1, 1`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Directives<'a> {
+    raw_string: &'a str,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Directive<'a> {
+    Maxntid([u32; 3]),
+    Reqntid([u32; 3]),
+    Minnctapersm(u32),
+    Maxnreg(u32),
+    /// `.explicitcluster`: the kernel must be launched with a cluster.
+    ExplicitCluster,
+    Reqnctapercluster([u32; 3]),
+    Maxclusterrank(u32),
+    Other(&'a str),
+}
+
+impl<'a> Directives<'a> {
+    pub fn raw_string(&self) -> &'a str {
+        self.raw_string
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Directive<'a>> {
+        self.raw_string
+            .split('.')
+            .map(str::trim)
+            .filter(|directive| !directive.is_empty())
+            .map(|directive| {
+                let (name, values) = directive
+                    .split_once(char::is_whitespace)
+                    .unwrap_or((directive, ""));
+                let mut values = values
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|value| !value.is_empty())
+                    .map(str::parse::<u32>);
+                let mut dimensions = [1; 3];
+                for (dimension, value) in dimensions.iter_mut().zip(values.by_ref()) {
+                    match value {
+                        Ok(value) => *dimension = value,
+                        Err(_) => return Directive::Other(directive),
+                    }
+                }
+                match name {
+                    "maxntid" => Directive::Maxntid(dimensions),
+                    "reqntid" => Directive::Reqntid(dimensions),
+                    "minnctapersm" => Directive::Minnctapersm(dimensions[0]),
+                    "maxnreg" => Directive::Maxnreg(dimensions[0]),
+                    "explicitcluster" => Directive::ExplicitCluster,
+                    "reqnctapercluster" => Directive::Reqnctapercluster(dimensions),
+                    "maxclusterrank" => Directive::Maxclusterrank(dimensions[0]),
+                    _ => Directive::Other(directive),
+                }
+            })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct ReturnValue<'a> {
     raw_string: &'a str,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Parameter<'a> {
     pub name: &'a str,
     pub ty: &'a str,
@@ -29,12 +129,37 @@ pub struct Parameter<'a> {
     raw_string: &'a str,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Parameters<'a> {
     pub params: Vec<Parameter<'a>>,
     raw_string: &'a str,
 }
 
+impl<'a> ReturnValue<'a> {
+    /// The declaration between the parentheses, e.g. `.param .b64 func_retval0`.
+    pub fn raw_string(&self) -> &'a str {
+        self.raw_string
+    }
+}
+
+impl<'a> Parameter<'a> {
+    /// The whole declaration, e.g. `.param .b64 _foo_param_0`.
+    pub fn raw_string(&self) -> &'a str {
+        self.raw_string
+    }
+}
+
+impl<'a> Parameters<'a> {
+    pub fn params(&self) -> &[Parameter<'a>] {
+        &self.params
+    }
+
+    /// The declarations between the parentheses.
+    pub fn raw_string(&self) -> &'a str {
+        self.raw_string
+    }
+}
+
 impl Parameters<'_> {
     fn parse(&mut self) {
         for p_raw_str in self.raw_string.split('\n') {
@@ -46,31 +171,16 @@ impl Parameters<'_> {
             }
             let ty = parts[1];
             let name = parts[2];
-            let size = match ty {
-                ".s8" => 1,
-                ".s16" => 2,
-                ".s32" => 4,
-                ".s64" => 8,
-                ".u8" => 1,
-                ".u16" => 2,
-                ".u32" => 4,
-                ".u64" => 8,
-                ".f16" => 2,
-                ".f16x2" => 4,
-                ".f32" => 4,
-                ".f64" => 8,
-                ".b8" => 1,
-                ".b16" => 2,
-                ".b32" => 4,
-                ".b64" => 8,
-                ".b128" => 16,
-                //TODO: more types
-                _ => panic!("unknown type: {}", ty),
+            let size = match ty.strip_prefix('.').and_then(ScalarType::from_modifier) {
+                Some(scalar) => scalar.size(),
+                None if matches!(ty, ".texref" | ".samplerref" | ".surfref") => 8,
+                // Declarations this does not understand are left out rather than guessed at.
+                None => continue,
             };
             self.params.push(Parameter {
-                name: name,
-                ty: ty,
-                size: size,
+                name,
+                ty,
+                size,
                 raw_string: p_raw_str,
             });
         }
@@ -79,7 +189,7 @@ impl Parameters<'_> {
 #[cfg(test)]
 mod test_parse_function_signature {
 
-    use crate::parser::function::{parse::parse_function_signature, Parameter};
+    use crate::parser::function::{parse::parse_function_signature, Directive, Parameter};
 
     use super::{FunctionSignature, Parameters, ReturnValue};
 
@@ -97,6 +207,7 @@ mod test_parse_function_signature {
                     return_value: None,
                     name: "_Z6kernelPiS_i",
                     parameters: None,
+                    directives: None,
                 }
             ))
         )
@@ -116,6 +227,7 @@ mod test_parse_function_signature {
                     return_value: None,
                     name: "_Z6kernelPiS_i",
                     parameters: None,
+                    directives: None,
                 }
             ))
         )
@@ -138,6 +250,7 @@ mod test_parse_function_signature {
                         raw_string: "hi",
                         params: vec![],
                     }),
+                    directives: None,
                 }
             ))
         )
@@ -188,6 +301,7 @@ mod test_parse_function_signature {
                             },
                         ],
                     }),
+                    directives: None,
                 }
             ))
         )
@@ -230,11 +344,54 @@ mod test_parse_function_signature {
                                 raw_string: ".param .b64 _foo_param_1",
                             },
                         ]
-                    })
+                    }),
+                    directives: None,
                 }
             ))
         )
     }
+
+    #[test]
+    fn cluster_directives() {
+        let input = ".visible .entry reduce(
+	.param .u64 reduce_param_0
+)
+.explicitcluster
+.reqnctapercluster 2, 1, 1
+.maxntid 128
+{";
+        let (input, signature) = parse_function_signature(input).unwrap();
+        assert_eq!(input.trim_start(), "{");
+        assert_eq!(
+            signature.directives.unwrap().iter().collect::<Vec<_>>(),
+            [
+                Directive::ExplicitCluster,
+                Directive::Reqnctapercluster([2, 1, 1]),
+                Directive::Maxntid([128, 1, 1]),
+            ]
+        );
+    }
+
+    #[test]
+    fn parameter_types() {
+        let input = ".func scale(
+	.param .bf16 scale_param_0,
+	.param .pred scale_param_1,
+	.param .f8x4 scale_param_2,
+	.param .b64 scale_param_3
+)";
+        let (_, signature) = parse_function_signature(input).unwrap();
+        let params = signature.parameters.unwrap();
+        let params = params.params().iter().map(|param| (param.name, param.ty, param.size)).collect::<Vec<_>>();
+        assert_eq!(
+            params,
+            [
+                ("scale_param_0", ".bf16", 2),
+                ("scale_param_1", ".pred", 1),
+                ("scale_param_3", ".b64", 8),
+            ]
+        );
+    }
 }
 
 #[cfg(test)]
@@ -257,9 +414,7 @@ mod test_parse_function_body {
             body,
             Ok((
                 "",
-                FunctionBody {
-                    body: Some(".reg .b32 %r<3>")
-                }
+                FunctionBody::new(Some(".reg .b32 %r<3>"))
             ))
         )
     }
@@ -286,6 +441,7 @@ mod test_parse_function {
                         return_value: None,
                         name: "_Z6kernelPiS_i",
                         parameters: None,
+                        directives: None,
                     },
                     body: None,
                 }
@@ -308,10 +464,9 @@ mod test_parse_function {
                         return_value: None,
                         name: "_Z6kernelPiS_i",
                         parameters: None,
+                        directives: None,
                     },
-                    body: Some(FunctionBody {
-                        body: Some(" \n foo \n bar ")
-                    }),
+                    body: Some(FunctionBody::new(Some(" \n foo \n bar "))),
                 }
             ))
         )
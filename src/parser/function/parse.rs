@@ -1,10 +1,11 @@
 use nom::{
     branch::alt,
-    bytes::complete::tag,
+    bytes::complete::{tag, take_while},
     character::complete::char,
-    character::complete::{multispace0, space0, space1},
-    combinator::{map, opt, value},
-    sequence::preceded,
+    character::complete::{alpha1, multispace0, space0, space1},
+    combinator::{map, opt, recognize, value},
+    multi::many1_count,
+    sequence::{preceded, tuple},
     IResult, Parser,
 };
 
@@ -13,9 +14,9 @@ use crate::parser::{
     parse_parenthesized_naive,
 };
 
-use super::{body::FunctionBody, Function, FunctionSignature, Parameter, Parameters, ReturnValue};
+use super::{body::FunctionBody, Directives, Function, FunctionSignature, Parameter, Parameters, ReturnValue};
 
-pub(crate) fn parse_function(input: &str) -> IResult<&str, Function> {
+pub(crate) fn parse_function(input: &str) -> IResult<&str, Function<'_>> {
     let (input, signature) = parse_function_signature(input)?;
     let (input, body) = preceded(
         opt(many1_comments_or_whitespace),
@@ -24,15 +25,13 @@ pub(crate) fn parse_function(input: &str) -> IResult<&str, Function> {
     Ok((input, Function { signature, body }))
 }
 
-pub(super) fn parse_function_body(input: &str) -> IResult<&str, FunctionBody> {
+pub(super) fn parse_function_body(input: &str) -> IResult<&str, FunctionBody<'_>> {
     parse_braced_balanced
-        .map(|raw_string| FunctionBody {
-            body: Some(raw_string),
-        })
+        .map(|raw_string| FunctionBody::new(Some(raw_string)))
         .parse(input)
 }
 
-pub(super) fn parse_function_signature(input: &str) -> IResult<&str, FunctionSignature> {
+pub(crate) fn parse_function_signature(input: &str) -> IResult<&str, FunctionSignature<'_>> {
     let (input, (visible, entry)) = alt((
         value((true, true), tag(".visible").and(space1).and(tag(".entry"))),
         value((false, false), tag(".func")),
@@ -55,6 +54,17 @@ pub(super) fn parse_function_signature(input: &str) -> IResult<&str, FunctionSig
         })),
     )(input)?;
 
+    let (input, directives) = opt(preceded(
+        multispace0,
+        recognize(many1_count(tuple((
+            multispace0,
+            char('.'),
+            alpha1,
+            take_while(|c: char| c.is_ascii_digit() || c == ',' || c == ' ' || c == '\t'),
+        ))))
+        .map(|raw_string| Directives { raw_string }),
+    ))(input)?;
+
     Ok((
         input,
         FunctionSignature {
@@ -63,6 +73,7 @@ pub(super) fn parse_function_signature(input: &str) -> IResult<&str, FunctionSig
             return_value,
             name,
             parameters,
+            directives,
         },
     ))
 }
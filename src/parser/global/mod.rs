@@ -1,8 +1,63 @@
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Global<'a> {
     raw_string: &'a str,
 }
 
+impl<'a> Global<'a> {
+    /// The declaration without its trailing `;`, e.g. `.global .align 4 .u32 counter`.
+    pub fn raw_string(&self) -> &'a str {
+        self.raw_string
+    }
+
+    /// Parses a `.texref`, `.samplerref` or `.surfref` declaration.
+    pub fn opaque(&self) -> Option<OpaqueDeclaration<'a>> {
+        parse::parse_opaque_declaration(self.raw_string)
+            .ok()
+            .map(|(_, declaration)| declaration)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OpaqueKind {
+    Texture,
+    Sampler,
+    Surface,
+}
+
+/// A texture, sampler or surface reference such as
+/// `.global .samplerref smp = { addr_mode_0 = clamp_to_edge, filter_mode = linear };`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OpaqueDeclaration<'a> {
+    pub kind: OpaqueKind,
+    pub name: &'a str,
+    pub fields: Vec<OpaqueField<'a>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum OpaqueField<'a> {
+    /// `addr_mode_0`, `addr_mode_1` or `addr_mode_2`.
+    AddressMode { dimension: u8, mode: AddressMode },
+    FilterMode(FilterMode),
+    NormalizedCoords(bool),
+    Other { name: &'a str, value: &'a str },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AddressMode {
+    Wrap,
+    Mirror,
+    ClampOgl,
+    ClampToEdge,
+    ClampToBorder,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilterMode {
+    Nearest,
+    Linear,
+}
+
 pub(crate) mod parse;
 
 #[cfg(test)]
@@ -16,3 +71,52 @@ mod test_parse_global {
         assert_eq!(parse_global(input), expected)
     }
 }
+
+#[cfg(test)]
+mod test_opaque_declaration {
+    use crate::parser::global::{
+        parse::parse_global, AddressMode, FilterMode, OpaqueDeclaration, OpaqueField, OpaqueKind,
+    };
+
+    #[test]
+    fn texref() {
+        let (_, global) = parse_global(".global .texref tex_a;").unwrap();
+        assert_eq!(
+            global.opaque(),
+            Some(OpaqueDeclaration { kind: OpaqueKind::Texture, name: "tex_a", fields: vec![] })
+        );
+    }
+
+    #[test]
+    fn samplerref_with_fields() {
+        let input = ".global .samplerref smp = { addr_mode_0 = clamp_to_edge, addr_mode_1 = wrap,
+    filter_mode = linear, normalized_coords = 1, border_color = 0 };";
+        let (_, global) = parse_global(input).unwrap();
+        assert_eq!(
+            global.opaque(),
+            Some(OpaqueDeclaration {
+                kind: OpaqueKind::Sampler,
+                name: "smp",
+                fields: vec![
+                    OpaqueField::AddressMode { dimension: 0, mode: AddressMode::ClampToEdge },
+                    OpaqueField::AddressMode { dimension: 1, mode: AddressMode::Wrap },
+                    OpaqueField::FilterMode(FilterMode::Linear),
+                    OpaqueField::NormalizedCoords(true),
+                    OpaqueField::Other { name: "border_color", value: "0" },
+                ],
+            })
+        );
+    }
+
+    #[test]
+    fn surfref() {
+        let (_, global) = parse_global(".global .surfref surf_a;").unwrap();
+        assert_eq!(global.opaque().map(|declaration| declaration.kind), Some(OpaqueKind::Surface));
+    }
+
+    #[test]
+    fn data() {
+        let (_, global) = parse_global(".global .align 8 .b8 anon_$_af85108618407798382bf1e18eed69f7_$_2;").unwrap();
+        assert_eq!(global.opaque(), None);
+    }
+}
@@ -1,13 +1,18 @@
 use nom::{
+    branch::alt,
     bytes::complete::{tag, take_while1},
-    character::complete::{char, space1},
-    sequence::delimited,
+    character::complete::{char, multispace0, space1},
+    combinator::{eof, map_opt, opt, value},
+    multi::fold_many0,
+    sequence::{delimited, preceded, separated_pair, terminated},
     IResult, Parser,
 };
 
-use super::Global;
+use crate::parser::parse_name;
 
-pub(crate) fn parse_global(input: &str) -> IResult<&str, Global> {
+use super::{AddressMode, FilterMode, Global, OpaqueDeclaration, OpaqueField, OpaqueKind};
+
+pub(crate) fn parse_global(input: &str) -> IResult<&str, Global<'_>> {
     delimited(
         tag(".global").and(space1),
         take_while1(|c: char| c != ';')
@@ -15,3 +20,73 @@ pub(crate) fn parse_global(input: &str) -> IResult<&str, Global> {
         char(';'),
     )(input)
 }
+
+fn parse_opaque_field(input: &str) -> IResult<&str, OpaqueField<'_>> {
+    map_opt(
+        separated_pair(
+            parse_name,
+            delimited(multispace0, char('='), multispace0),
+            parse_name,
+        ),
+        |(name, field_value)| {
+            Some(match name {
+                "addr_mode_0" | "addr_mode_1" | "addr_mode_2" => OpaqueField::AddressMode {
+                    dimension: name.as_bytes()[10] - b'0',
+                    mode: match field_value {
+                        "wrap" => AddressMode::Wrap,
+                        "mirror" => AddressMode::Mirror,
+                        "clamp_ogl" => AddressMode::ClampOgl,
+                        "clamp_to_edge" => AddressMode::ClampToEdge,
+                        "clamp_to_border" => AddressMode::ClampToBorder,
+                        _ => return None,
+                    },
+                },
+                "filter_mode" => OpaqueField::FilterMode(match field_value {
+                    "nearest" => FilterMode::Nearest,
+                    "linear" => FilterMode::Linear,
+                    _ => return None,
+                }),
+                "normalized_coords" => OpaqueField::NormalizedCoords(match field_value {
+                    "0" | "false" => false,
+                    "1" | "true" => true,
+                    _ => return None,
+                }),
+                name => OpaqueField::Other { name, value: field_value },
+            })
+        },
+    )(input)
+}
+
+pub(crate) fn parse_opaque_declaration(input: &str) -> IResult<&str, OpaqueDeclaration<'_>> {
+    let (input, (kind, name)) = alt((
+        value(OpaqueKind::Texture, tag(".texref")),
+        value(OpaqueKind::Sampler, tag(".samplerref")),
+        value(OpaqueKind::Surface, tag(".surfref")),
+    ))
+    .and(preceded(space1, parse_name))
+    .parse(input)?;
+    let (input, fields) = opt(preceded(
+        delimited(multispace0, char('='), multispace0),
+        delimited(
+            char('{').and(multispace0),
+            opt(parse_opaque_field.and(fold_many0(
+                preceded(delimited(multispace0, char(','), multispace0), parse_opaque_field),
+                Vec::new,
+                |mut fields, field| {
+                    fields.push(field);
+                    fields
+                },
+            ))),
+            multispace0.and(char('}')),
+        ),
+    ))(input)?;
+    let fields = match fields {
+        Some(Some((first, mut fields))) => {
+            fields.insert(0, first);
+            fields
+        }
+        _ => Vec::new(),
+    };
+    terminated(multispace0, eof)(input)
+        .map(|(input, _)| (input, OpaqueDeclaration { kind, name, fields }))
+}
@@ -0,0 +1,544 @@
+use crate::parser::function::body::Operation;
+
+use super::{
+    instruction_error,
+    memory::{AccessKind, MemoryAccess},
+    mma::{TensorInstruction, TensorOpcode},
+    types::StateSpace,
+    Operand,
+};
+
+/// How the completion of an asynchronous copy is observed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompletionMechanism {
+    /// `cp.async.commit_group` and `cp.async.wait_group`
+    AsyncGroup,
+    /// `.bulk_group`, with `cp.async.bulk.commit_group` and `cp.async.bulk.wait_group`
+    BulkGroup,
+    /// `.mbarrier::complete_tx::bytes`, observed through `mbarrier.try_wait` or `mbarrier.test_wait`
+    Mbarrier,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum AsyncOpcode {
+    CpAsync,
+    CpAsyncCommitGroup,
+    CpAsyncWaitGroup,
+    CpAsyncWaitAll,
+    CpAsyncMbarrierArrive,
+    CpAsyncBulk,
+    CpAsyncBulkTensor,
+    CpAsyncBulkCommitGroup,
+    CpAsyncBulkWaitGroup,
+    MbarrierInit,
+    MbarrierArrive,
+    MbarrierArriveDrop,
+    MbarrierExpectTx,
+    MbarrierTestWait,
+    MbarrierTryWait,
+    MbarrierInval,
+}
+
+/// An asynchronous copy or mbarrier instruction such as
+/// `cp.async.bulk.tensor.2d.shared::cluster.global.mbarrier::complete_tx::bytes [...], [...], [...];`.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct AsyncInstruction<'a> {
+    pub opcode: AsyncOpcode,
+    /// The state space written by a copy, or holding the mbarrier object.
+    pub destination_space: Option<StateSpace>,
+    pub source_space: Option<StateSpace>,
+    pub completion: Option<CompletionMechanism>,
+    /// The `.1d` to `.5d` dimensionality of a tensor copy.
+    pub dimensions: Option<u8>,
+    pub operands: Vec<Operand<'a>>,
+}
+
+impl AsyncInstruction<'_> {
+    /// The `N` of `wait_group N`: the number of most recent groups allowed to remain pending.
+    pub fn pending_groups(&self) -> Option<usize> {
+        match (self.opcode, self.operands.first()) {
+            (
+                AsyncOpcode::CpAsyncWaitGroup | AsyncOpcode::CpAsyncBulkWaitGroup,
+                Some(Operand::Immediate(n)),
+            ) => n.parse().ok(),
+            _ => None,
+        }
+    }
+
+    pub fn is_copy(&self) -> bool {
+        matches!(
+            self.opcode,
+            AsyncOpcode::CpAsync | AsyncOpcode::CpAsyncBulk | AsyncOpcode::CpAsyncBulkTensor
+        )
+    }
+}
+
+impl<'a> TryFrom<&Operation<'a>> for AsyncInstruction<'a> {
+    type Error = nom::Err<nom::error::Error<&'a str>>;
+
+    fn try_from(operation: &Operation<'a>) -> Result<Self, Self::Error> {
+        let error = || instruction_error(operation.operation());
+        let modifiers = operation.modifiers().collect::<Vec<_>>();
+        let opcode = match (operation.opcode(), modifiers.as_slice()) {
+            ("cp", ["async", "commit_group", ..]) => AsyncOpcode::CpAsyncCommitGroup,
+            ("cp", ["async", "wait_group", ..]) => AsyncOpcode::CpAsyncWaitGroup,
+            ("cp", ["async", "wait_all", ..]) => AsyncOpcode::CpAsyncWaitAll,
+            ("cp", ["async", "mbarrier", "arrive", ..]) => AsyncOpcode::CpAsyncMbarrierArrive,
+            ("cp", ["async", "bulk", "commit_group", ..]) => AsyncOpcode::CpAsyncBulkCommitGroup,
+            ("cp", ["async", "bulk", "wait_group", ..]) => AsyncOpcode::CpAsyncBulkWaitGroup,
+            ("cp", ["async", "bulk", "tensor", ..]) => AsyncOpcode::CpAsyncBulkTensor,
+            ("cp", ["async", "bulk", ..]) => AsyncOpcode::CpAsyncBulk,
+            ("cp", ["async", ..]) => AsyncOpcode::CpAsync,
+            ("mbarrier", ["init", ..]) => AsyncOpcode::MbarrierInit,
+            ("mbarrier", ["arrive", ..]) => AsyncOpcode::MbarrierArrive,
+            ("mbarrier", ["arrive_drop", ..]) => AsyncOpcode::MbarrierArriveDrop,
+            ("mbarrier", ["expect_tx", ..]) => AsyncOpcode::MbarrierExpectTx,
+            ("mbarrier", ["test_wait", ..]) => AsyncOpcode::MbarrierTestWait,
+            ("mbarrier", ["try_wait", ..]) => AsyncOpcode::MbarrierTryWait,
+            ("mbarrier", ["inval", ..]) => AsyncOpcode::MbarrierInval,
+            _ => return Err(error()),
+        };
+        let mut spaces = modifiers.iter().filter_map(|modifier| StateSpace::from_modifier(modifier));
+        let (destination_space, source_space) = (spaces.next(), spaces.next());
+        let completion = match opcode {
+            AsyncOpcode::CpAsync => Some(CompletionMechanism::AsyncGroup),
+            AsyncOpcode::CpAsyncBulk | AsyncOpcode::CpAsyncBulkTensor => {
+                modifiers.iter().find_map(|modifier| match *modifier {
+                    "bulk_group" => Some(CompletionMechanism::BulkGroup),
+                    modifier if modifier.starts_with("mbarrier::") => Some(CompletionMechanism::Mbarrier),
+                    _ => None,
+                })
+            }
+            _ => None,
+        };
+        let dimensions = modifiers.iter().find_map(|modifier| {
+            modifier
+            .strip_suffix('d')
+            .and_then(|n| n.parse().ok())
+            .filter(|n| (1..=5).contains(n))
+        });
+        Ok(AsyncInstruction {
+            opcode,
+            destination_space,
+            source_space,
+            completion,
+            dimensions,
+            operands: operation.operands()?,
+        })
+    }
+}
+
+/// A read of shared memory which may observe an asynchronous copy before it completes.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum AsyncCopyHazard<'a> {
+    ReadBeforeWait {
+        copy: Operation<'a>,
+        read: Operation<'a>,
+    },
+    NeverWaited {
+        copy: Operation<'a>,
+    },
+}
+
+/// A shared memory range as `base+offset` and an optional size in bytes.
+type Range<'a> = (&'a str, i64, Option<usize>);
+
+fn address_range<'a>(address: Option<&Operand<'a>>, size: Option<usize>) -> Option<Range<'a>> {
+    let Some(Operand::Address(address)) = address else {
+        return None;
+    };
+    Some(match address.split_once('+') {
+        Some((base, offset)) => (base.trim(), offset.trim().parse().ok()?, size),
+        None => (address.trim(), 0, size),
+    })
+}
+
+/// Copies and reads through different bases, or of unknown size, are assumed to overlap.
+fn may_overlap(copy: Option<Range>, read: Option<Range>) -> bool {
+    match (copy, read) {
+        (Some((copy_base, copy_offset, Some(copy_size))), Some((read_base, read_offset, Some(read_size))))
+            if copy_base == read_base =>
+        {
+            copy_offset < read_offset + read_size as i64 && read_offset < copy_offset + copy_size as i64
+        }
+        _ => true,
+    }
+}
+
+fn copied_range<'a>(copy: &Operation<'a>) -> Option<Range<'a>> {
+    let instruction = AsyncInstruction::try_from(copy).ok()?;
+    let size = match (instruction.opcode, instruction.operands.get(2)) {
+        (AsyncOpcode::CpAsync, Some(Operand::Immediate(size))) => size.parse().ok(),
+        _ => None,
+    };
+    address_range(instruction.operands.first(), size)
+}
+
+/// The shared memory range read by `operation`, or `None` if it does not read shared memory.
+fn shared_read<'a>(operation: &Operation<'a>) -> Option<Option<Range<'a>>> {
+    match (MemoryAccess::try_from(operation), TensorInstruction::try_from(operation)) {
+        (Ok(access), _) if access.kind == AccessKind::Load && access.state_space.is_shared() => {
+            Some(address_range(Some(&access.address), Some(access.size())))
+        }
+        (_, Ok(TensorInstruction { opcode: TensorOpcode::Ldmatrix | TensorOpcode::WmmaLoad(_), .. }))
+            if operation.modifiers().filter_map(StateSpace::from_modifier).any(StateSpace::is_shared) =>
+        {
+            Some(None)
+        }
+        _ => None,
+    }
+}
+
+/// The mbarrier an instruction signals or waits on: the last address among its operands,
+/// e.g. `%r9` for `[%r9]`.
+fn barrier<'a>(instruction: &AsyncInstruction<'a>) -> Option<&'a str> {
+    instruction.operands.iter().rev().find_map(|operand| match operand {
+        Operand::Address(address) => Some(address.trim()),
+        _ => None,
+    })
+}
+
+/// Follows `operations` in program order and reports every shared memory read which
+/// happens while an overlapping copy into shared memory has not been waited on, and
+/// every copy which is never waited on at all.
+///
+/// Copies completing through an mbarrier are only waited on by a wait on the same
+/// barrier address. Waits are assumed to succeed, so the loop around `mbarrier.try_wait`
+/// is not needed. Branches are not followed: the operations are taken as one straight
+/// line, so a wait on only one path, or a copy waited on in a later loop iteration, is
+/// not modeled.
+pub fn check_async_copies<'a>(operations: impl IntoIterator<Item = Operation<'a>>) -> Vec<AsyncCopyHazard<'a>> {
+    let mut uncommitted = Vec::new();
+    let mut groups: Vec<Vec<Operation<'a>>> = Vec::new();
+    let mut bulk_uncommitted = Vec::new();
+    let mut bulk_groups: Vec<Vec<Operation<'a>>> = Vec::new();
+    // Copies by the barrier they complete on, `None` when it could not be told.
+    let mut mbarrier: Vec<(Option<&'a str>, Operation<'a>)> = Vec::new();
+    let mut hazards = Vec::new();
+    for operation in operations {
+        let Ok(instruction) = AsyncInstruction::try_from(&operation) else {
+            if let Some(read) = shared_read(&operation) {
+                let pending = uncommitted
+                    .iter()
+                    .chain(groups.iter().flatten())
+                    .chain(&bulk_uncommitted)
+                    .chain(bulk_groups.iter().flatten())
+                    .chain(mbarrier.iter().map(|(_, copy)| copy))
+                    .find(|copy| may_overlap(copied_range(copy), read));
+                if let Some(copy) = pending {
+                    hazards.push(AsyncCopyHazard::ReadBeforeWait { copy: copy.clone(), read: operation });
+                }
+            }
+            continue;
+        };
+        match (instruction.opcode, instruction.completion) {
+            _ if instruction.is_copy() && !instruction.destination_space.is_some_and(StateSpace::is_shared) => (),
+            (_, Some(CompletionMechanism::AsyncGroup)) => uncommitted.push(operation),
+            (_, Some(CompletionMechanism::BulkGroup)) => bulk_uncommitted.push(operation),
+            (_, Some(CompletionMechanism::Mbarrier)) => mbarrier.push((barrier(&instruction), operation)),
+            (AsyncOpcode::CpAsyncCommitGroup, _) => groups.push(core::mem::take(&mut uncommitted)),
+            (AsyncOpcode::CpAsyncBulkCommitGroup, _) => bulk_groups.push(core::mem::take(&mut bulk_uncommitted)),
+            (AsyncOpcode::CpAsyncWaitGroup, _) => {
+                let pending = instruction.pending_groups().unwrap_or(0);
+                groups.drain(..groups.len().saturating_sub(pending));
+            }
+            (AsyncOpcode::CpAsyncBulkWaitGroup, _) => {
+                let pending = instruction.pending_groups().unwrap_or(0);
+                bulk_groups.drain(..bulk_groups.len().saturating_sub(pending));
+            }
+            (AsyncOpcode::CpAsyncWaitAll, _) => {
+                uncommitted.clear();
+                groups.clear();
+            }
+            (AsyncOpcode::CpAsyncMbarrierArrive, _) => {
+                let barrier = barrier(&instruction);
+                let copies = uncommitted.drain(..).chain(groups.drain(..).flatten());
+                mbarrier.extend(copies.map(|copy| (barrier, copy)));
+            }
+            (AsyncOpcode::MbarrierTestWait | AsyncOpcode::MbarrierTryWait, _) => {
+                let waited = barrier(&instruction);
+                mbarrier.retain(|(barrier, _)| barrier.is_some() && waited.is_some() && *barrier != waited);
+            }
+            _ => (),
+        }
+    }
+    hazards.extend(
+        uncommitted
+            .into_iter()
+            .chain(groups.into_iter().flatten())
+            .chain(bulk_uncommitted)
+            .chain(bulk_groups.into_iter().flatten())
+            .chain(mbarrier.into_iter().map(|(_, copy)| copy))
+            .map(|copy| AsyncCopyHazard::NeverWaited { copy }),
+    );
+    hazards
+}
+
+#[cfg(test)]
+mod test_async_instruction {
+    use crate::parser::{
+        function::body::Operation,
+        instruction::{
+            async_copy::{AsyncInstruction, AsyncOpcode, CompletionMechanism},
+            types::StateSpace,
+            Operand,
+        },
+    };
+
+    fn instruction(line: &str) -> AsyncInstruction<'_> {
+        let (operation, arguments) = line.split_once(' ').unwrap_or((line, ""));
+        AsyncInstruction::try_from(&Operation::new(operation, arguments)).unwrap()
+    }
+
+    #[test]
+    fn cp_async() {
+        assert_eq!(
+            instruction("cp.async.ca.shared.global [%r1], [%rd1], 16"),
+            AsyncInstruction {
+                opcode: AsyncOpcode::CpAsync,
+                destination_space: Some(StateSpace::Shared),
+                source_space: Some(StateSpace::Global),
+                completion: Some(CompletionMechanism::AsyncGroup),
+                dimensions: None,
+                operands: vec![
+                    Operand::Address("%r1"),
+                    Operand::Address("%rd1"),
+                    Operand::Immediate("16"),
+                ],
+            }
+        );
+    }
+
+    #[test]
+    fn groups() {
+        assert_eq!(instruction("cp.async.commit_group").opcode, AsyncOpcode::CpAsyncCommitGroup);
+        let wait = instruction("cp.async.wait_group 1");
+        assert_eq!(wait.opcode, AsyncOpcode::CpAsyncWaitGroup);
+        assert_eq!(wait.pending_groups(), Some(1));
+        assert_eq!(instruction("cp.async.bulk.wait_group.read 0").opcode, AsyncOpcode::CpAsyncBulkWaitGroup);
+    }
+
+    #[test]
+    fn bulk_tensor() {
+        let copy = instruction(
+            "cp.async.bulk.tensor.2d.shared::cluster.global.mbarrier::complete_tx::bytes [%r1], [%rd1, {%r2, %r3}], [%r4]",
+        );
+        assert_eq!(copy.opcode, AsyncOpcode::CpAsyncBulkTensor);
+        assert_eq!(copy.dimensions, Some(2));
+        assert_eq!(copy.destination_space, Some(StateSpace::SharedCluster));
+        assert_eq!(copy.source_space, Some(StateSpace::Global));
+        assert_eq!(copy.completion, Some(CompletionMechanism::Mbarrier));
+        assert_eq!(copy.operands[1].address_operands().map(|operands| operands.len()), Some(2));
+
+        let store = instruction("cp.async.bulk.tensor.1d.global.shared::cta.bulk_group [%rd1, {%r1}], [%r2]");
+        assert_eq!(store.destination_space, Some(StateSpace::Global));
+        assert_eq!(store.completion, Some(CompletionMechanism::BulkGroup));
+    }
+
+    #[test]
+    fn mbarrier() {
+        let init = instruction("mbarrier.init.shared::cta.b64 [%r1], 128");
+        assert_eq!(init.opcode, AsyncOpcode::MbarrierInit);
+        assert_eq!(init.destination_space, Some(StateSpace::SharedCta));
+        assert_eq!(
+            instruction("mbarrier.arrive.expect_tx.shared::cta.b64 %rd1, [%r1], 4096").opcode,
+            AsyncOpcode::MbarrierArrive
+        );
+        assert_eq!(
+            instruction("mbarrier.try_wait.parity.shared::cta.b64 %p1, [%r1], %r2").opcode,
+            AsyncOpcode::MbarrierTryWait
+        );
+    }
+
+    #[test]
+    fn not_async() {
+        assert!(AsyncInstruction::try_from(&Operation::new("cvt.rn.f32.u32", "%f1, %r1")).is_err());
+    }
+}
+
+#[cfg(test)]
+mod test_check_async_copies {
+    use crate::parser::{
+        function::body::Operation,
+        instruction::async_copy::{check_async_copies, AsyncCopyHazard},
+    };
+
+    fn operations<'a>(lines: &[&'a str]) -> Vec<Operation<'a>> {
+        lines
+            .iter()
+            .map(|line| {
+                let (operation, arguments) = line.split_once(' ').unwrap_or((line, ""));
+                Operation::new(operation, arguments)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn waited() {
+        let operations = operations(&[
+            "cp.async.ca.shared.global [%r1], [%rd1], 16",
+            "cp.async.commit_group",
+            "cp.async.ca.shared.global [%r2], [%rd2], 16",
+            "cp.async.commit_group",
+            "cp.async.wait_group 0",
+            "ld.shared.f32 %f1, [%r1]",
+        ]);
+        assert_eq!(check_async_copies(operations), vec![]);
+    }
+
+    #[test]
+    fn read_before_wait() {
+        let operations = operations(&[
+            "cp.async.ca.shared.global [%r1], [%rd1], 16",
+            "cp.async.commit_group",
+            "cp.async.ca.shared.global [%r2], [%rd2], 16",
+            "cp.async.commit_group",
+            "cp.async.wait_group 1",
+            "ld.shared.f32 %f1, [%r1]",
+            "cp.async.wait_all",
+        ]);
+        assert_eq!(
+            check_async_copies(operations.clone()),
+            vec![AsyncCopyHazard::ReadBeforeWait {
+                copy: operations[2].clone(),
+                read: operations[5].clone(),
+            }]
+        );
+    }
+
+    #[test]
+    fn disjoint_read() {
+        let operations = operations(&[
+            "cp.async.ca.shared.global [%r1+16], [%rd1], 16",
+            "cp.async.commit_group",
+            "ld.shared.v4.f32 {%f1, %f2, %f3, %f4}, [%r1]",
+            "ld.shared.f32 %f5, [%r1+28]",
+            "ld.shared.f32 %f6, [%r2]",
+            "cp.async.wait_all",
+        ]);
+        assert_eq!(
+            check_async_copies(operations.clone()),
+            vec![
+                AsyncCopyHazard::ReadBeforeWait {
+                    copy: operations[0].clone(),
+                    read: operations[3].clone(),
+                },
+                AsyncCopyHazard::ReadBeforeWait {
+                    copy: operations[0].clone(),
+                    read: operations[4].clone(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn never_waited() {
+        let operations = operations(&[
+            "cp.async.cg.shared.global [%r1], [%rd1], 16",
+            "cp.async.commit_group",
+        ]);
+        assert_eq!(
+            check_async_copies(operations.clone()),
+            vec![AsyncCopyHazard::NeverWaited { copy: operations[0].clone() }]
+        );
+    }
+
+    #[test]
+    fn mbarrier_completion() {
+        let operations = operations(&[
+            "mbarrier.init.shared::cta.b64 [%r9], 1",
+            "cp.async.bulk.tensor.2d.shared::cluster.global.mbarrier::complete_tx::bytes [%r1], [%rd1, {%r2, %r3}], [%r9]",
+            "ldmatrix.sync.aligned.m8n8.x1.shared.b16 {%r4}, [%r1]",
+            "mbarrier.try_wait.parity.shared::cta.b64 %p1, [%r9], 0",
+            "ldmatrix.sync.aligned.m8n8.x1.shared.b16 {%r5}, [%r1]",
+        ]);
+        assert_eq!(
+            check_async_copies(operations.clone()),
+            vec![AsyncCopyHazard::ReadBeforeWait {
+                copy: operations[1].clone(),
+                read: operations[2].clone(),
+            }]
+        );
+    }
+
+    #[test]
+    fn two_barriers() {
+        let operations = operations(&[
+            "cp.async.bulk.shared::cluster.global.mbarrier::complete_tx::bytes [%r1], [%rd1], 256, [%r8]",
+            "cp.async.bulk.shared::cluster.global.mbarrier::complete_tx::bytes [%r2], [%rd2], 256, [%r9]",
+            "mbarrier.try_wait.parity.shared::cta.b64 %p1, [%r8], 0",
+            "ld.shared.f32 %f1, [%r1]",
+            "ld.shared.f32 %f2, [%r2]",
+        ]);
+        assert_eq!(
+            check_async_copies(operations.clone()),
+            vec![
+                AsyncCopyHazard::ReadBeforeWait {
+                    copy: operations[1].clone(),
+                    read: operations[3].clone(),
+                },
+                AsyncCopyHazard::ReadBeforeWait {
+                    copy: operations[1].clone(),
+                    read: operations[4].clone(),
+                },
+                AsyncCopyHazard::NeverWaited { copy: operations[1].clone() },
+            ]
+        );
+    }
+
+    #[test]
+    fn predicated() {
+        let (_, function) = crate::parser::function::parse::parse_function(
+            ".func f(.param .b32 a)
+{
+	.reg .pred %p<2>;
+	.reg .b32 %r<3>;
+	.reg .b64 %rd<2>;
+	.reg .f32 %f<2>;
+	ld.param.b32 %r2, [a];
+	setp.ne.s32 %p1, %r2, 0;
+	@%p1 cp.async.ca.shared.global [%r1], [%rd1], 16;
+	cp.async.commit_group;
+	@%p1 ld.shared.f32 %f1, [%r1];
+	cp.async.wait_all;
+	ret;
+}",
+        )
+        .unwrap();
+        let operations = function.operations().collect::<Vec<_>>();
+        assert_eq!(operations.len(), 6);
+        assert_eq!(
+            check_async_copies(operations.clone()),
+            vec![AsyncCopyHazard::ReadBeforeWait {
+                copy: operations[2].clone(),
+                read: operations[4].clone(),
+            }]
+        );
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg(test)]
+mod test_async_copy_usage {
+    use crate::{
+        parser::{instruction::async_copy::AsyncCopyHazard, PtxFile},
+        ptx_files::pipeline,
+    };
+
+    #[test]
+    fn pipeline() {
+        let ptx: PtxFile = pipeline::_PTX.try_into().unwrap();
+        assert_eq!(ptx.async_instructions().len(), 10);
+        let hazards = ptx.async_copy_hazards();
+        assert_eq!(hazards.len(), 1);
+        let (function, AsyncCopyHazard::ReadBeforeWait { copy, read }) = &hazards[0] else {
+            panic!("unexpected hazard {hazards:?}");
+        };
+        assert_eq!(*function, "pipeline");
+        assert_eq!(copy.to_string(), "cp.async.cg.shared.global [%r1+256], [%rd1+256], 16");
+        assert_eq!(read.to_string(), "ld.shared.f32 %f2, [%r1+256]");
+    }
+}
@@ -0,0 +1,292 @@
+use crate::parser::function::body::Operation;
+
+use super::{
+    instruction_error,
+    types::{MemoryOrder, ScalarType, Scope, StateSpace, VectorWidth},
+    Operand,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AtomicOpcode {
+    /// `atom`, which returns the previous value of the memory location
+    Atom,
+    /// `red`, which only updates memory
+    Red,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum AtomicOperation {
+    And,
+    Or,
+    Xor,
+    Cas,
+    Exch,
+    Add,
+    Inc,
+    Dec,
+    Min,
+    Max,
+}
+
+impl AtomicOperation {
+    pub fn from_modifier(modifier: &str) -> Option<Self> {
+        Some(match modifier {
+            "and" => AtomicOperation::And,
+            "or" => AtomicOperation::Or,
+            "xor" => AtomicOperation::Xor,
+            "cas" => AtomicOperation::Cas,
+            "exch" => AtomicOperation::Exch,
+            "add" => AtomicOperation::Add,
+            "inc" => AtomicOperation::Inc,
+            "dec" => AtomicOperation::Dec,
+            "min" => AtomicOperation::Min,
+            "max" => AtomicOperation::Max,
+            _ => return None,
+        })
+    }
+}
+
+/// An atomic or reduction such as `atom.acq_rel.sys.global.cas.b32 %r1, [%rd1], %r2, %r3;`.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct AtomicInstruction<'a> {
+    pub opcode: AtomicOpcode,
+    pub order: Option<MemoryOrder>,
+    pub scope: Option<Scope>,
+    pub state_space: StateSpace,
+    pub operation: AtomicOperation,
+    pub vector: Option<VectorWidth>,
+    pub ty: ScalarType,
+    pub operands: Vec<Operand<'a>>,
+}
+
+impl<'a> AtomicInstruction<'a> {
+    /// Atomics without an explicit order are `.relaxed`.
+    pub fn effective_order(&self) -> MemoryOrder {
+        self.order.unwrap_or(MemoryOrder::Relaxed)
+    }
+
+    /// Atomics without an explicit scope are `.gpu` scoped.
+    pub fn effective_scope(&self) -> Scope {
+        self.scope.unwrap_or(Scope::Gpu)
+    }
+
+    /// The register receiving the previous value, for `atom`.
+    pub fn destination(&self) -> Option<&Operand<'a>> {
+        match self.opcode {
+            AtomicOpcode::Atom => self.operands.first(),
+            AtomicOpcode::Red => None,
+        }
+    }
+
+    pub fn address(&self) -> Option<&'a str> {
+        self.operands.iter().find_map(|operand| match operand {
+            Operand::Address(address) => Some(*address),
+            _ => None,
+        })
+    }
+}
+
+impl<'a> TryFrom<&Operation<'a>> for AtomicInstruction<'a> {
+    type Error = nom::Err<nom::error::Error<&'a str>>;
+
+    fn try_from(operation: &Operation<'a>) -> Result<Self, Self::Error> {
+        let error = || instruction_error(operation.operation());
+        let opcode = match operation.opcode() {
+            "atom" => AtomicOpcode::Atom,
+            "red" => AtomicOpcode::Red,
+            _ => return Err(error()),
+        };
+        let ty = operation
+            .modifiers()
+            .filter_map(ScalarType::from_modifier)
+            .last()
+            .ok_or_else(error)?;
+        Ok(AtomicInstruction {
+            opcode,
+            order: operation.modifiers().find_map(MemoryOrder::from_modifier),
+            scope: operation.modifiers().find_map(Scope::from_modifier),
+            state_space: operation
+                .modifiers()
+                .find_map(StateSpace::from_modifier)
+                .unwrap_or(StateSpace::Generic),
+            operation: operation
+                .modifiers()
+                .find_map(AtomicOperation::from_modifier)
+                .ok_or_else(error)?,
+            vector: operation.modifiers().find_map(VectorWidth::from_modifier),
+            ty,
+            operands: operation.operands()?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FenceOpcode {
+    Fence,
+    /// `membar.cta`, `membar.gl` and `membar.sys`, equivalent to `fence.sc` at the same scope
+    Membar,
+}
+
+/// A memory fence such as `fence.acq_rel.gpu;`, `membar.gl;` or `fence.proxy.async.shared::cta;`.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct FenceInstruction<'a> {
+    pub opcode: FenceOpcode,
+    pub order: Option<MemoryOrder>,
+    pub scope: Option<Scope>,
+    /// The proxy kinds ordered by a proxy fence, e.g. `async.shared::cta`.
+    pub proxy: Option<&'a str>,
+}
+
+impl<'a> TryFrom<&Operation<'a>> for FenceInstruction<'a> {
+    type Error = nom::Err<nom::error::Error<&'a str>>;
+
+    fn try_from(operation: &Operation<'a>) -> Result<Self, Self::Error> {
+        match operation.opcode() {
+            "membar" => {
+                let scope = operation.modifiers().find_map(|modifier| match modifier {
+                    "gl" => Some(Scope::Gpu),
+                    modifier => Scope::from_modifier(modifier),
+                });
+                let proxy = operation.operation().split_once(".proxy.").map(|(_, proxy)| proxy);
+                Ok(FenceInstruction {
+                    opcode: FenceOpcode::Membar,
+                    order: proxy.is_none().then_some(MemoryOrder::Sc),
+                    scope,
+                    proxy,
+                })
+            }
+            "fence" => Ok(FenceInstruction {
+                opcode: FenceOpcode::Fence,
+                order: operation.modifiers().find_map(MemoryOrder::from_modifier),
+                scope: operation.modifiers().find_map(Scope::from_modifier),
+                proxy: operation.operation().split_once(".proxy.").map(|(_, proxy)| proxy),
+            }),
+            _ => Err(instruction_error(operation.operation())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_atomic_instruction {
+    use crate::parser::{
+        function::body::Operation,
+        instruction::{
+            atomic::{AtomicInstruction, AtomicOpcode, AtomicOperation, FenceInstruction, FenceOpcode},
+            types::{MemoryOrder, ScalarType, Scope, StateSpace},
+            Operand,
+        },
+    };
+
+    fn operation(line: &str) -> Operation<'_> {
+        let (operation, arguments) = line.split_once(' ').unwrap_or((line, ""));
+        Operation::new(operation, arguments)
+    }
+
+    fn atomic(line: &str) -> AtomicInstruction<'_> {
+        AtomicInstruction::try_from(&operation(line)).unwrap()
+    }
+
+    fn fence(line: &str) -> FenceInstruction<'_> {
+        FenceInstruction::try_from(&operation(line)).unwrap()
+    }
+
+    #[test]
+    fn compare_and_swap() {
+        assert_eq!(
+            atomic("atom.acq_rel.sys.global.cas.b32 %r1, [%rd1], %r2, %r3"),
+            AtomicInstruction {
+                opcode: AtomicOpcode::Atom,
+                order: Some(MemoryOrder::AcqRel),
+                scope: Some(Scope::Sys),
+                state_space: StateSpace::Global,
+                operation: AtomicOperation::Cas,
+                vector: None,
+                ty: ScalarType::B32,
+                operands: vec![
+                    Operand::Register("%r1"),
+                    Operand::Address("%rd1"),
+                    Operand::Register("%r2"),
+                    Operand::Register("%r3"),
+                ],
+            }
+        );
+    }
+
+    #[test]
+    fn defaults() {
+        let atom = atomic("atom.shared.add.u32 %r1, [%r2], 1");
+        assert_eq!(atom.order, None);
+        assert_eq!(atom.effective_order(), MemoryOrder::Relaxed);
+        assert_eq!(atom.effective_scope(), Scope::Gpu);
+        assert_eq!(atom.destination(), Some(&Operand::Register("%r1")));
+        assert_eq!(atom.address(), Some("%r2"));
+
+        let red = atomic("red.add.f32 [%rd1], %f1");
+        assert_eq!(red.opcode, AtomicOpcode::Red);
+        assert_eq!(red.state_space, StateSpace::Generic);
+        assert_eq!(red.destination(), None);
+    }
+
+    #[test]
+    fn not_atomic() {
+        assert!(AtomicInstruction::try_from(&operation("ld.global.u32 %r1, [%rd1]")).is_err());
+        assert!(AtomicInstruction::try_from(&operation("atom.global.u32 %r1, [%rd1], 1")).is_err());
+    }
+
+    #[test]
+    fn fences() {
+        assert_eq!(
+            fence("fence.acq_rel.gpu"),
+            FenceInstruction {
+                opcode: FenceOpcode::Fence,
+                order: Some(MemoryOrder::AcqRel),
+                scope: Some(Scope::Gpu),
+                proxy: None,
+            }
+        );
+        let membar = fence("membar.gl");
+        assert_eq!(membar.order, Some(MemoryOrder::Sc));
+        assert_eq!(membar.scope, Some(Scope::Gpu));
+        assert_eq!(fence("fence.proxy.async.shared::cta").proxy, Some("async.shared::cta"));
+        assert_eq!(fence("membar.proxy.alias").order, None);
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg(test)]
+mod test_atomic_queries {
+    use crate::{
+        parser::{
+            instruction::{
+                atomic::{AtomicOpcode, AtomicOperation},
+                types::{MemoryOrder, Scope, StateSpace},
+                Operand,
+            },
+            PtxFile,
+        },
+        ptx_files::atomics,
+    };
+
+    #[test]
+    fn atomics_by_space_and_scope() {
+        let ptx: PtxFile = atomics::_PTX.try_into().unwrap();
+        assert_eq!(ptx.atomic_instructions().len(), 5);
+        let system = ptx.atomics_with(StateSpace::Global, Scope::Sys);
+        assert_eq!(system.len(), 2);
+        assert!(system.iter().all(|(function, _)| *function == "publish"));
+        let (_, unlock) = &system[1];
+        assert_eq!(unlock.opcode, AtomicOpcode::Atom);
+        assert_eq!(unlock.operation, AtomicOperation::Exch);
+        assert_eq!(unlock.order, Some(MemoryOrder::Release));
+        assert_eq!(unlock.destination(), Some(&Operand::Register("%r4")));
+
+        let shared = ptx.atomics_with(StateSpace::Shared, Scope::Gpu);
+        assert_eq!(shared.len(), 2, "including the predicated `red.shared::cta`");
+        assert_eq!(shared[1].1.state_space, StateSpace::SharedCta);
+        assert_eq!(ptx.atomics_with(StateSpace::SharedCta, Scope::Gpu), shared);
+        assert_eq!(ptx.fences().len(), 2);
+    }
+}
@@ -0,0 +1,220 @@
+use crate::parser::function::body::Operation;
+
+use super::{
+    instruction_error,
+    types::{MemoryOrder, ScalarType, StateSpace},
+    Operand,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ClusterOpcode {
+    /// `mapa`, which maps a shared memory address to the same variable in another CTA of the cluster
+    Mapa,
+    /// `getctarank`, the rank of the CTA owning a shared memory address
+    Getctarank,
+    BarrierClusterArrive,
+    BarrierClusterWait,
+}
+
+/// A distributed shared memory or cluster barrier instruction such as
+/// `mapa.shared::cluster.u32 %r2, %r1, %r3;` or `barrier.cluster.arrive.release.aligned;`.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct ClusterInstruction<'a> {
+    pub opcode: ClusterOpcode,
+    /// `.shared::cluster` for `mapa` and `getctarank`, or generic addressing when absent.
+    pub state_space: Option<StateSpace>,
+    pub ty: Option<ScalarType>,
+    /// `.release` or `.relaxed` on an arrive, `.acquire` on a wait.
+    pub order: Option<MemoryOrder>,
+    pub aligned: bool,
+    pub operands: Vec<Operand<'a>>,
+}
+
+impl<'a> ClusterInstruction<'a> {
+    /// The shared memory address translated by `mapa` or `getctarank`.
+    pub fn address(&self) -> Option<&Operand<'a>> {
+        match self.opcode {
+            ClusterOpcode::Mapa | ClusterOpcode::Getctarank => self.operands.get(1),
+            _ => None,
+        }
+    }
+
+    /// The rank of the CTA whose shared memory `mapa` maps into.
+    pub fn target_rank(&self) -> Option<&Operand<'a>> {
+        match self.opcode {
+            ClusterOpcode::Mapa => self.operands.get(2),
+            _ => None,
+        }
+    }
+}
+
+impl<'a> TryFrom<&Operation<'a>> for ClusterInstruction<'a> {
+    type Error = nom::Err<nom::error::Error<&'a str>>;
+
+    fn try_from(operation: &Operation<'a>) -> Result<Self, Self::Error> {
+        let modifiers = operation.modifiers().collect::<Vec<_>>();
+        let opcode = match (operation.opcode(), modifiers.as_slice()) {
+            ("mapa", _) => ClusterOpcode::Mapa,
+            ("getctarank", _) => ClusterOpcode::Getctarank,
+            ("barrier", ["cluster", "arrive", ..]) => ClusterOpcode::BarrierClusterArrive,
+            ("barrier", ["cluster", "wait", ..]) => ClusterOpcode::BarrierClusterWait,
+            _ => return Err(instruction_error(operation.operation())),
+        };
+        Ok(ClusterInstruction {
+            opcode,
+            state_space: modifiers.iter().find_map(|modifier| StateSpace::from_modifier(modifier)),
+            ty: modifiers.iter().find_map(|modifier| ScalarType::from_modifier(modifier)),
+            order: modifiers.iter().find_map(|modifier| MemoryOrder::from_modifier(modifier)),
+            aligned: modifiers.contains(&"aligned"),
+            operands: operation.operands()?,
+        })
+    }
+}
+
+/// The special registers describing the cluster of the executing CTA.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ClusterRegister {
+    /// `%cluster_ctarank`
+    Ctarank,
+    /// `%cluster_nctarank`
+    Nctarank,
+    /// `%cluster_ctaid`, with a `.x`, `.y` or `.z` component
+    Ctaid,
+    /// `%cluster_nctaid`
+    Nctaid,
+    /// `%clusterid`
+    ClusterId,
+    /// `%nclusterid`
+    NclusterId,
+    /// `%is_explicit_cluster`
+    IsExplicitCluster,
+}
+
+impl ClusterRegister {
+    /// Recognizes a register name such as `%cluster_ctarank` or `%cluster_ctaid.x`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.split('.').next()?;
+        Some(match name {
+            "%cluster_ctarank" => ClusterRegister::Ctarank,
+            "%cluster_nctarank" => ClusterRegister::Nctarank,
+            "%cluster_ctaid" => ClusterRegister::Ctaid,
+            "%cluster_nctaid" => ClusterRegister::Nctaid,
+            "%clusterid" => ClusterRegister::ClusterId,
+            "%nclusterid" => ClusterRegister::NclusterId,
+            "%is_explicit_cluster" => ClusterRegister::IsExplicitCluster,
+            _ => return None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test_cluster_instruction {
+    use crate::parser::{
+        function::body::Operation,
+        instruction::{
+            cluster::{ClusterInstruction, ClusterOpcode, ClusterRegister},
+            types::{MemoryOrder, ScalarType, StateSpace},
+            Operand,
+        },
+    };
+
+    fn instruction(line: &str) -> ClusterInstruction<'_> {
+        let (operation, arguments) = line.split_once(' ').unwrap_or((line, ""));
+        ClusterInstruction::try_from(&Operation::new(operation, arguments)).unwrap()
+    }
+
+    #[test]
+    fn mapa() {
+        assert_eq!(
+            instruction("mapa.shared::cluster.u32 %r2, %r1, %r3"),
+            ClusterInstruction {
+                opcode: ClusterOpcode::Mapa,
+                state_space: Some(StateSpace::SharedCluster),
+                ty: Some(ScalarType::U32),
+                order: None,
+                aligned: false,
+                operands: vec![
+                    Operand::Register("%r2"),
+                    Operand::Register("%r1"),
+                    Operand::Register("%r3"),
+                ],
+            }
+        );
+        let generic = instruction("mapa.u64 %rd2, %rd1, 1");
+        assert_eq!(generic.state_space, None);
+        assert_eq!(generic.target_rank(), Some(&Operand::Immediate("1")));
+    }
+
+    #[test]
+    fn getctarank() {
+        let rank = instruction("getctarank.shared::cluster.u32 %r4, %r2");
+        assert_eq!(rank.opcode, ClusterOpcode::Getctarank);
+        assert_eq!(rank.address(), Some(&Operand::Register("%r2")));
+        assert_eq!(rank.target_rank(), None);
+    }
+
+    #[test]
+    fn barriers() {
+        let arrive = instruction("barrier.cluster.arrive.release.aligned");
+        assert_eq!(arrive.opcode, ClusterOpcode::BarrierClusterArrive);
+        assert_eq!(arrive.order, Some(MemoryOrder::Release));
+        assert!(arrive.aligned);
+        let wait = instruction("barrier.cluster.wait");
+        assert_eq!(wait.opcode, ClusterOpcode::BarrierClusterWait);
+        assert_eq!(wait.order, None);
+        assert!(ClusterInstruction::try_from(&Operation::new("bar.sync", "0")).is_err());
+    }
+
+    #[test]
+    fn registers() {
+        assert_eq!(ClusterRegister::from_name("%cluster_ctarank"), Some(ClusterRegister::Ctarank));
+        assert_eq!(ClusterRegister::from_name("%cluster_ctaid.x"), Some(ClusterRegister::Ctaid));
+        assert_eq!(ClusterRegister::from_name("%ctaid.x"), None);
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg(test)]
+mod test_cluster_queries {
+    use crate::{
+        parser::{
+            instruction::{cluster::{ClusterOpcode, ClusterRegister}, memory::MemoryAccess, types::StateSpace},
+            PtxFile,
+        },
+        ptx_files::cluster,
+    };
+
+    #[test]
+    fn distributed_shared_memory() {
+        let ptx: PtxFile = cluster::_PTX.try_into().unwrap();
+        let opcodes = ptx
+            .cluster_instructions()
+            .into_iter()
+            .map(|(_, instruction)| instruction.opcode)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            opcodes,
+            [
+                ClusterOpcode::BarrierClusterArrive,
+                ClusterOpcode::BarrierClusterWait,
+                ClusterOpcode::Mapa,
+                ClusterOpcode::Getctarank,
+                ClusterOpcode::BarrierClusterArrive,
+                ClusterOpcode::BarrierClusterWait,
+            ]
+        );
+        assert_eq!(
+            ptx.cluster_registers(),
+            [("reduce_cluster", ClusterRegister::Ctarank), ("reduce_cluster", ClusterRegister::Nctarank)]
+        );
+        let remote = ptx
+            .typed_operations::<MemoryAccess>()
+            .into_iter()
+            .filter(|(_, access)| access.state_space == StateSpace::SharedCluster)
+            .count();
+        assert_eq!(remote, 1);
+    }
+}
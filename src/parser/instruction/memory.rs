@@ -0,0 +1,220 @@
+use crate::parser::function::body::Operation;
+
+use super::{
+    instruction_error,
+    types::{MemoryOrder, ScalarType, Scope, StateSpace, VectorWidth},
+    Operand,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccessKind {
+    Load,
+    Store,
+}
+
+/// An `ld`, `ldu` or `st` instruction such as `ld.param.v2.b64 {%rd11, %rd12}, [retval0+0];`.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct MemoryAccess<'a> {
+    pub kind: AccessKind,
+    pub state_space: StateSpace,
+    /// `.volatile`, `.relaxed`, `.acquire` or `.release`; plain accesses are weak.
+    pub order: Option<MemoryOrder>,
+    pub scope: Option<Scope>,
+    pub vector: Option<VectorWidth>,
+    pub ty: ScalarType,
+    /// The register or brace list loaded into or stored from memory.
+    pub value: Operand<'a>,
+    pub address: Operand<'a>,
+}
+
+/// A value operand whose element count disagrees with the vector width of the access.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VectorMismatch {
+    pub expected: usize,
+    pub found: usize,
+}
+
+impl MemoryAccess<'_> {
+    /// The number of elements moved by the access.
+    pub fn lanes(&self) -> usize {
+        self.vector.map_or(1, |vector| vector.lanes())
+    }
+
+    /// The number of bytes moved by the access.
+    pub fn size(&self) -> usize {
+        self.ty.size() * self.lanes()
+    }
+
+    /// The alignment required of the address, which for vector accesses is the size of
+    /// the whole vector.
+    pub fn alignment(&self) -> usize {
+        self.size()
+    }
+
+    /// Checks that a brace list is used exactly when the access is a vector access,
+    /// and that it has one element per vector lane.
+    pub fn check_vector_operand(&self) -> Result<(), VectorMismatch> {
+        let found = match &self.value {
+            Operand::Vector(elements) => elements.len(),
+            _ if self.vector.is_none() => return Ok(()),
+            _ => 1,
+        };
+        match self.lanes() {
+            expected if expected == found && self.vector.is_some() => Ok(()),
+            expected => Err(VectorMismatch { expected, found }),
+        }
+    }
+}
+
+impl<'a> TryFrom<&Operation<'a>> for MemoryAccess<'a> {
+    type Error = nom::Err<nom::error::Error<&'a str>>;
+
+    fn try_from(operation: &Operation<'a>) -> Result<Self, Self::Error> {
+        let error = || instruction_error(operation.operation());
+        let kind = match operation.opcode() {
+            "ld" | "ldu" => AccessKind::Load,
+            "st" => AccessKind::Store,
+            _ => return Err(error()),
+        };
+        let state_space = operation
+            .modifiers()
+            .find_map(StateSpace::from_modifier)
+            .unwrap_or(StateSpace::Generic);
+        let vector = operation.modifiers().find_map(VectorWidth::from_modifier);
+        let ty = operation
+            .modifiers()
+            .filter_map(ScalarType::from_modifier)
+            .last()
+            .ok_or_else(error)?;
+        let mut operands = operation.operands()?.into_iter();
+        let (value, address) = match (kind, operands.next(), operands.next(), operands.next()) {
+            (AccessKind::Load, Some(value), Some(address @ Operand::Address(_)), None) => (value, address),
+            (AccessKind::Store, Some(address @ Operand::Address(_)), Some(value), None) => (value, address),
+            _ => return Err(error()),
+        };
+        Ok(MemoryAccess {
+            kind,
+            state_space,
+            order: operation.modifiers().find_map(MemoryOrder::from_modifier),
+            scope: operation.modifiers().find_map(Scope::from_modifier),
+            vector,
+            ty,
+            value,
+            address,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test_memory_access {
+    use crate::parser::{
+        function::body::Operation,
+        instruction::{
+            memory::{AccessKind, MemoryAccess, VectorMismatch},
+            types::{MemoryOrder, ScalarType, Scope, StateSpace, VectorWidth},
+            Operand,
+        },
+    };
+
+    fn access(line: &str) -> MemoryAccess<'_> {
+        let (operation, arguments) = line.split_once(' ').unwrap();
+        MemoryAccess::try_from(&Operation::new(operation, arguments)).unwrap()
+    }
+
+    #[test]
+    fn vector_param_load() {
+        let load = access("ld.param.v2.b64 {%rd11, %rd12}, [retval0+0]");
+        assert_eq!(
+            load,
+            MemoryAccess {
+                kind: AccessKind::Load,
+                state_space: StateSpace::Param,
+                order: None,
+                scope: None,
+                vector: Some(VectorWidth::V2),
+                ty: ScalarType::B64,
+                value: Operand::Vector(vec!["%rd11", "%rd12"]),
+                address: Operand::Address("retval0+0"),
+            }
+        );
+        assert_eq!(load.alignment(), 16);
+        assert_eq!(load.check_vector_operand(), Ok(()));
+    }
+
+    #[test]
+    fn vector_global_store() {
+        let store = access("st.global.v4.f32 [%rd4], {%f1, %f2, %f3, %f4}");
+        assert_eq!(store.kind, AccessKind::Store);
+        assert_eq!(store.value, Operand::Vector(vec!["%f1", "%f2", "%f3", "%f4"]));
+        assert_eq!(store.alignment(), 16);
+    }
+
+    #[test]
+    fn scalar_access() {
+        let load = access("ld.global.nc.u32 %r1, [%rd2+4]");
+        assert_eq!(load.state_space, StateSpace::Global);
+        assert_eq!(load.vector, None);
+        assert_eq!(load.alignment(), 4);
+        assert_eq!(load.check_vector_operand(), Ok(()));
+
+        let load = access("ld.u8 %rs1, [%rd2]");
+        assert_eq!(load.state_space, StateSpace::Generic);
+    }
+
+    #[test]
+    fn ordered_access() {
+        let load = access("ld.acquire.gpu.global.u32 %r1, [%rd1]");
+        assert_eq!(load.order, Some(MemoryOrder::Acquire));
+        assert_eq!(load.scope, Some(Scope::Gpu));
+        assert_eq!(load.state_space, StateSpace::Global);
+
+        let store = access("st.release.sys.u32 [%rd1], %r1");
+        assert_eq!(store.order, Some(MemoryOrder::Release));
+        assert_eq!(store.scope, Some(Scope::Sys));
+        assert_eq!(store.state_space, StateSpace::Generic);
+
+        assert_eq!(access("ld.volatile.shared.u32 %r1, [%r2]").order, Some(MemoryOrder::Volatile));
+    }
+
+    #[test]
+    fn mismatched_vector() {
+        let load = access("ld.global.v4.f32 {%f1, %f2}, [%rd4]");
+        assert_eq!(load.check_vector_operand(), Err(VectorMismatch { expected: 4, found: 2 }));
+
+        let load = access("ld.global.v2.f32 %f1, [%rd4]");
+        assert_eq!(load.check_vector_operand(), Err(VectorMismatch { expected: 2, found: 1 }));
+
+        let load = access("ld.global.f32 {%f1, %f2}, [%rd4]");
+        assert_eq!(load.check_vector_operand(), Err(VectorMismatch { expected: 1, found: 2 }));
+    }
+
+    #[test]
+    fn not_a_memory_access() {
+        let operation = Operation::new("add.s64", "%rd1, %rd5, %rd7");
+        assert!(MemoryAccess::try_from(&operation).is_err());
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg(test)]
+mod test_vector_accesses {
+    use crate::{
+        parser::{instruction::types::VectorWidth, PtxFile},
+        ptx_files::b,
+    };
+
+    #[test]
+    fn vector_accesses_b() {
+        let ptx: PtxFile = b::_PTX.try_into().unwrap();
+        let accesses = ptx.memory_accesses();
+        let vectors = accesses
+            .iter()
+            .filter(|(_, access)| access.vector == Some(VectorWidth::V2))
+            .collect::<Vec<_>>();
+        assert_eq!(vectors.len(), 7);
+        assert!(vectors
+            .iter()
+            .all(|(_, access)| access.check_vector_operand().is_ok() && access.alignment() == 16));
+    }
+}
@@ -0,0 +1,423 @@
+use crate::parser::function::body::Operation;
+
+use super::{instruction_error, types::ScalarType, Operand};
+
+/// The `mMnNkK` shape of a matrix instruction; `ldmatrix` shapes such as `m8n8` have no `k`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MatrixShape {
+    pub m: u32,
+    pub n: u32,
+    pub k: Option<u32>,
+}
+
+impl MatrixShape {
+    pub fn from_modifier(modifier: &str) -> Option<Self> {
+        let rest = modifier.strip_prefix('m')?;
+        let (m, rest) = rest.split_once('n')?;
+        let (n, k) = match rest.split_once('k') {
+            Some((n, k)) => (n, Some(k.parse().ok()?)),
+            None => (rest, None),
+        };
+        Some(MatrixShape { m: m.parse().ok()?, n: n.parse().ok()?, k })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Layout {
+    Row,
+    Col,
+}
+
+impl Layout {
+    pub fn from_modifier(modifier: &str) -> Option<Self> {
+        match modifier {
+            "row" => Some(Layout::Row),
+            "col" => Some(Layout::Col),
+            _ => None,
+        }
+    }
+}
+
+/// The element type of a matrix fragment, which besides the fundamental types
+/// includes sub-byte integers and 8-bit floating point formats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ElementType {
+    Scalar(ScalarType),
+    S4,
+    U4,
+    B1,
+    E4m3,
+    E5m2,
+}
+
+impl ElementType {
+    pub fn from_modifier(modifier: &str) -> Option<Self> {
+        Some(match modifier {
+            "s4" => ElementType::S4,
+            "u4" => ElementType::U4,
+            "b1" => ElementType::B1,
+            "e4m3" => ElementType::E4m3,
+            "e5m2" => ElementType::E5m2,
+            modifier => ElementType::Scalar(ScalarType::from_modifier(modifier)?),
+        })
+    }
+
+    pub fn bits(&self) -> usize {
+        match self {
+            ElementType::Scalar(ty) => ty.bits(),
+            ElementType::S4 | ElementType::U4 => 4,
+            ElementType::B1 => 1,
+            ElementType::E4m3 | ElementType::E5m2 => 8,
+        }
+    }
+}
+
+/// The role of a matrix operand: `d = a * b + c`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fragment {
+    A,
+    B,
+    C,
+    D,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum TensorOpcode {
+    /// `mma.sync.aligned`
+    Mma,
+    /// `wmma.load.a`, `wmma.load.b` or `wmma.load.c`
+    WmmaLoad(Fragment),
+    /// `wmma.store.d`
+    WmmaStore,
+    /// `wmma.mma.sync.aligned`
+    WmmaMma,
+    Ldmatrix,
+    /// `wgmma.mma_async`
+    Wgmma,
+    WgmmaFence,
+    WgmmaCommitGroup,
+    WgmmaWaitGroup,
+}
+
+/// A brace list whose register count disagrees with the shape and types of the instruction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FragmentMismatch {
+    pub fragment: Fragment,
+    pub expected: usize,
+    pub found: usize,
+}
+
+/// A tensor-core instruction such as
+/// `mma.sync.aligned.m16n8k16.row.col.f32.f16.f16.f32 {...}, {...}, {...}, {...};`.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct TensorInstruction<'a> {
+    pub opcode: TensorOpcode,
+    pub shape: Option<MatrixShape>,
+    /// The layouts of `a` and `b` in order, or the single layout of a `wmma` load or store.
+    pub layouts: Vec<Layout>,
+    /// The element types in modifier order, e.g. `d`, `a`, `b`, `c` for `mma`.
+    pub types: Vec<ElementType>,
+    /// The `.x1`, `.x2` or `.x4` matrix count of `ldmatrix`.
+    pub matrices: Option<usize>,
+    pub operands: Vec<Operand<'a>>,
+}
+
+impl<'a> TensorInstruction<'a> {
+    fn element_type(&self, fragment: Fragment) -> Option<ElementType> {
+        let index = match (self.opcode, self.types.len(), fragment) {
+            (TensorOpcode::WmmaLoad(_) | TensorOpcode::WmmaStore, _, _) => 0,
+            (TensorOpcode::WmmaMma, 2, Fragment::A | Fragment::B) => {
+                return Some(ElementType::Scalar(ScalarType::F16))
+            }
+            (TensorOpcode::WmmaMma, 2, Fragment::D) => 0,
+            (TensorOpcode::WmmaMma, 2, Fragment::C) => 1,
+            (TensorOpcode::Wgmma, _, Fragment::C) => 0,
+            (_, _, Fragment::D) => 0,
+            (_, _, Fragment::A) => 1,
+            (_, _, Fragment::B) => 2,
+            (_, _, Fragment::C) => 3,
+        };
+        self.types.get(index).copied()
+    }
+
+    /// The operands holding matrix fragments, paired with their role.
+    pub fn fragments(&self) -> Vec<(Fragment, &Operand<'a>)> {
+        let roles: &[Option<Fragment>] = match self.opcode {
+            TensorOpcode::Mma | TensorOpcode::WmmaMma => {
+                &[Some(Fragment::D), Some(Fragment::A), Some(Fragment::B), Some(Fragment::C)]
+            }
+            TensorOpcode::WmmaLoad(fragment) => &[Some(fragment)][..],
+            TensorOpcode::WmmaStore => &[None, Some(Fragment::D)],
+            TensorOpcode::Ldmatrix => &[Some(Fragment::A)],
+            TensorOpcode::Wgmma => &[Some(Fragment::D), Some(Fragment::A)],
+            _ => &[],
+        };
+        roles
+            .iter()
+            .zip(&self.operands)
+            .filter_map(|(role, operand)| role.map(|role| (role, operand)))
+            .collect()
+    }
+
+    /// The number of registers each thread holds for `fragment`.
+    pub fn fragment_registers(&self, fragment: Fragment) -> Option<usize> {
+        if self.opcode == TensorOpcode::Ldmatrix {
+            let per_matrix = match self.shape? {
+                MatrixShape { m: 16, n: 16, .. } => 2,
+                _ => 1,
+            };
+            return Some(per_matrix * self.matrices?);
+        }
+        let MatrixShape { m, n, k } = self.shape?;
+        let (m, n, k) = (m as usize, n as usize, k? as usize);
+        let element = self.element_type(fragment)?;
+        let elements = match fragment {
+            Fragment::A => m * k,
+            Fragment::B => n * k,
+            Fragment::C | Fragment::D => m * n,
+        };
+        let (threads, register_bits) = match (self.opcode, element) {
+            (TensorOpcode::Wgmma, _) => (128, 32),
+            (_, ElementType::Scalar(ScalarType::F64)) => (32, 64),
+            _ => (32, 32),
+        };
+        let f16 = ElementType::Scalar(ScalarType::F16);
+        let duplication = match (self.opcode, fragment, element) {
+            // The quad-pair layout of `m8n8k4` keeps four copies of each fragment in a warp.
+            (TensorOpcode::Mma, _, _) if (m, n, k) == (8, 8, 4) && self.element_type(Fragment::A) == Some(f16) => 4,
+            (TensorOpcode::WmmaLoad(_) | TensorOpcode::WmmaMma, Fragment::A | Fragment::B, element) if element == f16 => 2,
+            _ => 1,
+        };
+        Some((elements * element.bits() * duplication).div_ceil(threads * register_bits))
+    }
+
+    /// Checks that every brace list holds one register per fragment register.
+    ///
+    /// A `wgmma` `a` operand given as a shared memory descriptor is not a brace list and is not checked.
+    pub fn check_fragments(&self) -> Result<(), FragmentMismatch> {
+        for (fragment, operand) in self.fragments() {
+            let found = match operand {
+                Operand::Vector(elements) => elements.len(),
+                _ if self.opcode == TensorOpcode::Wgmma && fragment == Fragment::A => continue,
+                _ => 1,
+            };
+            match self.fragment_registers(fragment) {
+                Some(expected) if expected != found => {
+                    return Err(FragmentMismatch { fragment, expected, found })
+                }
+                _ => (),
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<'a> TryFrom<&Operation<'a>> for TensorInstruction<'a> {
+    type Error = nom::Err<nom::error::Error<&'a str>>;
+
+    fn try_from(operation: &Operation<'a>) -> Result<Self, Self::Error> {
+        let error = || instruction_error(operation.operation());
+        let mut modifiers = operation.modifiers();
+        let opcode = match (operation.opcode(), modifiers.next(), modifiers.next()) {
+            ("mma", Some("sync"), _) => TensorOpcode::Mma,
+            ("wmma", Some("load"), Some("a")) => TensorOpcode::WmmaLoad(Fragment::A),
+            ("wmma", Some("load"), Some("b")) => TensorOpcode::WmmaLoad(Fragment::B),
+            ("wmma", Some("load"), Some("c")) => TensorOpcode::WmmaLoad(Fragment::C),
+            ("wmma", Some("store"), Some("d")) => TensorOpcode::WmmaStore,
+            ("wmma", Some("mma"), _) => TensorOpcode::WmmaMma,
+            ("ldmatrix", _, _) => TensorOpcode::Ldmatrix,
+            ("wgmma", Some("mma_async"), _) => TensorOpcode::Wgmma,
+            ("wgmma", Some("fence"), _) => TensorOpcode::WgmmaFence,
+            ("wgmma", Some("commit_group"), _) => TensorOpcode::WgmmaCommitGroup,
+            ("wgmma", Some("wait_group"), _) => TensorOpcode::WgmmaWaitGroup,
+            _ => return Err(error()),
+        };
+        let matrices = operation.modifiers().find_map(|modifier| match modifier {
+            "x1" => Some(1),
+            "x2" => Some(2),
+            "x4" => Some(4),
+            _ => None,
+        });
+        Ok(TensorInstruction {
+            opcode,
+            shape: operation.modifiers().find_map(MatrixShape::from_modifier),
+            layouts: operation.modifiers().filter_map(Layout::from_modifier).collect(),
+            types: operation.modifiers().filter_map(ElementType::from_modifier).collect(),
+            matrices,
+            operands: operation.operands()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test_tensor_instruction {
+    use crate::parser::{
+        function::body::Operation,
+        instruction::{
+            mma::{ElementType, Fragment, FragmentMismatch, Layout, MatrixShape, TensorInstruction, TensorOpcode},
+            types::ScalarType,
+        },
+    };
+
+    fn instruction(line: &str) -> TensorInstruction<'_> {
+        let (operation, arguments) = line.split_once(' ').unwrap();
+        TensorInstruction::try_from(&Operation::new(operation, arguments)).unwrap()
+    }
+
+    #[test]
+    fn shapes() {
+        assert_eq!(MatrixShape::from_modifier("m16n8k16"), Some(MatrixShape { m: 16, n: 8, k: Some(16) }));
+        assert_eq!(MatrixShape::from_modifier("m8n8"), Some(MatrixShape { m: 8, n: 8, k: None }));
+        assert_eq!(MatrixShape::from_modifier("mma_async"), None);
+    }
+
+    #[test]
+    fn mma_m16n8k16() {
+        let mma = instruction(
+            "mma.sync.aligned.m16n8k16.row.col.f32.f16.f16.f32 {%f1, %f2, %f3, %f4}, {%r1, %r2, %r3, %r4}, {%r5, %r6}, {%f5, %f6, %f7, %f8}",
+        );
+        assert_eq!(mma.opcode, TensorOpcode::Mma);
+        assert_eq!(mma.layouts, vec![Layout::Row, Layout::Col]);
+        assert_eq!(
+            mma.types,
+            vec![
+                ElementType::Scalar(ScalarType::F32),
+                ElementType::Scalar(ScalarType::F16),
+                ElementType::Scalar(ScalarType::F16),
+                ElementType::Scalar(ScalarType::F32),
+            ]
+        );
+        assert_eq!(mma.fragment_registers(Fragment::A), Some(4));
+        assert_eq!(mma.fragment_registers(Fragment::B), Some(2));
+        assert_eq!(mma.fragment_registers(Fragment::D), Some(4));
+        assert_eq!(mma.check_fragments(), Ok(()));
+    }
+
+    #[test]
+    fn mma_fragment_counts() {
+        let cases = [
+            ("mma.sync.aligned.m16n8k8.row.col.f16.f16.f16.f16", [2, 2, 1, 2]),
+            ("mma.sync.aligned.m8n8k4.row.col.f32.f16.f16.f32", [8, 2, 2, 8]),
+            ("mma.sync.aligned.m16n8k8.row.col.f32.tf32.tf32.f32", [4, 4, 2, 4]),
+            ("mma.sync.aligned.m16n8k32.row.col.s32.s8.s8.s32", [4, 4, 2, 4]),
+            ("mma.sync.aligned.m8n8k32.row.col.s32.s4.s4.s32", [2, 1, 1, 2]),
+            ("mma.sync.aligned.m8n8k4.row.col.f64.f64.f64.f64", [2, 1, 1, 2]),
+            ("mma.sync.aligned.m16n8k32.row.col.f32.e4m3.e4m3.f32", [4, 4, 2, 4]),
+        ];
+        for (operation, [d, a, b, c]) in cases {
+            let mma = TensorInstruction::try_from(&Operation::new(operation, "%r1, %r2, %r3, %r4")).unwrap();
+            assert_eq!(
+                [Fragment::D, Fragment::A, Fragment::B, Fragment::C].map(|f| mma.fragment_registers(f).unwrap()),
+                [d, a, b, c],
+                "{operation}"
+            );
+        }
+    }
+
+    #[test]
+    fn mma_wrong_count() {
+        let mma = instruction(
+            "mma.sync.aligned.m16n8k16.row.col.f32.bf16.bf16.f32 {%f1, %f2, %f3, %f4}, {%r1, %r2}, {%r5, %r6}, {%f5, %f6, %f7, %f8}",
+        );
+        assert_eq!(
+            mma.check_fragments(),
+            Err(FragmentMismatch { fragment: Fragment::A, expected: 4, found: 2 })
+        );
+    }
+
+    #[test]
+    fn ldmatrix() {
+        let ldmatrix = instruction("ldmatrix.sync.aligned.m8n8.x4.shared.b16 {%r1, %r2, %r3, %r4}, [%r5]");
+        assert_eq!(ldmatrix.opcode, TensorOpcode::Ldmatrix);
+        assert_eq!(ldmatrix.matrices, Some(4));
+        assert_eq!(ldmatrix.check_fragments(), Ok(()));
+
+        let ldmatrix = instruction("ldmatrix.sync.aligned.m8n8.x2.trans.shared.b16 {%r1}, [%r5]");
+        assert_eq!(
+            ldmatrix.check_fragments(),
+            Err(FragmentMismatch { fragment: Fragment::A, expected: 2, found: 1 })
+        );
+    }
+
+    #[test]
+    fn wmma() {
+        let load = instruction(
+            "wmma.load.a.sync.aligned.row.m16n16k16.global.f16 {%r1, %r2, %r3, %r4, %r5, %r6, %r7, %r8}, [%rd1], %r9",
+        );
+        assert_eq!(load.opcode, TensorOpcode::WmmaLoad(Fragment::A));
+        assert_eq!(load.layouts, vec![Layout::Row]);
+        assert_eq!(load.check_fragments(), Ok(()));
+
+        let mma = instruction(
+            "wmma.mma.sync.aligned.row.col.m16n16k16.f32.f32 {%f1, %f2, %f3, %f4, %f5, %f6, %f7, %f8}, {%r1, %r2, %r3, %r4, %r5, %r6, %r7, %r8}, {%r9, %r10, %r11, %r12, %r13, %r14, %r15, %r16}, {%f9, %f10, %f11, %f12, %f13, %f14, %f15, %f16}",
+        );
+        assert_eq!(mma.check_fragments(), Ok(()));
+
+        let store = instruction("wmma.store.d.sync.aligned.row.m16n16k16.global.f32 [%rd1], {%f1, %f2, %f3, %f4}, %r1");
+        assert_eq!(
+            store.check_fragments(),
+            Err(FragmentMismatch { fragment: Fragment::D, expected: 8, found: 4 })
+        );
+    }
+
+    #[test]
+    fn wgmma() {
+        let wgmma = instruction(
+            "wgmma.mma_async.sync.aligned.m64n8k16.f32.f16.f16 {%f1, %f2, %f3, %f4}, %rd1, %rd2, 1, 1, 1, 0, 0",
+        );
+        assert_eq!(wgmma.opcode, TensorOpcode::Wgmma);
+        assert_eq!(wgmma.fragment_registers(Fragment::D), Some(4));
+        assert_eq!(wgmma.check_fragments(), Ok(()));
+
+        let wgmma = instruction("wgmma.mma_async.sync.aligned.m64n16k16.f16.f16.f16 {%r1, %r2}, %rd1, %rd2, 1, 1, 1, 0, 0");
+        assert_eq!(
+            wgmma.check_fragments(),
+            Err(FragmentMismatch { fragment: Fragment::D, expected: 4, found: 2 })
+        );
+
+        let wait = instruction("wgmma.wait_group.sync.aligned 0");
+        assert_eq!(wait.opcode, TensorOpcode::WgmmaWaitGroup);
+    }
+
+    #[test]
+    fn not_a_tensor_instruction() {
+        assert!(TensorInstruction::try_from(&Operation::new("mov.u32", "%r1, %r2")).is_err());
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg(test)]
+mod test_tensor_usage {
+    use crate::{
+        parser::{instruction::mma::TensorOpcode, PtxFile},
+        ptx_files::tensor,
+    };
+
+    #[test]
+    fn matmul() {
+        let ptx: PtxFile = tensor::_PTX.try_into().unwrap();
+        let instructions = ptx.tensor_instructions();
+        let opcodes = instructions
+            .iter()
+            .map(|(_, instruction)| instruction.opcode)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            opcodes,
+            vec![
+                TensorOpcode::Ldmatrix,
+                TensorOpcode::Ldmatrix,
+                TensorOpcode::Mma,
+                TensorOpcode::WgmmaFence,
+                TensorOpcode::Wgmma,
+                TensorOpcode::WgmmaCommitGroup,
+                TensorOpcode::WgmmaWaitGroup,
+            ]
+        );
+        assert!(instructions
+            .iter()
+            .all(|(_, instruction)| instruction.check_fragments().is_ok()));
+    }
+}
@@ -0,0 +1,125 @@
+pub(crate) mod parse;
+pub mod async_copy;
+pub mod atomic;
+pub mod cluster;
+pub mod memory;
+pub mod mma;
+pub mod texture;
+pub mod types;
+
+/// An instruction operand, as found in the arguments of an `Operation`.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum Operand<'a> {
+    /// `%r1`, `%tid.x`
+    Register(&'a str),
+    /// `42`, `-1`, `0f3F800000`
+    Immediate(&'a str),
+    /// The contents of `[%rd4+8]`, `[param0+0]` or `[tex_a, {%f1, %f2}]`.
+    Address(&'a str),
+    /// `{%f1, %f2, %f3, %f4}`
+    Vector(Vec<&'a str>),
+    /// Labels, variables and function names.
+    Symbol(&'a str),
+    /// `%p1|%p2`, the two destinations of `setp`, or of `shfl` and `elect` as `%r1|%p1`.
+    Pair(&'a str, &'a str),
+}
+
+impl<'a> Operand<'a> {
+    /// The operands nested in an address such as `[tex_a, {%f1, %f2}]`.
+    pub fn address_operands(&self) -> Option<Vec<Operand<'a>>> {
+        match self {
+            Operand::Address(address) => parse::parse_operands(address)
+                .ok()
+                .map(|(_, operands)| operands),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> Option<&'a str> {
+        match self {
+            Operand::Register(name) | Operand::Symbol(name) => Some(name),
+            _ => None,
+        }
+    }
+}
+
+pub(crate) fn instruction_error(input: &str) -> nom::Err<nom::error::Error<&str>> {
+    nom::Err::Error(nom::error::Error::new(input, nom::error::ErrorKind::Tag))
+}
+
+#[cfg(test)]
+mod test_parse_operands {
+    use crate::parser::instruction::{parse::parse_operands, Operand};
+
+    #[test]
+    fn registers_and_immediates() {
+        assert_eq!(
+            parse_operands("%r5, %r3, %r4, -1"),
+            Ok((
+                "",
+                vec![
+                    Operand::Register("%r5"),
+                    Operand::Register("%r3"),
+                    Operand::Register("%r4"),
+                    Operand::Immediate("-1"),
+                ]
+            ))
+        );
+    }
+
+    #[test]
+    fn special_register_and_symbol() {
+        assert_eq!(
+            parse_operands("%r2, %tid.x"),
+            Ok(("", vec![Operand::Register("%r2"), Operand::Register("%tid.x")]))
+        );
+        assert_eq!(
+            parse_operands("%rd1, anon_$_03c68ec27500ea325c9bf6f32c376c28_$_0"),
+            Ok((
+                "",
+                vec![
+                    Operand::Register("%rd1"),
+                    Operand::Symbol("anon_$_03c68ec27500ea325c9bf6f32c376c28_$_0"),
+                ]
+            ))
+        );
+    }
+
+    #[test]
+    fn vector_and_address() {
+        assert_eq!(
+            parse_operands("{%rd11, %rd12}, [retval0+0]"),
+            Ok((
+                "",
+                vec![
+                    Operand::Vector(vec!["%rd11", "%rd12"]),
+                    Operand::Address("retval0+0"),
+                ]
+            ))
+        );
+    }
+
+    #[test]
+    fn texture_address() {
+        let (_, operands) = parse_operands("{%f1, %f2, %f3, %f4}, [tex_a, {%f5, %f6}]").unwrap();
+        assert_eq!(operands[1], Operand::Address("tex_a, {%f5, %f6}"));
+        assert_eq!(
+            operands[1].address_operands(),
+            Some(vec![Operand::Symbol("tex_a"), Operand::Vector(vec!["%f5", "%f6"])])
+        );
+    }
+
+    #[test]
+    fn pair() {
+        assert_eq!(
+            parse_operands("%p1|%p2, %r2, %r1"),
+            Ok(("", vec![Operand::Pair("%p1", "%p2"), Operand::Register("%r2"), Operand::Register("%r1")]))
+        );
+    }
+
+    #[test]
+    fn trailing_garbage() {
+        assert!(parse_operands("%r1, %r2 %r3").is_err());
+    }
+}
@@ -0,0 +1,82 @@
+use nom::{
+    branch::alt,
+    bytes::complete::{take_till, take_while1},
+    character::complete::{char, multispace0, one_of},
+    combinator::{eof, recognize},
+    multi::fold_many0,
+    sequence::{delimited, preceded, separated_pair, terminated},
+    IResult, Parser,
+};
+
+use super::Operand;
+
+fn is_register_char(c: char) -> bool {
+    c.is_alphanumeric() || ['_', '.', '%', '$'].contains(&c)
+}
+
+fn parse_register(input: &str) -> IResult<&str, &str> {
+    recognize(char('%').and(take_while1(is_register_char)))(input)
+}
+
+fn parse_immediate(input: &str) -> IResult<&str, &str> {
+    recognize(
+        one_of("-+0123456789")
+        .and(take_till(|c: char| c.is_whitespace() || [',', ';', ']', '}'].contains(&c))),
+    )(input)
+}
+
+fn parse_symbol(input: &str) -> IResult<&str, &str> {
+    take_while1(|c: char| !c.is_whitespace() && !",;{}[]".contains(c))(input)
+}
+
+fn separator(input: &str) -> IResult<&str, char> {
+    delimited(multispace0, char(','), multispace0)(input)
+}
+
+fn parse_vector(input: &str) -> IResult<&str, Vec<&str>> {
+    let element = || alt((parse_register, parse_symbol));
+    delimited(
+        char('{').and(multispace0),
+        element().and(fold_many0(preceded(separator, element()), Vec::new, |mut elements, element| {
+            elements.push(element);
+            elements
+        })),
+        multispace0.and(char('}')),
+    )
+    .map(|(first, mut elements)| {
+        elements.insert(0, first);
+        elements
+    })
+    .parse(input)
+}
+
+pub(crate) fn parse_operand(input: &str) -> IResult<&str, Operand<'_>> {
+    alt((
+        parse_vector.map(Operand::Vector),
+        delimited(
+            char('['),
+            recognize(take_till(|c: char| c == '[' || c == ']')),
+            char(']'),
+        )
+        .map(|address: &str| Operand::Address(address.trim())),
+        separated_pair(parse_register, char('|'), parse_register).map(|(first, second)| Operand::Pair(first, second)),
+        parse_register.map(Operand::Register),
+        parse_immediate.map(Operand::Immediate),
+        parse_symbol.map(Operand::Symbol),
+    ))(input)
+}
+
+pub(crate) fn parse_operands(input: &str) -> IResult<&str, Vec<Operand<'_>>> {
+    terminated(
+        parse_operand.and(fold_many0(preceded(separator, parse_operand), Vec::new, |mut operands, operand| {
+            operands.push(operand);
+            operands
+        })),
+        multispace0.and(eof),
+    )
+    .map(|(first, mut operands)| {
+        operands.insert(0, first);
+        operands
+    })
+    .parse(input)
+}
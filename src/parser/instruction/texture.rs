@@ -0,0 +1,237 @@
+use crate::parser::function::body::Operation;
+
+use super::{instruction_error, Operand};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Geometry {
+    OneD,
+    TwoD,
+    ThreeD,
+    ArrayOneD,
+    ArrayTwoD,
+    Cube,
+    ArrayCube,
+    TwoDMultisample,
+    ArrayTwoDMultisample,
+}
+
+impl Geometry {
+    pub fn from_modifier(modifier: &str) -> Option<Self> {
+        Some(match modifier {
+            "1d" => Geometry::OneD,
+            "2d" => Geometry::TwoD,
+            "3d" => Geometry::ThreeD,
+            "a1d" => Geometry::ArrayOneD,
+            "a2d" => Geometry::ArrayTwoD,
+            "cube" => Geometry::Cube,
+            "acube" => Geometry::ArrayCube,
+            "2dms" => Geometry::TwoDMultisample,
+            "a2dms" => Geometry::ArrayTwoDMultisample,
+            _ => return None,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum TextureOpcode {
+    Tex,
+    Tld4,
+    Txq,
+    Suld,
+    Sust,
+    Suq,
+}
+
+impl TextureOpcode {
+    pub fn from_opcode(opcode: &str) -> Option<Self> {
+        Some(match opcode {
+            "tex" => TextureOpcode::Tex,
+            "tld4" => TextureOpcode::Tld4,
+            "txq" => TextureOpcode::Txq,
+            "suld" => TextureOpcode::Suld,
+            "sust" => TextureOpcode::Sust,
+            "suq" => TextureOpcode::Suq,
+            _ => return None,
+        })
+    }
+}
+
+/// A texture or surface instruction such as `tex.2d.v4.f32.f32 {...}, [tex_a, {%f1, %f2}];`.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct TextureInstruction<'a> {
+    pub opcode: TextureOpcode,
+    pub geometry: Option<Geometry>,
+    /// The attribute read by `txq` and `suq`, e.g. `width`.
+    pub query: Option<&'a str>,
+    pub destination: Option<Operand<'a>>,
+    /// The `.texref` or `.surfref` accessed, by name or through a register.
+    pub handle: &'a str,
+    pub sampler: Option<&'a str>,
+    pub coordinates: Option<Operand<'a>>,
+    /// The value written by `sust`.
+    pub source: Option<Operand<'a>>,
+}
+
+impl<'a> TryFrom<&Operation<'a>> for TextureInstruction<'a> {
+    type Error = nom::Err<nom::error::Error<&'a str>>;
+
+    fn try_from(operation: &Operation<'a>) -> Result<Self, Self::Error> {
+        let error = || instruction_error(operation.operation());
+        let opcode = TextureOpcode::from_opcode(operation.opcode()).ok_or_else(error)?;
+        let geometry = operation.modifiers().find_map(Geometry::from_modifier);
+        let query = match opcode {
+            TextureOpcode::Txq | TextureOpcode::Suq => operation.modifiers().find(|m| *m != "level"),
+            _ => None,
+        };
+        let mut operands = operation.operands()?.into_iter();
+        let (destination, address, source) = match opcode {
+            TextureOpcode::Sust => (None, operands.next(), operands.next()),
+            _ => (operands.next(), operands.next(), None),
+        };
+        let mut address = address
+            .as_ref()
+            .and_then(Operand::address_operands)
+            .ok_or_else(error)?
+            .into_iter();
+        let handle = address.next().as_ref().and_then(Operand::name).ok_or_else(error)?;
+        let (sampler, coordinates) = match (address.next(), address.next()) {
+            (Some(sampler), Some(coordinates)) => (sampler.name(), Some(coordinates)),
+            (coordinates, _) => (None, coordinates),
+        };
+        Ok(TextureInstruction {
+            opcode,
+            geometry,
+            query,
+            destination,
+            handle,
+            sampler,
+            coordinates,
+            source,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test_texture_instruction {
+    use crate::parser::{
+        function::body::Operation,
+        instruction::{
+            texture::{Geometry, TextureInstruction, TextureOpcode},
+            Operand,
+        },
+    };
+
+    fn instruction(line: &str) -> TextureInstruction<'_> {
+        let (operation, arguments) = line.split_once(' ').unwrap();
+        TextureInstruction::try_from(&Operation::new(operation, arguments)).unwrap()
+    }
+
+    #[test]
+    fn tex_2d() {
+        assert_eq!(
+            instruction("tex.2d.v4.f32.f32 {%f1, %f2, %f3, %f4}, [tex_a, {%f5, %f6}]"),
+            TextureInstruction {
+                opcode: TextureOpcode::Tex,
+                geometry: Some(Geometry::TwoD),
+                query: None,
+                destination: Some(Operand::Vector(vec!["%f1", "%f2", "%f3", "%f4"])),
+                handle: "tex_a",
+                sampler: None,
+                coordinates: Some(Operand::Vector(vec!["%f5", "%f6"])),
+                source: None,
+            }
+        );
+    }
+
+    #[test]
+    fn tex_with_sampler() {
+        let tex = instruction("tex.1d.v4.s32.f32 {%r1, %r2, %r3, %r4}, [%rd1, smp, %f1]");
+        assert_eq!(tex.geometry, Some(Geometry::OneD));
+        assert_eq!(tex.handle, "%rd1");
+        assert_eq!(tex.sampler, Some("smp"));
+        assert_eq!(tex.coordinates, Some(Operand::Register("%f1")));
+    }
+
+    #[test]
+    fn tld4() {
+        let tld4 = instruction("tld4.r.2d.v4.f32.f32 {%f1, %f2, %f3, %f4}, [tex_a, {%f5, %f6}]");
+        assert_eq!(tld4.opcode, TextureOpcode::Tld4);
+        assert_eq!(tld4.geometry, Some(Geometry::TwoD));
+    }
+
+    #[test]
+    fn surfaces() {
+        let suld = instruction("suld.b.3d.v2.b32.trap {%r1, %r2}, [surf_a, {%r3, %r4, %r5, %r6}]");
+        assert_eq!(suld.opcode, TextureOpcode::Suld);
+        assert_eq!(suld.geometry, Some(Geometry::ThreeD));
+        assert_eq!(suld.destination, Some(Operand::Vector(vec!["%r1", "%r2"])));
+
+        let sust = instruction("sust.b.a2d.b32.clamp [surf_a, {%r1, %r2, %r3, %r4}], %r5");
+        assert_eq!(sust.geometry, Some(Geometry::ArrayTwoD));
+        assert_eq!(sust.handle, "surf_a");
+        assert_eq!(sust.destination, None);
+        assert_eq!(sust.source, Some(Operand::Register("%r5")));
+    }
+
+    #[test]
+    fn queries() {
+        let txq = instruction("txq.level.width.b32 %r1, [tex_a], %r2");
+        assert_eq!(txq.query, Some("width"));
+        assert_eq!(txq.handle, "tex_a");
+        assert_eq!(txq.coordinates, None);
+
+        let suq = instruction("suq.channel_order.b32 %r1, [surf_a]");
+        assert_eq!(suq.opcode, TextureOpcode::Suq);
+        assert_eq!(suq.query, Some("channel_order"));
+    }
+
+    #[test]
+    fn not_a_texture_instruction() {
+        let operation = Operation::new("ld.global.f32", "%f1, [%rd4]");
+        assert!(TextureInstruction::try_from(&operation).is_err());
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg(test)]
+mod test_texture_usage {
+    use crate::{
+        parser::{global::OpaqueKind, instruction::texture::TextureOpcode, PtxFile},
+        ptx_files::texture,
+    };
+
+    #[test]
+    fn audit_module() {
+        let ptx: PtxFile = texture::_PTX.try_into().unwrap();
+        let kinds = ptx
+            .opaque_declarations()
+            .into_iter()
+            .map(|declaration| (declaration.kind, declaration.name))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            vec![
+                (OpaqueKind::Texture, "tex_a"),
+                (OpaqueKind::Sampler, "smp"),
+                (OpaqueKind::Surface, "surf_a"),
+            ]
+        );
+        let instructions = ptx
+            .texture_instructions()
+            .into_iter()
+            .map(|(function, instruction)| (function, instruction.opcode, instruction.handle))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            instructions,
+            vec![
+                ("blur", TextureOpcode::Tex, "tex_a"),
+                ("blur", TextureOpcode::Txq, "tex_a"),
+                ("blur", TextureOpcode::Suld, "surf_a"),
+                ("blur", TextureOpcode::Sust, "surf_a"),
+            ]
+        );
+    }
+}
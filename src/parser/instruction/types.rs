@@ -0,0 +1,313 @@
+/// A fundamental PTX type such as `.u32` or `.f16x2`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ScalarType {
+    S8,
+    S16,
+    S32,
+    S64,
+    U8,
+    U16,
+    U32,
+    U64,
+    F16,
+    F16x2,
+    Bf16,
+    Bf16x2,
+    Tf32,
+    F32,
+    F64,
+    B8,
+    B16,
+    B32,
+    B64,
+    B128,
+    Pred,
+}
+
+impl ScalarType {
+    /// Parses a modifier without its leading `.`, e.g. `u64`.
+    pub fn from_modifier(modifier: &str) -> Option<Self> {
+        Some(match modifier {
+            "s8" => ScalarType::S8,
+            "s16" => ScalarType::S16,
+            "s32" => ScalarType::S32,
+            "s64" => ScalarType::S64,
+            "u8" => ScalarType::U8,
+            "u16" => ScalarType::U16,
+            "u32" => ScalarType::U32,
+            "u64" => ScalarType::U64,
+            "f16" => ScalarType::F16,
+            "f16x2" => ScalarType::F16x2,
+            "bf16" => ScalarType::Bf16,
+            "bf16x2" => ScalarType::Bf16x2,
+            "tf32" => ScalarType::Tf32,
+            "f32" => ScalarType::F32,
+            "f64" => ScalarType::F64,
+            "b8" => ScalarType::B8,
+            "b16" => ScalarType::B16,
+            "b32" => ScalarType::B32,
+            "b64" => ScalarType::B64,
+            "b128" => ScalarType::B128,
+            "pred" => ScalarType::Pred,
+            _ => return None,
+        })
+    }
+
+    /// The modifier without its leading `.`, the inverse of `from_modifier`.
+    pub fn modifier(&self) -> &'static str {
+        match self {
+            ScalarType::S8 => "s8",
+            ScalarType::S16 => "s16",
+            ScalarType::S32 => "s32",
+            ScalarType::S64 => "s64",
+            ScalarType::U8 => "u8",
+            ScalarType::U16 => "u16",
+            ScalarType::U32 => "u32",
+            ScalarType::U64 => "u64",
+            ScalarType::F16 => "f16",
+            ScalarType::F16x2 => "f16x2",
+            ScalarType::Bf16 => "bf16",
+            ScalarType::Bf16x2 => "bf16x2",
+            ScalarType::Tf32 => "tf32",
+            ScalarType::F32 => "f32",
+            ScalarType::F64 => "f64",
+            ScalarType::B8 => "b8",
+            ScalarType::B16 => "b16",
+            ScalarType::B32 => "b32",
+            ScalarType::B64 => "b64",
+            ScalarType::B128 => "b128",
+            ScalarType::Pred => "pred",
+        }
+    }
+
+    pub fn is_float(&self) -> bool {
+        matches!(
+            self,
+            ScalarType::F16
+                | ScalarType::F16x2
+                | ScalarType::Bf16
+                | ScalarType::Bf16x2
+                | ScalarType::Tf32
+                | ScalarType::F32
+                | ScalarType::F64
+        )
+    }
+
+    pub fn bits(&self) -> usize {
+        match self {
+            ScalarType::Pred => 1,
+            ScalarType::S8 | ScalarType::U8 | ScalarType::B8 => 8,
+            ScalarType::S16 | ScalarType::U16 | ScalarType::F16 | ScalarType::Bf16 | ScalarType::B16 => 16,
+            ScalarType::S32
+            | ScalarType::U32
+            | ScalarType::F16x2
+            | ScalarType::Bf16x2
+            | ScalarType::Tf32
+            | ScalarType::F32
+            | ScalarType::B32 => 32,
+            ScalarType::S64 | ScalarType::U64 | ScalarType::F64 | ScalarType::B64 => 64,
+            ScalarType::B128 => 128,
+        }
+    }
+
+    /// The size in bytes; `.pred` occupies a single byte.
+    pub fn size(&self) -> usize {
+        self.bits().div_ceil(8)
+    }
+}
+
+/// The width of a `.v2`, `.v4` or `.v8` vector access.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VectorWidth {
+    V2,
+    V4,
+    V8,
+}
+
+impl VectorWidth {
+    pub fn from_modifier(modifier: &str) -> Option<Self> {
+        Some(match modifier {
+            "v2" => VectorWidth::V2,
+            "v4" => VectorWidth::V4,
+            "v8" => VectorWidth::V8,
+            _ => return None,
+        })
+    }
+
+    pub fn lanes(&self) -> usize {
+        match self {
+            VectorWidth::V2 => 2,
+            VectorWidth::V4 => 4,
+            VectorWidth::V8 => 8,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum StateSpace {
+    Reg,
+    Sreg,
+    Const,
+    Global,
+    Local,
+    Param,
+    /// `.shared`, the shared memory of the executing CTA
+    Shared,
+    /// `.shared::cta`, the same window as `.shared` spelled explicitly
+    SharedCta,
+    /// `.shared::cluster`, the shared memory of any CTA in the cluster
+    SharedCluster,
+    Tex,
+    /// No state space qualifier: the address is resolved at runtime.
+    Generic,
+}
+
+impl StateSpace {
+    pub fn from_modifier(modifier: &str) -> Option<Self> {
+        Some(match modifier {
+            "reg" => StateSpace::Reg,
+            "sreg" => StateSpace::Sreg,
+            "const" => StateSpace::Const,
+            "global" => StateSpace::Global,
+            "local" => StateSpace::Local,
+            "param" => StateSpace::Param,
+            "shared" => StateSpace::Shared,
+            "shared::cta" => StateSpace::SharedCta,
+            "shared::cluster" => StateSpace::SharedCluster,
+            "tex" => StateSpace::Tex,
+            _ => return None,
+        })
+    }
+
+    /// The modifier without its leading `.`, or `None` for the generic state space.
+    pub fn modifier(self) -> Option<&'static str> {
+        Some(match self {
+            StateSpace::Reg => "reg",
+            StateSpace::Sreg => "sreg",
+            StateSpace::Const => "const",
+            StateSpace::Global => "global",
+            StateSpace::Local => "local",
+            StateSpace::Param => "param",
+            StateSpace::Shared => "shared",
+            StateSpace::SharedCta => "shared::cta",
+            StateSpace::SharedCluster => "shared::cluster",
+            StateSpace::Tex => "tex",
+            StateSpace::Generic => return None,
+        })
+    }
+
+    pub fn is_shared(self) -> bool {
+        matches!(self, StateSpace::Shared | StateSpace::SharedCta | StateSpace::SharedCluster)
+    }
+
+    /// Whether `self` and `other` name the same memory, counting `.shared`, `.shared::cta`
+    /// and `.shared::cluster` as one family.
+    pub fn same_family(self, other: StateSpace) -> bool {
+        self == other || (self.is_shared() && other.is_shared())
+    }
+
+    /// Whether addresses in this state space only reach the shared memory of the executing CTA.
+    pub fn is_cta_shared(self) -> bool {
+        matches!(self, StateSpace::Shared | StateSpace::SharedCta)
+    }
+}
+
+/// The memory consistency semantics of a memory operation or fence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum MemoryOrder {
+    Volatile,
+    Relaxed,
+    Acquire,
+    Release,
+    AcqRel,
+    Sc,
+}
+
+impl MemoryOrder {
+    pub fn from_modifier(modifier: &str) -> Option<Self> {
+        Some(match modifier {
+            "volatile" => MemoryOrder::Volatile,
+            "relaxed" => MemoryOrder::Relaxed,
+            "acquire" => MemoryOrder::Acquire,
+            "release" => MemoryOrder::Release,
+            "acq_rel" => MemoryOrder::AcqRel,
+            "sc" => MemoryOrder::Sc,
+            _ => return None,
+        })
+    }
+}
+
+/// The set of threads with which a memory operation or fence synchronizes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[non_exhaustive]
+pub enum Scope {
+    Cta,
+    Cluster,
+    Gpu,
+    Sys,
+}
+
+impl Scope {
+    pub fn from_modifier(modifier: &str) -> Option<Self> {
+        Some(match modifier {
+            "cta" => Scope::Cta,
+            "cluster" => Scope::Cluster,
+            "gpu" => Scope::Gpu,
+            "sys" => Scope::Sys,
+            _ => return None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test_types {
+    use crate::parser::instruction::types::{MemoryOrder, ScalarType, Scope, StateSpace, VectorWidth};
+
+    #[test]
+    fn scalar_sizes() {
+        assert_eq!(ScalarType::from_modifier("b64").map(|ty| ty.size()), Some(8));
+        assert_eq!(ScalarType::from_modifier("f16x2").map(|ty| ty.size()), Some(4));
+        assert_eq!(ScalarType::from_modifier("pred").map(|ty| ty.size()), Some(1));
+        assert_eq!(ScalarType::from_modifier("param"), None);
+    }
+
+    #[test]
+    fn modifiers_round_trip() {
+        for modifier in ["s8", "u64", "f16x2", "bf16", "tf32", "f64", "b128", "pred"] {
+            assert_eq!(ScalarType::from_modifier(modifier).map(|ty| ty.modifier()), Some(modifier));
+        }
+        for modifier in ["global", "shared::cta", "param", "tex"] {
+            assert_eq!(StateSpace::from_modifier(modifier).and_then(StateSpace::modifier), Some(modifier));
+        }
+        assert_eq!(StateSpace::Generic.modifier(), None);
+        assert!(ScalarType::Bf16x2.is_float() && !ScalarType::B32.is_float());
+    }
+
+    #[test]
+    fn vector_widths() {
+        assert_eq!(VectorWidth::from_modifier("v4").map(|width| width.lanes()), Some(4));
+        assert_eq!(VectorWidth::from_modifier("b32"), None);
+    }
+
+    #[test]
+    fn orders_and_scopes() {
+        assert_eq!(MemoryOrder::from_modifier("acq_rel"), Some(MemoryOrder::AcqRel));
+        assert_eq!(Scope::from_modifier("sys"), Some(Scope::Sys));
+        assert!(Scope::Cta < Scope::Sys);
+        assert_eq!(Scope::from_modifier("global"), None);
+    }
+
+    #[test]
+    fn state_spaces() {
+        assert_eq!(StateSpace::from_modifier("shared"), Some(StateSpace::Shared));
+        assert_eq!(StateSpace::from_modifier("wide"), None);
+        assert_eq!(StateSpace::from_modifier("shared::cluster"), Some(StateSpace::SharedCluster));
+        assert!(StateSpace::SharedCta.is_cta_shared());
+        assert!(StateSpace::SharedCluster.is_shared() && !StateSpace::SharedCluster.is_cta_shared());
+        assert!(StateSpace::Shared.same_family(StateSpace::SharedCluster));
+        assert!(!StateSpace::Global.same_family(StateSpace::Generic));
+    }
+}
@@ -0,0 +1,68 @@
+pub(crate) mod parse;
+
+/// An entry of the module's file table, declared with `.file 1 "kernel.cu"`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct File<'a> {
+    pub index: u32,
+    pub name: &'a str,
+}
+
+/// A source position, declared with `.loc 1 42 7` inside a function body.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Location {
+    pub file: u32,
+    pub line: u32,
+    pub column: u32,
+}
+
+#[cfg(test)]
+mod test_parse_file {
+    use crate::parser::location::{File, parse::parse_file};
+
+    #[test]
+    fn index_and_name() {
+        assert_eq!(
+            parse_file(".file 1 \"kernel.cu\""),
+            Ok(("", File { index: 1, name: "kernel.cu" }))
+        );
+    }
+
+    #[test]
+    fn timestamp_and_size() {
+        assert_eq!(
+            parse_file(".file\t2 \"/src/lib.rs\", 1681234567, 4096\n"),
+            Ok(("\n", File { index: 2, name: "/src/lib.rs" }))
+        );
+    }
+
+    #[test]
+    fn missing_name() {
+        assert!(parse_file(".file 1").is_err());
+    }
+}
+
+#[cfg(test)]
+mod test_parse_location {
+    use crate::parser::location::{Location, parse::parse_location};
+
+    #[test]
+    fn file_line_column() {
+        assert_eq!(
+            parse_location(".loc 1 42 7\n"),
+            Ok(("\n", Location { file: 1, line: 42, column: 7 }))
+        );
+    }
+
+    #[test]
+    fn inlined_at() {
+        assert_eq!(
+            parse_location(".loc\t1 42 7, function_name $L__info_string0, inlined_at 1 10 3\n"),
+            Ok(("\n", Location { file: 1, line: 42, column: 7 }))
+        );
+    }
+
+    #[test]
+    fn not_a_location() {
+        assert!(parse_location(".local .align 8 .b8 __local_depot1[16];").is_err());
+    }
+}
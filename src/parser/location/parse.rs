@@ -0,0 +1,40 @@
+use nom::{
+    bytes::complete::{tag, take_while},
+    character::complete::{char, space0, space1, u32},
+    combinator::opt,
+    sequence::{delimited, preceded, Tuple},
+    IResult, Parser,
+};
+
+use super::{File, Location};
+
+fn rest_of_line(input: &str) -> IResult<&str, &str> {
+    preceded(
+        space0.and(char(',')),
+        take_while(|c: char| c != '\n'),
+    )(input)
+}
+
+pub(crate) fn parse_file(input: &str) -> IResult<&str, File<'_>> {
+    (
+        preceded(tag(".file").and(space1), u32),
+        preceded(
+            space1,
+            delimited(char('"'), take_while(|c: char| c != '"'), char('"')),
+        ),
+        opt(rest_of_line),
+    )
+    .parse(input)
+    .map(|(input, (index, name, _))| (input, File { index, name }))
+}
+
+pub(crate) fn parse_location(input: &str) -> IResult<&str, Location> {
+    (
+        preceded(tag(".loc").and(space1), u32),
+        preceded(space1, u32),
+        preceded(space1, u32),
+        opt(rest_of_line),
+    )
+    .parse(input)
+    .map(|(input, (file, line, column, _))| (input, Location { file, line, column }))
+}
@@ -1,16 +1,51 @@
 use nom::{bytes::complete::take_while1, character::complete::char, sequence::delimited, IResult};
 
+pub mod availability;
+#[cfg(feature = "std")]
+pub mod builder;
+#[cfg(feature = "std")]
+pub mod call_graph;
+pub mod cfg;
 pub mod comment;
+pub mod cst;
+pub mod diagnostic;
 pub mod function;
 pub mod global;
+pub mod instruction;
+pub mod location;
 pub mod preamble;
+pub mod printer;
 pub mod ptx_file;
+pub mod section;
+#[cfg(feature = "std")]
+pub mod symbol;
+#[cfg(feature = "std")]
+pub mod validate;
+pub mod visit;
 
-use function::Function;
-use global::Global;
 use preamble::Preamble;
+#[cfg(feature = "std")]
+use function::{body::{BodyLine, Operation}, inline_asm::InlineAsm, Function};
+#[cfg(feature = "std")]
+use diagnostic::Diagnostic;
+#[cfg(feature = "std")]
+use global::{Global, OpaqueDeclaration};
+#[cfg(feature = "std")]
+use instruction::{
+    async_copy::{check_async_copies, AsyncCopyHazard, AsyncInstruction},
+    atomic::{AtomicInstruction, FenceInstruction},
+    cluster::{ClusterInstruction, ClusterRegister},
+    memory::MemoryAccess,
+    mma::TensorInstruction,
+    texture::TextureInstruction,
+    types::{Scope, StateSpace},
+};
+#[cfg(feature = "std")]
+use location::File;
+#[cfg(feature = "std")]
+use section::{dwarf::{CompileUnit, DwarfError}, Section, SectionBytes};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct PtxParser<'a> {
     preamble: Preamble<'a>,
     body: Option<&'a str>,
@@ -23,11 +58,263 @@ impl<'a> PtxParser<'a> {
 }
 
 #[cfg(feature = "std")]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct PtxFile<'a> {
     preamble: Preamble<'a>,
     functions: Vec<Function<'a>>,
     globals: Vec<Global<'a>>,
+    files: Vec<File<'a>>,
+    sections: Vec<Section<'a>>,
+    /// The kind of every item, in the order of the source.
+    items: Vec<ptx_file::ItemKind>,
+    /// The whole text the module was parsed from, if it was parsed from a `&str`.
+    source: Option<&'a str>,
+}
+
+/// The text the modules were parsed from is not compared.
+#[cfg(feature = "std")]
+impl PartialEq for PtxFile<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.preamble == other.preamble
+            && self.functions == other.functions
+            && self.globals == other.globals
+            && self.files == other.files
+            && self.sections == other.sections
+            && self.items == other.items
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'a> PtxFile<'a> {
+    pub fn preamble(&self) -> &Preamble<'a> {
+        &self.preamble
+    }
+
+    pub fn functions(&self) -> &[Function<'a>] {
+        &self.functions
+    }
+
+    pub fn globals(&self) -> &[Global<'a>] {
+        &self.globals
+    }
+
+    /// The file table built from the module's `.file` directives.
+    pub fn files(&self) -> &[File<'a>] {
+        &self.files
+    }
+
+    pub fn file(&self, index: u32) -> Option<&File<'a>> {
+        self.files.iter().find(|file| file.index == index)
+    }
+
+    pub fn sections(&self) -> &[Section<'a>] {
+        &self.sections
+    }
+
+    /// The kind of every item, in the order of the source.
+    pub(crate) fn items(&self) -> &[ptx_file::ItemKind] {
+        &self.items
+    }
+
+    pub fn section(&self, name: &str) -> Option<&Section<'a>> {
+        self.sections.iter().find(|section| section.name == name)
+    }
+
+    /// Lays out every section as bytes, resolving references between them.
+    pub fn section_bytes(&self) -> Vec<SectionBytes<'a>> {
+        section::section_bytes(&self.sections)
+    }
+
+    /// Decodes the compile units described by the module's DWARF sections, if any.
+    pub fn compile_units(&self) -> Result<Vec<CompileUnit>, DwarfError> {
+        let sections = self.section_bytes();
+        let bytes = |name: &str| {
+            sections
+            .iter()
+            .find(|section| section.name == name)
+            .map(|section| section.bytes.as_slice())
+        };
+        match (bytes(".debug_abbrev"), bytes(".debug_info")) {
+            (Some(debug_abbrev), Some(debug_info)) => {
+                section::dwarf::compile_units(debug_abbrev, debug_info, bytes(".debug_str"))
+            }
+            _ => Ok(Vec::new()),
+        }
+    }
+
+    /// The `.texref`, `.samplerref` and `.surfref` declarations of the module.
+    pub fn opaque_declarations(&self) -> Vec<OpaqueDeclaration<'a>> {
+        self.globals.iter().filter_map(Global::opaque).collect()
+    }
+
+    /// Every operation which converts into `T`, with the name of the function using it.
+    pub fn typed_operations<T>(&self) -> Vec<(&'a str, T)>
+    where
+        T: for<'o> TryFrom<&'o Operation<'a>>,
+    {
+        self.functions
+            .iter()
+            .flat_map(|function| {
+                let name = function.signature.name;
+                function
+                .operations()
+                .filter_map(move |operation| {
+                    T::try_from(&operation)
+                    .ok()
+                    .map(|instruction| (name, instruction))
+                })
+            })
+            .collect()
+    }
+
+    /// Every texture and surface instruction, with the name of the function using it.
+    pub fn texture_instructions(&self) -> Vec<(&'a str, TextureInstruction<'a>)> {
+        self.typed_operations()
+    }
+
+    /// Every `ld`, `ldu` and `st` instruction, with the name of the function using it.
+    pub fn memory_accesses(&self) -> Vec<(&'a str, MemoryAccess<'a>)> {
+        self.typed_operations()
+    }
+
+    /// Every tensor-core instruction, with the name of the function using it.
+    pub fn tensor_instructions(&self) -> Vec<(&'a str, TensorInstruction<'a>)> {
+        self.typed_operations()
+    }
+
+    /// Every asynchronous copy and mbarrier instruction, with the name of the function using it.
+    pub fn async_instructions(&self) -> Vec<(&'a str, AsyncInstruction<'a>)> {
+        self.typed_operations()
+    }
+
+    /// Every `atom` and `red` instruction, with the name of the function using it.
+    pub fn atomic_instructions(&self) -> Vec<(&'a str, AtomicInstruction<'a>)> {
+        self.typed_operations()
+    }
+
+    /// The atomics on `state_space` whose effective scope is `scope`, e.g. every
+    /// system-scoped atomic on global memory. Asking for any shared state space finds
+    /// the atomics on `.shared`, `.shared::cta` and `.shared::cluster` alike.
+    pub fn atomics_with(&self, state_space: StateSpace, scope: Scope) -> Vec<(&'a str, AtomicInstruction<'a>)> {
+        self.atomic_instructions()
+            .into_iter()
+            .filter(|(_, atomic)| atomic.state_space.same_family(state_space) && atomic.effective_scope() == scope)
+            .collect()
+    }
+
+    /// Every `fence` and `membar` instruction, with the name of the function using it.
+    pub fn fences(&self) -> Vec<(&'a str, FenceInstruction<'a>)> {
+        self.typed_operations()
+    }
+
+    /// Every `mapa`, `getctarank` and cluster barrier, with the name of the function using it.
+    pub fn cluster_instructions(&self) -> Vec<(&'a str, ClusterInstruction<'a>)> {
+        self.typed_operations()
+    }
+
+    /// Every read of a cluster special register such as `%cluster_ctarank`, in program order.
+    pub fn cluster_registers(&self) -> Vec<(&'a str, ClusterRegister)> {
+        self.functions
+            .iter()
+            .flat_map(|function| {
+                let name = function.signature.name;
+                function
+                .operations()
+                .flat_map(|operation| operation.operands().unwrap_or_default())
+                .filter_map(move |operand| {
+                    ClusterRegister::from_name(operand.name()?).map(|register| (name, register))
+                })
+            })
+            .collect()
+    }
+
+    /// Checks the module for what `ptxas` would reject: undefined and duplicate names,
+    /// mistyped registers, invalid modifiers, wrong operand counts, state space misuse
+    /// and pointers narrower or wider than `.address_size`, as well as instructions
+    /// its `.version` and `.target` do not allow.
+    pub fn validate(&self) -> Vec<Diagnostic<'a>> {
+        validate::validate(self)
+    }
+
+    /// The calls between the functions of the module, including those through the
+    /// function pointers of global initializers.
+    pub fn call_graph(&self) -> call_graph::CallGraph<'a> {
+        call_graph::CallGraph::new(self)
+    }
+
+    /// The most registers live at once in each `.entry`, by register class.
+    pub fn register_pressure(&self) -> Vec<cfg::liveness::RegisterPressure<'a>> {
+        self.functions
+            .iter()
+            .filter(|function| function.signature.entry)
+            .filter_map(cfg::liveness::RegisterPressure::new)
+            .collect()
+    }
+
+    /// Flags the instructions and directives the module's `.version` and `.target` do not allow.
+    pub fn check_availability(&self) -> Vec<Diagnostic<'a>> {
+        availability::check(self)
+    }
+
+    /// The oldest `.version` and `.target` which allow everything the module uses, or
+    /// `None` if it uses the architecture-specific features of two targets.
+    pub fn requirement(&self) -> Option<availability::Requirement> {
+        availability::requirement(self)
+    }
+
+    /// Every inline assembly region, with the name of the function containing it.
+    pub fn inline_asm(&self) -> Vec<(&'a str, InlineAsm<'a>)> {
+        self.functions
+            .iter()
+            .flat_map(|function| {
+                let name = function.signature.name;
+                function.inline_asm().map(move |region| (name, region))
+            })
+            .collect()
+    }
+
+    /// Checks, function by function, that asynchronous copies into shared memory are
+    /// waited on before shared memory is read.
+    pub fn async_copy_hazards(&self) -> Vec<(&'a str, AsyncCopyHazard<'a>)> {
+        self.functions
+            .iter()
+            .flat_map(|function| {
+                let name = function.signature.name;
+                check_async_copies(function.operations())
+                .into_iter()
+                .map(move |hazard| (name, hazard))
+            })
+            .collect()
+    }
+
+    /// Lists the instructions whose active `.loc` points at `line` of the file named
+    /// `file`, together with the name of the function containing them. Labels and
+    /// directives are left out.
+    pub fn instructions_at(&self, file: &str, line: u32) -> Vec<(&'a str, BodyLine<'a>)> {
+        let indices = self.files
+            .iter()
+            .filter(|f| f.name == file)
+            .map(|f| f.index)
+            .collect::<Vec<_>>();
+        self.functions
+            .iter()
+            .filter_map(|function| {
+                function.body
+                .clone()
+                .map(|body| (function.signature.name, body))
+            })
+            .flat_map(|(name, body)| {
+                body.located_lines()
+                .filter_map(Result::ok)
+                .filter_map(move |(_, (location, body_line))| match location {
+                    Some(location) if body_line.is_instruction() => Some((location, (name, body_line))),
+                    _ => None,
+                })
+            })
+            .filter(|(location, _)| location.line == line && indices.contains(&location.file))
+            .map(|(_, found)| found)
+            .collect()
+    }
 }
 
 fn is_special(c: char) -> bool {
@@ -39,6 +326,48 @@ fn parse_name(input: &str) -> IResult<&str, &str> {
     take_while1(|c: char| !c.is_whitespace() && !is_special(c))(input)
 }
 
+/// Whether `a` and `b` hold the same tokens, ignoring how they are laid out.
+pub(crate) fn same_tokens(a: &str, b: &str) -> bool {
+    a.split_whitespace().eq(b.split_whitespace())
+}
+
+/// Whether `a` and `b` are equal once all whitespace is removed, for text such as
+/// argument lists where whitespace may also be added or removed around punctuation.
+pub(crate) fn same_characters(a: &str, b: &str) -> bool {
+    let characters = |text: &'_ str| text.chars().filter(|c| !c.is_whitespace()).collect::<String>();
+    characters(a) == characters(b)
+}
+
+/// The byte range of `text` within `source`, if `text` was sliced from it.
+pub(crate) fn span_in(text: &str, source: &str) -> Option<core::ops::Range<usize>> {
+    let start = (text.as_ptr() as usize).checked_sub(source.as_ptr() as usize)?;
+    let end = start + text.len();
+    (end <= source.len()).then_some(start..end)
+}
+
+/// Whether `name` is a predefined register such as `%tid.x`, `%laneid` or `%envreg3`.
+pub(crate) fn is_special_register(name: &str) -> bool {
+    const NAMES: [&str; 24] = [
+        "tid", "ntid", "laneid", "warpid", "nwarpid", "ctaid", "nctaid", "smid", "nsmid", "gridid",
+        "clock", "clock_hi", "clock64", "globaltimer", "globaltimer_lo", "globaltimer_hi",
+        "total_smem_size", "aggr_smem_size", "dynamic_smem_size", "current_graph_exec",
+        "is_explicit_cluster", "clusterid", "nclusterid", "WARP_SZ",
+    ];
+    const PREFIXES: [&str; 4] = ["lanemask_", "envreg", "reserved_smem_offset_", "cluster_"];
+    let Some(name) = name.strip_prefix('%') else {
+        return name == "WARP_SZ";
+    };
+    let name = name.split('.').next().unwrap_or(name);
+    NAMES.contains(&name) || PREFIXES.iter().any(|prefix| name.starts_with(prefix)) || is_performance_monitor(name)
+}
+
+/// Whether `name` is one of the performance monitoring counters `pm0` to `pm7`, or their
+/// 64-bit forms `pm0_64` to `pm7_64`.
+fn is_performance_monitor(name: &str) -> bool {
+    let counter = name.strip_prefix("pm").map(|counter| counter.strip_suffix("_64").unwrap_or(counter));
+    matches!(counter, Some("0" | "1" | "2" | "3" | "4" | "5" | "6" | "7"))
+}
+
 fn parse_parenthesized_naive(input: &str) -> IResult<&str, &str> {
     delimited(
         char('('),
@@ -56,7 +385,7 @@ fn _parse_braced_naive(input: &str) -> IResult<&str, &str> {
 }
 
 fn parse_braced_balanced(input: &str) -> IResult<&str, &str> {
-    let mut chars = input.chars().enumerate();
+    let mut chars = input.char_indices();
     let (mut depth, mut end) = match chars.next() {
         Some((_, '{')) => (1, None),
         _ => return Err(nom::Err::Error(
@@ -196,3 +525,25 @@ mod test_parse_braced_balanced {
         assert!(parse_braced_balanced(input).is_err())
     }
 }
+
+#[cfg(test)]
+mod test_is_special_register {
+    use super::is_special_register;
+
+    #[test]
+    fn predefined_registers() {
+        assert!(is_special_register("%tid.x"));
+        assert!(is_special_register("%envreg3"));
+        assert!(is_special_register("%cluster_ctarank"));
+        assert!(!is_special_register("%rd1"));
+    }
+
+    #[test]
+    fn performance_monitors() {
+        assert!(is_special_register("%pm0"));
+        assert!(is_special_register("%pm7_64"));
+        assert!(!is_special_register("%pm8"));
+        assert!(!is_special_register("%pmax9"));
+        assert!(!is_special_register("%pm0_32"));
+    }
+}
@@ -1,28 +1,89 @@
 pub(crate) mod parse;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Preamble<'a> {
     version: Version<'a>,
     target: Target<'a>,
     address_size: AddressSize<'a>,
 }
 
-#[derive(Debug, PartialEq)]
-pub(super) struct Version<'a> {
+#[derive(Debug, Clone, PartialEq)]
+pub struct Version<'a> {
     major: &'a str,
     minor: &'a str,
 }
 
-#[derive(Debug, PartialEq)]
-pub(super) struct Target<'a> {
+#[derive(Debug, Clone, PartialEq)]
+pub struct Target<'a> {
     target: &'a str,
+    /// Trailing entries such as `debug` or `texmode_independent` in `.target sm_52, debug`.
+    options: Option<&'a str>,
 }
 
-#[derive(Debug, PartialEq)]
-pub(super) struct AddressSize<'a> {
+#[derive(Debug, Clone, PartialEq)]
+pub struct AddressSize<'a> {
     size: &'a str,
 }
 
+impl<'a> Preamble<'a> {
+    /// The `.version` directive.
+    pub fn version(&self) -> &Version<'a> {
+        &self.version
+    }
+
+    /// The `.target` directive.
+    pub fn target(&self) -> &Target<'a> {
+        &self.target
+    }
+
+    /// The `.address_size` directive.
+    pub fn address_size(&self) -> &AddressSize<'a> {
+        &self.address_size
+    }
+}
+
+impl<'a> Version<'a> {
+    pub fn major(&self) -> &'a str {
+        self.major
+    }
+
+    pub fn minor(&self) -> &'a str {
+        self.minor
+    }
+
+    /// The version as a `(major, minor)` pair, e.g. `(7, 5)` for `.version 7.5`.
+    pub fn number(&self) -> Option<(u32, u32)> {
+        Some((self.major.parse().ok()?, self.minor.parse().ok()?))
+    }
+}
+
+impl<'a> Target<'a> {
+    /// The architecture, e.g. `sm_90a`.
+    pub fn target(&self) -> &'a str {
+        self.target
+    }
+
+    /// The entries after the architecture, e.g. `debug` in `.target sm_52, debug`.
+    pub fn options(&self) -> impl Iterator<Item = &'a str> {
+        self.options
+            .into_iter()
+            .flat_map(|options| options.split(','))
+            .map(str::trim)
+            .filter(|option| !option.is_empty())
+    }
+}
+
+impl<'a> AddressSize<'a> {
+    pub fn size(&self) -> &'a str {
+        self.size
+    }
+
+    /// The address size in bits, 32 or 64.
+    pub fn bits(&self) -> Option<u32> {
+        self.size.parse().ok()
+    }
+}
+
 #[cfg(test)]
 mod test_parse_version {
     use crate::parser::preamble::{Version, parse::parse_version};
@@ -83,7 +144,7 @@ mod test_parse_target {
     fn no_whitespace() {
         assert_eq!(
             parse_target(".target sm_30"),
-            Ok(("", Target { target: "sm_30" }))
+            Ok(("", Target { target: "sm_30", options: None }))
         );
     }
 
@@ -96,7 +157,23 @@ mod test_parse_target {
     fn trailing_whitespace() {
         assert_eq!(
             parse_target(".target sm_30  "),
-            Ok(("  ", Target { target: "sm_30" }))
+            Ok(("  ", Target { target: "sm_30", options: None }))
+        );
+    }
+
+    #[test]
+    fn options() {
+        assert_eq!(
+            parse_target(".target sm_52, debug\n"),
+            Ok(("\n", Target { target: "sm_52", options: Some("debug") }))
+        );
+    }
+
+    #[test]
+    fn many_options() {
+        assert_eq!(
+            parse_target(".target sm_52 , texmode_independent,debug"),
+            Ok(("", Target { target: "sm_52", options: Some("texmode_independent,debug") }))
         );
     }
 
@@ -104,7 +181,7 @@ mod test_parse_target {
     fn immediate_comment() {
         assert_eq!(
             parse_target(".target sm_30// This is a comment"),
-            Ok(("// This is a comment", Target { target: "sm_30" }))
+            Ok(("// This is a comment", Target { target: "sm_30", options: None }))
         );
     }
 }
@@ -158,7 +235,7 @@ mod test_parse_preamble {
                         major: "1",
                         minor: "0"
                     },
-                    target: Target { target: "sm_30" },
+                    target: Target { target: "sm_30", options: None },
                     address_size: AddressSize { size: "64" }
                 })
             ))
@@ -176,7 +253,7 @@ mod test_parse_preamble {
                         major: "1",
                         minor: "0"
                     },
-                    target: Target { target: "sm_30" },
+                    target: Target { target: "sm_30", options: None },
                     address_size: AddressSize { size: "64" }
                 })
             ))
@@ -194,7 +271,7 @@ mod test_parse_preamble {
                         major: "1",
                         minor: "0"
                     },
-                    target: Target { target: "sm_30" },
+                    target: Target { target: "sm_30", options: None },
                     address_size: AddressSize { size: "64" }
                 })
             ))
@@ -212,7 +289,7 @@ mod test_parse_preamble {
                         major: "1",
                         minor: "0"
                     },
-                    target: Target { target: "sm_30" },
+                    target: Target { target: "sm_30", options: None },
                     address_size: AddressSize { size: "64" }
                 })
             ))
@@ -230,7 +307,7 @@ mod test_parse_preamble {
                         major: "1",
                         minor: "0"
                     },
-                    target: Target { target: "sm_30" },
+                    target: Target { target: "sm_30", options: None },
                     address_size: AddressSize { size: "64" }
                 })
             ))
@@ -248,10 +325,19 @@ mod test_parse_preamble {
                         major: "1",
                         minor: "0"
                     },
-                    target: Target { target: "sm_30" },
+                    target: Target { target: "sm_30", options: None },
                     address_size: AddressSize { size: "64" }
                 })
             ))
         );
     }
+
+    #[test]
+    fn accessors() {
+        let (_, preamble) = parse_preamble(".version 8.0\n.target sm_52, debug\n.address_size 64").unwrap();
+        assert_eq!(preamble.version().number(), Some((8, 0)));
+        assert_eq!(preamble.target().target(), "sm_52");
+        assert_eq!(preamble.target().options().collect::<Vec<_>>(), ["debug"]);
+        assert_eq!(preamble.address_size().bits(), Some(64));
+    }
 }
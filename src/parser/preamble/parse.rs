@@ -1,7 +1,8 @@
 use nom::{
     bytes::complete::{tag, take_while1},
-    character::complete::{char, space1},
-    combinator::opt,
+    character::complete::{char, space0, space1},
+    multi::many0_count,
+    combinator::{opt, recognize},
     sequence::{preceded, Tuple},
     IResult, Parser,
 };
@@ -10,7 +11,7 @@ use crate::parser::{comment::parse::many1_comments_or_whitespace, parse_name};
 
 use super::{Preamble, Target, Version, AddressSize};
 
-pub(crate) fn parse_preamble(input: &str) -> IResult<&str, Preamble> {
+pub(crate) fn parse_preamble(input: &str) -> IResult<&str, Preamble<'_>> {
     (
         preceded(
             opt(many1_comments_or_whitespace), 
@@ -38,7 +39,7 @@ pub(crate) fn parse_preamble(input: &str) -> IResult<&str, Preamble> {
     })
 }
 
-pub(super) fn parse_version(input: &str) -> IResult<&str, Version> {
+pub(crate) fn parse_version(input: &str) -> IResult<&str, Version<'_>> {
     (
         preceded(tag(".version").and(space1), take_while1(char::is_numeric)),
         preceded(char('.'), take_while1(char::is_numeric)),
@@ -47,14 +48,22 @@ pub(super) fn parse_version(input: &str) -> IResult<&str, Version> {
     .map(|(input, (major, minor))| (input, Version { major, minor }))
 }
 
-pub(super) fn parse_target(input: &str) -> IResult<&str, Target> {
+pub(crate) fn parse_target(input: &str) -> IResult<&str, Target<'_>> {
     preceded(
         tag(".target").and(space1),
-        parse_name.map(|target| Target { target }),
+        parse_name
+        .and(opt(preceded(
+            space0.and(char(',')).and(space0),
+            recognize(parse_name.and(many0_count(preceded(
+                space0.and(char(',')).and(space0),
+                parse_name,
+            )))),
+        )))
+        .map(|(target, options)| Target { target, options }),
     )(input)
 }
 
-pub(super) fn parse_address_size(input: &str) -> IResult<&str, AddressSize> {
+pub(crate) fn parse_address_size(input: &str) -> IResult<&str, AddressSize<'_>> {
     preceded(
         tag(".address_size").and(space1),
         parse_name.map(|size| AddressSize { size }),
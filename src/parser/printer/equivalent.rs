@@ -0,0 +1,164 @@
+use std::vec::Vec;
+
+use crate::parser::{
+    comment::Comment,
+    function::{
+        body::{BodyLine, FunctionBody, FunctionCall, Operation, Register},
+        Directives, Function, FunctionSignature, Parameter, Parameters, ReturnValue,
+    },
+    same_characters, same_tokens,
+};
+#[cfg(feature = "std")]
+use crate::parser::PtxFile;
+
+/// Whether two nodes hold the same PTX whatever their layout, as is the case for a node
+/// and what its text formatted with `Printer::format` parses to. `==` also compares the
+/// layout of the text kept as written, which `Printer::print` leaves unchanged.
+pub trait Equivalent {
+    fn equivalent(&self, other: &Self) -> bool;
+}
+
+impl<T: Equivalent> Equivalent for Option<T> {
+    fn equivalent(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Some(a), Some(b)) => a.equivalent(b),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+}
+
+impl<T: Equivalent> Equivalent for [T] {
+    fn equivalent(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().zip(other).all(|(a, b)| a.equivalent(b))
+    }
+}
+
+/// The spacing inside the delimiters may differ.
+impl Equivalent for Comment<'_> {
+    fn equivalent(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Comment::Line(a), Comment::Line(b)) | (Comment::Block(a), Comment::Block(b)) => same_tokens(a, b),
+            _ => false,
+        }
+    }
+}
+
+impl Equivalent for Register<'_> {
+    fn equivalent(&self, other: &Self) -> bool {
+        same_tokens(self.raw_string(), other.raw_string())
+    }
+}
+
+/// Arguments which are not operands are compared token by token.
+impl Equivalent for Operation<'_> {
+    fn equivalent(&self, other: &Self) -> bool {
+        self.operation() == other.operation()
+            && match (self.arguments(), other.arguments()) {
+                (Some(a), Some(b)) => same_tokens(a, b),
+                _ => self.raw_arguments() == other.raw_arguments(),
+            }
+    }
+}
+
+/// Whitespace may be added or removed around the punctuation of a call sequence. Once
+/// the lines of either were replaced, the lines are compared instead of the text.
+impl Equivalent for FunctionCall<'_> {
+    fn equivalent(&self, other: &Self) -> bool {
+        let same_text = match (self.edited_lines(), other.edited_lines()) {
+            (None, None) => {
+                same_characters(self.setup(), other.setup()) && same_characters(self.arguments(), other.arguments())
+            }
+            _ => {
+                let lines = |call: &Self| call.lines().map_while(Result::ok).map(|(_, line)| line).collect::<Vec<_>>();
+                let statement = |call: &Self| call.arguments().split(';').next().unwrap_or_default();
+                lines(self).equivalent(&lines(other)) && same_characters(statement(self), statement(other))
+            }
+        };
+        same_text
+            && self.function().trim() == other.function().trim()
+            && self.comment().text().trim() == other.comment().text().trim()
+    }
+}
+
+impl Equivalent for BodyLine<'_> {
+    fn equivalent(&self, other: &Self) -> bool {
+        match (self, other) {
+            (BodyLine::Register(a), BodyLine::Register(b)) => a.equivalent(b),
+            (BodyLine::Operation(a), BodyLine::Operation(b)) => a.equivalent(b),
+            (BodyLine::FunctionCall(a), BodyLine::FunctionCall(b)) => a.equivalent(b),
+            (BodyLine::Unknown(a), BodyLine::Unknown(b)) => same_tokens(a, b),
+            (BodyLine::Comment(a), BodyLine::Comment(b)) => a.equivalent(b),
+            (BodyLine::Guarded(a, operation), BodyLine::Guarded(b, other_operation)) => {
+                a == b && operation.equivalent(other_operation)
+            }
+            (a, b) => a == b,
+        }
+    }
+}
+
+/// Bodies are compared line by line, whether their lines were parsed or given by
+/// `FunctionBody::from_lines`.
+impl Equivalent for FunctionBody<'_> {
+    fn equivalent(&self, other: &Self) -> bool {
+        let (lines, rest) = self.parsed();
+        let (other_lines, other_rest) = other.parsed();
+        lines.equivalent(&other_lines) && rest == other_rest
+    }
+}
+
+impl Equivalent for Directives<'_> {
+    fn equivalent(&self, other: &Self) -> bool {
+        same_tokens(self.raw_string(), other.raw_string())
+    }
+}
+
+impl Equivalent for ReturnValue<'_> {
+    fn equivalent(&self, other: &Self) -> bool {
+        same_tokens(self.raw_string(), other.raw_string())
+    }
+}
+
+impl Equivalent for Parameter<'_> {
+    fn equivalent(&self, other: &Self) -> bool {
+        self.name == other.name
+            && self.ty == other.ty
+            && self.size == other.size
+            && same_tokens(self.raw_string(), other.raw_string())
+    }
+}
+
+impl Equivalent for Parameters<'_> {
+    fn equivalent(&self, other: &Self) -> bool {
+        self.params.equivalent(&other.params) && same_tokens(self.raw_string(), other.raw_string())
+    }
+}
+
+impl Equivalent for FunctionSignature<'_> {
+    fn equivalent(&self, other: &Self) -> bool {
+        self.visible == other.visible
+            && self.entry == other.entry
+            && self.return_value.equivalent(&other.return_value)
+            && self.name == other.name
+            && self.parameters.equivalent(&other.parameters)
+            && self.directives.equivalent(&other.directives)
+    }
+}
+
+impl Equivalent for Function<'_> {
+    fn equivalent(&self, other: &Self) -> bool {
+        self.signature.equivalent(&other.signature) && self.body.equivalent(&other.body)
+    }
+}
+
+#[cfg(feature = "std")]
+impl Equivalent for PtxFile<'_> {
+    fn equivalent(&self, other: &Self) -> bool {
+        self.preamble() == other.preamble()
+            && self.items() == other.items()
+            && self.functions().equivalent(other.functions())
+            && self.globals() == other.globals()
+            && self.files() == other.files()
+            && self.sections() == other.sections()
+    }
+}
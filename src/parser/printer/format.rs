@@ -0,0 +1,310 @@
+use nom::IResult;
+
+use crate::parser::{
+    cst::{
+        parse::{line_nodes, trailing_trivia},
+        Node, NodeKind, SyntaxTree, Trivia,
+    },
+    function::{
+        body::{BodyLine, FunctionCall},
+        parse::parse_function_signature,
+    },
+    location::parse::parse_file,
+    preamble::parse::{parse_address_size, parse_target, parse_version},
+    section::parse::parse_section,
+};
+
+use super::{write_tokens, Print, Printer};
+
+impl Printer {
+    /// Rewrites `tree` in this printer's layout, keeping its comments: items are separated
+    /// by blank lines, every instruction is on a line of its own, and each comment is on a
+    /// line of its own or one space after the line it follows. Text which cannot be parsed
+    /// is kept as it is.
+    pub fn format(&self, tree: &SyntaxTree<'_>) -> String {
+        let printer = Printer { tokens: true, ..self.clone() };
+        let mut formatter = Formatter { printer: &printer, out: String::new() };
+        formatter.module(tree.root());
+        formatter.out
+    }
+}
+
+struct Formatter<'p> {
+    printer: &'p Printer,
+    out: String,
+}
+
+// Writing to a `String` cannot fail, so the results of `Print::print` are ignored.
+impl Formatter<'_> {
+    fn module(&mut self, module: &Node<'_>) {
+        let mut previous = None;
+        for item in module.children() {
+            let kind = item.kind();
+            let grouped = matches!(kind, NodeKind::Global | NodeKind::File | NodeKind::Unknown);
+            let preamble = matches!(kind, NodeKind::Version | NodeKind::Target | NodeKind::AddressSize);
+            if previous.is_some() && !preamble && !(grouped && previous == Some(kind)) {
+                self.out.push('\n');
+            }
+            previous = Some(kind);
+            if self.comments(item.leading_trivia(), 0) && kind == NodeKind::Version {
+                self.out.push('\n');
+            }
+            match kind {
+                NodeKind::Version => self.parsed(item.text(), parse_version),
+                NodeKind::Target => self.parsed(item.text(), parse_target),
+                NodeKind::AddressSize => self.parsed(item.text(), parse_address_size),
+                NodeKind::Function => self.function(item),
+                NodeKind::Global => match item.global() {
+                    Some(global) => self.node(&global),
+                    None => self.out.push_str(item.text().trim()),
+                },
+                NodeKind::File => self.parsed(item.text(), parse_file),
+                NodeKind::Section => self.parsed(item.text(), parse_section),
+                _ => self.out.push_str(item.text().trim()),
+            }
+            self.end_line(item.trailing_trivia());
+        }
+        if has_comments(module.trailing_trivia()) {
+            self.out.push('\n');
+            self.comments(module.trailing_trivia(), 0);
+        }
+    }
+
+    fn function(&mut self, function: &Node<'_>) {
+        let [signature, end] = function.children() else {
+            self.out.push_str(function.text().trim());
+            return;
+        };
+        self.parsed(signature.text(), parse_function_signature);
+        if end.kind() == NodeKind::Body {
+            self.end_line(signature.trailing_trivia());
+            self.comments(end.leading_trivia(), 0);
+            self.body(end);
+        } else {
+            self.out.push(';');
+            self.trailing(signature.trailing_trivia());
+            self.trailing(end.leading_trivia());
+            self.trailing(end.trailing_trivia());
+        }
+    }
+
+    fn body(&mut self, body: &Node<'_>) {
+        let [open, lines @ .., close] = body.children() else {
+            self.out.push_str(body.text().trim());
+            return;
+        };
+        self.out.push('{');
+        self.end_line(open.trailing_trivia());
+        self.lines(lines, 1);
+        self.comments(close.leading_trivia(), 1);
+        self.out.push('}');
+    }
+
+    /// Writes the lines of a body, with labels one level left of the instructions.
+    fn lines(&mut self, lines: &[Node<'_>], depth: usize) {
+        for line in lines {
+            self.comments(line.leading_trivia(), depth);
+            match line.body_line() {
+                Some(label @ BodyLine::Label(_)) => {
+                    self.indent(depth.saturating_sub(1));
+                    self.node(&label);
+                }
+                Some(BodyLine::FunctionCall(call)) => {
+                    self.indent(depth);
+                    self.call(&call, depth);
+                }
+                Some(BodyLine::Unknown(raw_string)) => {
+                    self.indent(depth);
+                    let _ = write_tokens(&mut self.out, raw_string);
+                    self.out.push(';');
+                }
+                Some(line) => {
+                    self.indent(depth);
+                    self.node(&line);
+                }
+                None => {
+                    self.indent(depth);
+                    self.out.push_str(line.text().trim());
+                }
+            }
+            self.end_line(line.trailing_trivia());
+        }
+    }
+
+    /// Writes a call sequence as a block of its own, with the `call.uni` statement on one line.
+    fn call(&mut self, call: &FunctionCall<'_>, depth: usize) {
+        let (setup, opening) = trailing_trivia(call.setup());
+        self.out.push('{');
+        self.end_line(&opening);
+        self.nested_lines(setup, depth + 1);
+        let (arguments, semicolon, after_call) = match call.arguments().split_once(';') {
+            Some((arguments, after_call)) => (arguments, ";", after_call),
+            None => (call.arguments(), "", ""),
+        };
+        self.indent(depth + 1);
+        self.out.push_str("call.uni");
+        let _ = self.printer.operands(&mut self.out, "call.uni");
+        self.call_operands(call.function());
+        self.out.push_str(", ");
+        self.call_operands(arguments);
+        self.out.push_str(semicolon);
+        self.out.push('\n');
+        self.nested_lines(after_call, depth + 1);
+        self.indent(depth);
+        self.out.push_str("} ");
+        self.comment(&call.comment().to_string());
+    }
+
+    fn nested_lines(&mut self, text: &str, depth: usize) {
+        let (lines, rest) = line_nodes(text);
+        self.lines(&lines, depth);
+        self.comments(&rest, depth);
+    }
+
+    /// Joins the tokens of a list of call operands, without spaces inside parentheses
+    /// or before commas.
+    fn call_operands(&mut self, text: &str) {
+        let start = self.out.len();
+        for token in text.split_whitespace() {
+            let glued = self.out.len() == start || self.out.ends_with('(') || token.starts_with([')', ',']);
+            if !glued {
+                self.out.push(' ');
+            }
+            self.out.push_str(token);
+        }
+    }
+
+    /// Writes each comment of `trivia` on a line of its own, returning whether there were any.
+    fn comments(&mut self, trivia: &[Trivia<'_>], depth: usize) -> bool {
+        for comment in comment_texts(trivia) {
+            self.indent(depth);
+            self.comment(comment);
+            self.out.push('\n');
+        }
+        has_comments(trivia)
+    }
+
+    /// Writes the comments of `trivia` after the current line.
+    fn trailing(&mut self, trivia: &[Trivia<'_>]) {
+        for comment in comment_texts(trivia) {
+            self.out.push(' ');
+            self.comment(comment);
+        }
+    }
+
+    fn end_line(&mut self, trivia: &[Trivia<'_>]) {
+        self.trailing(trivia);
+        self.out.push('\n');
+    }
+
+    /// Writes `// text` or `/* text */`, with exactly one space inside the delimiters.
+    fn comment(&mut self, comment: &str) {
+        let (open, text, close) = match comment.strip_prefix("//") {
+            Some(text) => ("//", text, ""),
+            None => ("/*", comment.trim_start_matches("/*").trim_end_matches("*/"), " */"),
+        };
+        let text = text.trim();
+        self.out.push_str(open);
+        if !text.is_empty() {
+            self.out.push(' ');
+            self.out.push_str(text);
+            self.out.push_str(close);
+        } else {
+            self.out.push_str(close.trim_start());
+        }
+    }
+
+    fn indent(&mut self, depth: usize) {
+        for _ in 0..depth {
+            let _ = self.printer.indent(&mut self.out);
+        }
+    }
+
+    fn node(&mut self, node: &impl Print) {
+        let _ = node.print(self.printer, &mut self.out);
+    }
+
+    /// Writes the text of a node through its typed form, or as it is if it fails to parse.
+    fn parsed<'a, T: Print>(&mut self, text: &'a str, parser: impl Fn(&'a str) -> IResult<&'a str, T>) {
+        match parser(text) {
+            Ok((_, node)) => self.node(&node),
+            Err(_) => self.out.push_str(text.trim()),
+        }
+    }
+}
+
+fn comment_texts<'t, 'a>(trivia: &'t [Trivia<'a>]) -> impl Iterator<Item = &'a str> + 't {
+    trivia.iter().filter_map(|trivia| match trivia {
+        Trivia::Comment(comment) => Some(*comment),
+        Trivia::Whitespace(_) => None,
+    })
+}
+
+fn has_comments(trivia: &[Trivia<'_>]) -> bool {
+    comment_texts(trivia).next().is_some()
+}
+
+#[cfg(test)]
+mod test_format {
+    use crate::parser::{
+        cst::SyntaxTree,
+        printer::{Indent, Printer},
+    };
+
+    fn format(printer: &Printer, input: &str) -> String {
+        printer.format(&SyntaxTree::try_from(input).unwrap())
+    }
+
+    #[test]
+    fn layout() {
+        let input = "//header\n.version 8.0\n.target sm_90\n.address_size 64\n.global .u32 x;\n.global .u32 y; //y\n\t// .globl\tk\n.visible .entry k(.param .u64 k_param_0)\n{\n.reg .b32 \t%r<3>;\n\n\tld.param.u64 \t%rd1, [k_param_0];   //load\n@%p1  st.global.u32 \t[%rd1], %r1;\n$L__BB0_1:\n\t/*done*/\n\tret;\n}\n";
+        let printer = Printer::new().with_indent(Indent::Spaces(4)).with_operand_column(14);
+        assert_eq!(
+            format(&printer, input),
+            "// header\n\n.version 8.0\n.target sm_90\n.address_size 64\n\n.global .u32 x;\n.global .u32 y; // y\n\n// .globl\tk\n.visible .entry k(\n    .param .u64 k_param_0\n)\n{\n    .reg .b32 %r<3>;\n    ld.param.u64  %rd1, [k_param_0]; // load\n    @%p1 st.global.u32 [%rd1], %r1;\n$L__BB0_1:\n    /* done */\n    ret;\n}\n"
+        );
+    }
+
+    #[test]
+    fn call_sequence() {
+        let input = ".version 8.0\n.target sm_90\n.address_size 64\n.visible .entry k(.param .u64 k_param_0)\n{\n\t{ // callseq 0, 0\n\t.param .b64 param0;\n\tst.param.b64 \t[param0+0], %rd1;\n\t.param .b64 retval0;\n\tcall.uni (retval0), \n\tf, \n\t(\n\tparam0\n\t);\n\tld.param.b64 \t%rd2, [retval0+0];\n\t} // callseq 0\n\tret;\n}\n";
+        assert_eq!(
+            format(&Printer::new(), input),
+            ".version 8.0\n.target sm_90\n.address_size 64\n\n.visible .entry k(\n\t.param .u64 k_param_0\n)\n{\n\t{ // callseq 0, 0\n\t\t.param .b64 param0;\n\t\tst.param.b64 [param0+0], %rd1;\n\t\t.param .b64 retval0;\n\t\tcall.uni (retval0), f, (param0);\n\t\tld.param.b64 %rd2, [retval0+0];\n\t} // callseq 0\n\tret;\n}\n"
+        );
+    }
+
+    #[test]
+    fn unparsed_text_is_kept() {
+        let input = ".version 8.0\n.target sm_90\n.address_size 64\n.weird  thing;\n";
+        assert_eq!(format(&Printer::new(), input), ".version 8.0\n.target sm_90\n.address_size 64\n\n.weird  thing;\n");
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg(test)]
+mod test_format_files {
+    use crate::{
+        parser::{
+            cst::SyntaxTree,
+            printer::{Equivalent, Indent, Printer},
+            PtxFile,
+        },
+        ptx_files::{self, _EXAMPLE_FILE},
+    };
+
+    #[test]
+    fn formatting_keeps_meaning_and_is_idempotent() {
+        for printer in [Printer::new(), Printer::new().with_indent(Indent::Spaces(4)).with_operand_column(16)] {
+            for input in ptx_files::ALL.into_iter().chain([_EXAMPLE_FILE]) {
+                let formatted = printer.format(&SyntaxTree::try_from(input).unwrap());
+                let original: PtxFile = input.try_into().unwrap();
+                let reparsed: PtxFile = formatted.as_str().try_into().unwrap();
+                assert!(reparsed.equivalent(&original));
+                let tree = SyntaxTree::try_from(formatted.as_str()).unwrap();
+                assert_eq!(tree.root().comments().count(), SyntaxTree::try_from(input).unwrap().root().comments().count());
+                assert_eq!(printer.format(&tree), formatted);
+            }
+        }
+    }
+}
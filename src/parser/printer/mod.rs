@@ -0,0 +1,623 @@
+use core::fmt::{self, Write};
+
+mod equivalent;
+mod format;
+
+pub use equivalent::Equivalent;
+
+use super::{
+    comment::Comment,
+    function::{
+        body::{Arguments, BodyLine, FunctionBody, FunctionCall, Goto, Operation, Predicate, Register},
+        Directives, Function, FunctionSignature, Parameters, ReturnValue,
+    },
+    global::Global,
+    instruction::Operand,
+    location::{File, Location},
+    preamble::{AddressSize, Preamble, Target, Version},
+    ptx_file::FunctionOrGlobal,
+    section::{DataValue, Section, SectionEntry},
+};
+#[cfg(feature = "std")]
+use super::{ptx_file::ItemKind, PtxFile};
+
+/// How the lines of a body are indented.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Indent {
+    Tab,
+    Spaces(usize),
+}
+
+/// Turns AST nodes back into PTX which parses to an equal AST.
+///
+/// Text the AST keeps as written, such as a `.reg` declaration or a parameter list, is
+/// written unchanged, so the printer only lays out the lines around it. The `Display`
+/// implementations of the AST use `Printer::default()`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Printer {
+    indent: Indent,
+    operand_column: Option<usize>,
+    /// Whether text kept as written is rewritten token by token, as `format` does.
+    tokens: bool,
+}
+
+impl Default for Printer {
+    /// Tab indentation, with the operands one space after the instruction name.
+    fn default() -> Self {
+        Printer { indent: Indent::Tab, operand_column: None, tokens: false }
+    }
+}
+
+impl Printer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_indent(mut self, indent: Indent) -> Self {
+        self.indent = indent;
+        self
+    }
+
+    /// Starts the operands of every instruction at `column`, counted from the start of
+    /// the instruction name, or one space after the name if it is longer.
+    pub fn with_operand_column(mut self, column: usize) -> Self {
+        self.operand_column = Some(column);
+        self
+    }
+
+    pub fn print(&self, node: &impl Print) -> String {
+        let mut out = String::new();
+        // Writing to a `String` cannot fail.
+        let _ = node.print(self, &mut out);
+        out
+    }
+
+    fn indent(&self, out: &mut dyn Write) -> fmt::Result {
+        match self.indent {
+            Indent::Tab => out.write_char('\t'),
+            Indent::Spaces(width) => write!(out, "{:width$}", ""),
+        }
+    }
+
+    /// Writes text the AST keeps as written.
+    fn text(&self, out: &mut dyn Write, text: &str) -> fmt::Result {
+        match self.tokens {
+            true => write_tokens(out, text),
+            false => out.write_str(text),
+        }
+    }
+
+    fn operands(&self, out: &mut dyn Write, name: &str) -> fmt::Result {
+        match self.operand_column {
+            Some(column) if column > name.len() => write!(out, "{:width$}", "", width = column - name.len()),
+            _ => out.write_char(' '),
+        }
+    }
+}
+
+/// An AST node which can be written as PTX.
+pub trait Print {
+    fn print(&self, printer: &Printer, out: &mut dyn Write) -> fmt::Result;
+}
+
+impl Print for Version<'_> {
+    fn print(&self, _: &Printer, out: &mut dyn Write) -> fmt::Result {
+        write!(out, ".version {}.{}", self.major(), self.minor())
+    }
+}
+
+impl Print for Target<'_> {
+    fn print(&self, _: &Printer, out: &mut dyn Write) -> fmt::Result {
+        write!(out, ".target {}", self.target())?;
+        self.options().try_for_each(|option| write!(out, ", {option}"))
+    }
+}
+
+impl Print for AddressSize<'_> {
+    fn print(&self, _: &Printer, out: &mut dyn Write) -> fmt::Result {
+        write!(out, ".address_size {}", self.size())
+    }
+}
+
+impl Print for Preamble<'_> {
+    fn print(&self, printer: &Printer, out: &mut dyn Write) -> fmt::Result {
+        self.version().print(printer, out)?;
+        out.write_char('\n')?;
+        self.target().print(printer, out)?;
+        out.write_char('\n')?;
+        self.address_size().print(printer, out)?;
+        out.write_char('\n')
+    }
+}
+
+impl Print for Global<'_> {
+    fn print(&self, _: &Printer, out: &mut dyn Write) -> fmt::Result {
+        write!(out, ".global {};", self.raw_string().trim())
+    }
+}
+
+impl Print for File<'_> {
+    fn print(&self, _: &Printer, out: &mut dyn Write) -> fmt::Result {
+        write!(out, ".file {} \"{}\"", self.index, self.name)
+    }
+}
+
+impl Print for Location {
+    fn print(&self, _: &Printer, out: &mut dyn Write) -> fmt::Result {
+        write!(out, ".loc {} {} {}", self.file, self.line, self.column)
+    }
+}
+
+impl Print for DataValue<'_> {
+    fn print(&self, _: &Printer, out: &mut dyn Write) -> fmt::Result {
+        match self {
+            DataValue::Integer(value) => write!(out, "{value}"),
+            DataValue::Symbol { symbol, addend: 0 } => out.write_str(symbol),
+            DataValue::Symbol { symbol, addend } if *addend < 0 => write!(out, "{symbol}-{}", addend.unsigned_abs()),
+            DataValue::Symbol { symbol, addend } => write!(out, "{symbol}+{addend}"),
+        }
+    }
+}
+
+impl Print for SectionEntry<'_> {
+    fn print(&self, printer: &Printer, out: &mut dyn Write) -> fmt::Result {
+        match self {
+            SectionEntry::Label(label) => write!(out, "{label}:"),
+            SectionEntry::Data { width, values } => {
+                write!(out, ".b{}", width * 8)?;
+                for (index, value) in values.iter().enumerate() {
+                    out.write_str(if index == 0 { " " } else { ", " })?;
+                    value.print(printer, out)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Print for Section<'_> {
+    fn print(&self, printer: &Printer, out: &mut dyn Write) -> fmt::Result {
+        writeln!(out, ".section {}", self.name)?;
+        out.write_str("{\n")?;
+        for entry in &self.entries {
+            printer.indent(out)?;
+            entry.print(printer, out)?;
+            out.write_char('\n')?;
+        }
+        out.write_char('}')
+    }
+}
+
+impl Print for Comment<'_> {
+    fn print(&self, _: &Printer, out: &mut dyn Write) -> fmt::Result {
+        match self {
+            Comment::Line(text) => write!(out, "//{text}"),
+            Comment::Block(text) => write!(out, "/*{text}*/"),
+        }
+    }
+}
+
+impl Print for Operand<'_> {
+    fn print(&self, _: &Printer, out: &mut dyn Write) -> fmt::Result {
+        match self {
+            Operand::Register(name) | Operand::Immediate(name) | Operand::Symbol(name) => out.write_str(name),
+            Operand::Address(address) => write!(out, "[{address}]"),
+            Operand::Pair(first, second) => write!(out, "{first}|{second}"),
+            Operand::Vector(elements) => {
+                out.write_char('{')?;
+                for (index, element) in elements.iter().enumerate() {
+                    if index > 0 {
+                        out.write_str(", ")?;
+                    }
+                    out.write_str(element)?;
+                }
+                out.write_char('}')
+            }
+        }
+    }
+}
+
+impl Print for Register<'_> {
+    fn print(&self, printer: &Printer, out: &mut dyn Write) -> fmt::Result {
+        out.write_str(".reg ")?;
+        printer.text(out, self.raw_string())
+    }
+}
+
+impl Print for Operation<'_> {
+    /// Declarations such as `.param .b64 param0` are not aligned like instructions.
+    fn print(&self, printer: &Printer, out: &mut dyn Write) -> fmt::Result {
+        out.write_str(self.operation())?;
+        if let Arguments::Operands(operands) = self.raw_arguments() {
+            if operands.is_empty() {
+                return Ok(());
+            }
+        }
+        if self.operation().starts_with('.') {
+            out.write_char(' ')?;
+        } else {
+            printer.operands(out, self.operation())?;
+        }
+        let operands = match self.raw_arguments() {
+            Arguments::Operands(operands) => operands,
+            Arguments::Text(text) => return printer.text(out, text),
+        };
+        for (index, operand) in operands.iter().enumerate() {
+            if index > 0 {
+                out.write_str(", ")?;
+            }
+            operand.print(printer, out)?;
+        }
+        Ok(())
+    }
+}
+
+impl Print for Predicate<'_> {
+    fn print(&self, _: &Printer, out: &mut dyn Write) -> fmt::Result {
+        match self {
+            Predicate::True(register) => write!(out, "@{register}"),
+            Predicate::False(register) => write!(out, "@!{register}"),
+        }
+    }
+}
+
+impl Print for Goto<'_> {
+    fn print(&self, printer: &Printer, out: &mut dyn Write) -> fmt::Result {
+        let name = match self.predicate() {
+            Some(predicate) => {
+                predicate.print(printer, out)?;
+                out.write_char(' ')?;
+                "bra"
+            }
+            None => "bra.uni",
+        };
+        out.write_str(name)?;
+        printer.operands(out, name)?;
+        write!(out, "${}", self.label())
+    }
+}
+
+impl Print for FunctionCall<'_> {
+    /// The call sequence is written as it was parsed, unless its lines were replaced
+    /// with `FunctionCall::set_lines`.
+    fn print(&self, printer: &Printer, out: &mut dyn Write) -> fmt::Result {
+        let Some((before, after)) = self.edited_lines() else {
+            write!(out, "{{{}call.uni {},{}}} ", self.setup(), self.function(), self.arguments())?;
+            return self.comment().print(printer, out);
+        };
+        let statement = self.arguments().split(';').next().unwrap_or_default();
+        out.write_char('{')?;
+        for line in before {
+            out.write_char('\n')?;
+            printer.indent(out)?;
+            printer.indent(out)?;
+            line.print(printer, out)?;
+        }
+        out.write_char('\n')?;
+        printer.indent(out)?;
+        printer.indent(out)?;
+        write!(out, "call.uni {},{statement};", self.function())?;
+        for line in after {
+            out.write_char('\n')?;
+            printer.indent(out)?;
+            printer.indent(out)?;
+            line.print(printer, out)?;
+        }
+        out.write_char('\n')?;
+        printer.indent(out)?;
+        out.write_str("} ")?;
+        self.comment().print(printer, out)
+    }
+}
+
+impl Print for BodyLine<'_> {
+    fn print(&self, printer: &Printer, out: &mut dyn Write) -> fmt::Result {
+        match self {
+            BodyLine::Register(register) => {
+                register.print(printer, out)?;
+                out.write_char(';')
+            }
+            BodyLine::Operation(operation) => {
+                operation.print(printer, out)?;
+                out.write_char(';')
+            }
+            BodyLine::Label(label) => write!(out, "${label}:"),
+            BodyLine::Location(location) => location.print(printer, out),
+            BodyLine::Goto(goto) => {
+                goto.print(printer, out)?;
+                out.write_char(';')
+            }
+            BodyLine::Return => out.write_str("ret;"),
+            BodyLine::FunctionCall(call) => call.print(printer, out),
+            BodyLine::Unknown(raw_string) => write!(out, "{raw_string};"),
+            BodyLine::Guarded(predicate, operation) => {
+                predicate.print(printer, out)?;
+                out.write_char(' ')?;
+                operation.print(printer, out)?;
+                out.write_char(';')
+            }
+            BodyLine::Comment(comment) => comment.print(printer, out),
+            BodyLine::OpenBrace => out.write_char('{'),
+            BodyLine::CloseBrace => out.write_char('}'),
+        }
+    }
+}
+
+impl Print for FunctionBody<'_> {
+    /// Labels are written at the start of their line and everything else is indented.
+    /// Text from the first line which fails to parse on is written unchanged.
+    fn print(&self, printer: &Printer, out: &mut dyn Write) -> fmt::Result {
+        let (lines, rest) = self.parsed();
+        out.write_str("{\n")?;
+        for line in lines {
+            if !matches!(line, BodyLine::Label(_)) {
+                printer.indent(out)?;
+            }
+            line.print(printer, out)?;
+            out.write_char('\n')?;
+        }
+        if let Some(rest) = rest {
+            printer.indent(out)?;
+            writeln!(out, "{rest}")?;
+        }
+        out.write_char('}')
+    }
+}
+
+impl Print for ReturnValue<'_> {
+    fn print(&self, printer: &Printer, out: &mut dyn Write) -> fmt::Result {
+        printer.text(out, self.raw_string())
+    }
+}
+
+impl Print for Parameters<'_> {
+    /// Rewritten token by token, with one declaration per line as `Parameters` expects
+    /// when reading them back.
+    fn print(&self, printer: &Printer, out: &mut dyn Write) -> fmt::Result {
+        if !printer.tokens {
+            return out.write_str(self.raw_string());
+        }
+        let declarations = self.raw_string().split(',').filter(|declaration| !declaration.trim().is_empty());
+        for (index, declaration) in declarations.enumerate() {
+            out.write_str(if index == 0 { "\n" } else { ",\n" })?;
+            printer.indent(out)?;
+            write_tokens(out, declaration)?;
+        }
+        out.write_char('\n')
+    }
+}
+
+impl Print for Directives<'_> {
+    /// Rewritten token by token, with one directive per line.
+    fn print(&self, printer: &Printer, out: &mut dyn Write) -> fmt::Result {
+        if !printer.tokens {
+            return out.write_str(self.raw_string());
+        }
+        let directives = self.raw_string().split('.').filter(|directive| !directive.trim().is_empty());
+        for (index, directive) in directives.enumerate() {
+            if index > 0 {
+                out.write_char('\n')?;
+            }
+            out.write_char('.')?;
+            write_tokens(out, directive)?;
+        }
+        Ok(())
+    }
+}
+
+impl Print for FunctionSignature<'_> {
+    fn print(&self, printer: &Printer, out: &mut dyn Write) -> fmt::Result {
+        out.write_str(if self.entry { ".visible .entry " } else { ".func " })?;
+        if let Some(return_value) = &self.return_value {
+            out.write_char('(')?;
+            return_value.print(printer, out)?;
+            out.write_str(") ")?;
+        }
+        out.write_str(self.name)?;
+        if let Some(parameters) = &self.parameters {
+            out.write_char('(')?;
+            parameters.print(printer, out)?;
+            out.write_char(')')?;
+        }
+        if let Some(directives) = &self.directives {
+            out.write_char('\n')?;
+            directives.print(printer, out)?;
+        }
+        Ok(())
+    }
+}
+
+impl Print for Function<'_> {
+    fn print(&self, printer: &Printer, out: &mut dyn Write) -> fmt::Result {
+        self.signature.print(printer, out)?;
+        match &self.body {
+            Some(body) => {
+                out.write_char('\n')?;
+                body.print(printer, out)
+            }
+            None => out.write_char(';'),
+        }
+    }
+}
+
+impl Print for FunctionOrGlobal<'_> {
+    fn print(&self, printer: &Printer, out: &mut dyn Write) -> fmt::Result {
+        match self {
+            FunctionOrGlobal::Function(function) => function.print(printer, out),
+            FunctionOrGlobal::Global(global) => global.print(printer, out),
+            FunctionOrGlobal::File(file) => file.print(printer, out),
+            FunctionOrGlobal::Section(section) => section.print(printer, out),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Print for PtxFile<'_> {
+    /// The preamble, then the items in the order of the source, since a declaration may
+    /// only refer to functions declared above it. Runs of globals or `.file` directives
+    /// are kept together and everything else is separated by a blank line.
+    fn print(&self, printer: &Printer, out: &mut dyn Write) -> fmt::Result {
+        self.preamble().print(printer, out)?;
+        let (mut functions, mut globals) = (self.functions().iter(), self.globals().iter());
+        let (mut files, mut sections) = (self.files().iter(), self.sections().iter());
+        let mut previous = None;
+        for &kind in self.items() {
+            let grouped = matches!(kind, ItemKind::Global | ItemKind::File);
+            if !(grouped && previous == Some(kind)) {
+                out.write_char('\n')?;
+            }
+            previous = Some(kind);
+            // `items` holds one kind per entry of the lists.
+            match kind {
+                ItemKind::Function => functions.next().map(|function| function.print(printer, out)),
+                ItemKind::Global => globals.next().map(|global| global.print(printer, out)),
+                ItemKind::File => files.next().map(|file| file.print(printer, out)),
+                ItemKind::Section => sections.next().map(|section| section.print(printer, out)),
+            }
+            .transpose()?;
+            out.write_char('\n')?;
+        }
+        Ok(())
+    }
+}
+
+fn write_tokens(out: &mut dyn Write, text: &str) -> fmt::Result {
+    for (index, token) in text.split_whitespace().enumerate() {
+        if index > 0 {
+            out.write_char(' ')?;
+        }
+        out.write_str(token)?;
+    }
+    Ok(())
+}
+
+macro_rules! display_with_printer {
+    ($($node:ty),* $(,)?) => {
+        $(
+            impl fmt::Display for $node {
+                fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                    self.print(&Printer::default(), f)
+                }
+            }
+        )*
+    };
+}
+
+display_with_printer!(
+    Version<'_>,
+    Target<'_>,
+    AddressSize<'_>,
+    Preamble<'_>,
+    Global<'_>,
+    File<'_>,
+    Location,
+    DataValue<'_>,
+    SectionEntry<'_>,
+    Section<'_>,
+    Comment<'_>,
+    Operand<'_>,
+    Register<'_>,
+    Operation<'_>,
+    Predicate<'_>,
+    Goto<'_>,
+    FunctionCall<'_>,
+    BodyLine<'_>,
+    FunctionBody<'_>,
+    ReturnValue<'_>,
+    Parameters<'_>,
+    Directives<'_>,
+    FunctionSignature<'_>,
+    Function<'_>,
+    FunctionOrGlobal<'_>,
+);
+
+#[cfg(feature = "std")]
+display_with_printer!(PtxFile<'_>);
+
+#[cfg(test)]
+mod test_printer {
+    use std::string::ToString;
+
+    use crate::parser::{
+        function::{body::Operation, parse::parse_function},
+        printer::{Indent, Printer},
+    };
+
+    #[test]
+    fn operation_alignment() {
+        let operation = Operation::new("ld.param.u64", "%rd1, [x]");
+        assert_eq!(operation.to_string(), "ld.param.u64 %rd1, [x]");
+        assert_eq!(Printer::new().with_operand_column(16).print(&operation), "ld.param.u64    %rd1, [x]");
+        assert_eq!(Printer::new().with_operand_column(4).print(&operation), "ld.param.u64 %rd1, [x]");
+        let declaration = Operation::new(".local", ".align 8 .b8 \t__local_depot0[16]");
+        assert_eq!(Printer::new().with_operand_column(16).print(&declaration), ".local .align 8 .b8 \t__local_depot0[16]");
+    }
+
+    #[test]
+    fn function() {
+        let input = ".visible .entry  k(\n\t.param .u64 k_param_0,\n\t.param .u32 k_param_1\n)\n.maxntid 128, 1,  1\n{\n.reg .b64 \t%rd<2>;\n\tld.param.u64 \t%rd1, [k_param_0];\n@%p1 bra \t$L__BB0_2;\n$L__BB0_2:\n\tret;\n}";
+        let (_, function) = parse_function(input).unwrap();
+        let printed = Printer::new().with_indent(Indent::Spaces(4)).print(&function);
+        assert_eq!(
+            printed,
+            ".visible .entry k(\n\t.param .u64 k_param_0,\n\t.param .u32 k_param_1\n)\n.maxntid 128, 1,  1\n{\n    .reg .b64 \t%rd<2>;\n    ld.param.u64 %rd1, [k_param_0];\n    @%p1 bra $L__BB0_2;\n$L__BB0_2:\n    ret;\n}"
+        );
+        assert_eq!(parse_function(&printed).unwrap().1, function);
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg(test)]
+mod test_round_trip {
+    use std::string::ToString;
+
+    use crate::{
+        parser::{
+            printer::{Indent, Printer},
+            PtxFile,
+        },
+        ptx_files::{self, b, _EXAMPLE_FILE},
+    };
+
+    fn files() -> impl Iterator<Item = &'static str> {
+        ptx_files::ALL.into_iter().chain([_EXAMPLE_FILE])
+    }
+
+    #[test]
+    fn parse_print_parse() {
+        for input in files() {
+            let file: PtxFile = input.try_into().unwrap();
+            let printed = file.to_string();
+            let reparsed: PtxFile = printed.as_str().try_into().unwrap();
+            assert_eq!(reparsed, file);
+            assert_eq!(reparsed.to_string(), printed);
+        }
+    }
+
+    #[test]
+    fn source_order() {
+        for input in files() {
+            let file: PtxFile = input.try_into().unwrap();
+            let printed = file.to_string();
+            let reparsed: PtxFile = printed.as_str().try_into().unwrap();
+            assert_eq!(reparsed.items(), file.items());
+        }
+        let printed = PtxFile::try_from(b::_PTX).unwrap().to_string();
+        let prototype = printed.find(".func _ZN4core3ptr88drop_in_place").unwrap();
+        let vtable = printed.find(".global .align 8 .u64 anon_$_af85108618407798382bf1e18eed69f7_$_262").unwrap();
+        let kernel = printed.find(".visible .entry run_length").unwrap();
+        assert!(prototype < vtable && vtable < kernel);
+    }
+
+    #[test]
+    fn configured_printer() {
+        let printer = Printer::new().with_indent(Indent::Spaces(2)).with_operand_column(20);
+        for input in files() {
+            let file: PtxFile = input.try_into().unwrap();
+            let printed = printer.print(&file);
+            let reparsed: PtxFile = printed.as_str().try_into().unwrap();
+            assert_eq!(reparsed, file);
+        }
+    }
+}
+
@@ -3,16 +3,36 @@ use nom::{branch::alt, combinator::opt, sequence::preceded, IResult, Parser};
 use super::{
     function::{Function, parse::parse_function},
     global::{Global, parse::parse_global},
+    location::{File, parse::parse_file},
+    section::{Section, parse::parse_section},
     PtxParser,
     comment::parse::many1_comments_or_whitespace,
 };
 
+#[cfg(feature = "std")]
+mod owned;
 mod try_from;
 
-#[derive(Debug)]
+#[cfg(feature = "std")]
+pub use owned::OwnedPtxFile;
+
+/// Which list of a `PtxFile` an item went to, kept in the order of the source.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ItemKind {
+    Function,
+    Global,
+    File,
+    Section,
+}
+
+#[derive(Debug, Clone)]
+#[non_exhaustive]
 pub enum FunctionOrGlobal<'a> {
     Function(Function<'a>),
     Global(Global<'a>),
+    File(File<'a>),
+    Section(Section<'a>),
 }
 
 impl<'a> Iterator for PtxParser<'a> {
@@ -27,6 +47,10 @@ impl<'a> Iterator for PtxParser<'a> {
                 .map(FunctionOrGlobal::Function),
                 parse_global
                 .map(FunctionOrGlobal::Global),
+                parse_file
+                .map(FunctionOrGlobal::File),
+                parse_section
+                .map(FunctionOrGlobal::Section),
         )))(body) {
             Ok((body, value)) => {
                 self.body = Some(body);
@@ -70,3 +94,49 @@ mod test_iterator {
         }
     }
 }
+
+#[cfg(feature = "std")]
+#[cfg(test)]
+mod test_ptx_file {
+    use crate::{
+        parser::{PtxFile, function::body::BodyLine, location::File},
+        ptx_files::{self, lineinfo, _EXAMPLE_FILE},
+    };
+
+    #[test]
+    fn parse_all_files() {
+        for input in ptx_files::ALL.into_iter().chain([_EXAMPLE_FILE]) {
+            let ptx: PtxFile = input.try_into().unwrap();
+            assert!(!ptx.functions().is_empty());
+        }
+    }
+
+    #[test]
+    fn file_table() {
+        let ptx: PtxFile = lineinfo::_PTX.try_into().unwrap();
+        assert_eq!(ptx.files(), &[File { index: 1, name: "/home/user/square.cu" }]);
+        assert_eq!(ptx.file(1).map(|file| file.name), Some("/home/user/square.cu"));
+        assert_eq!(ptx.file(2), None);
+    }
+
+    #[test]
+    fn instructions_at_line() {
+        let ptx: PtxFile = lineinfo::_PTX.try_into().unwrap();
+        let lines = ptx.instructions_at("/home/user/square.cu", 4);
+        assert_eq!(lines.len(), 2);
+        assert!(lines.iter().all(|(name, _)| *name == "_Z6squarePfS_i"));
+        assert!(matches!(lines[0].1, BodyLine::Operation(_)));
+        assert!(matches!(lines[1].1, BodyLine::Goto(_)));
+
+        let lines = ptx.instructions_at("/home/user/square.cu", 7);
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0].1, BodyLine::Return);
+
+        let lines = ptx.instructions_at("/home/user/square.cu", 5);
+        assert_eq!(lines.len(), 8, "the label ending the block is not an instruction");
+        assert!(lines.iter().all(|(_, line)| line.is_instruction()));
+
+        assert!(ptx.instructions_at("/home/user/square.cu", 1).is_empty());
+        assert!(ptx.instructions_at("other.cu", 4).is_empty());
+    }
+}
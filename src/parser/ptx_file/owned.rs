@@ -0,0 +1,166 @@
+use std::{string::String, sync::Arc};
+
+use crate::parser::{printer::Printer, PtxFile};
+
+/// A module together with the source it was parsed from, so it can outlive the buffer
+/// it was read from, be kept in a cache, or be sent to another thread.
+///
+/// The source is parsed once, when the `OwnedPtxFile` is made. Cloning is cheap, as the
+/// source is shared.
+#[derive(Debug, Clone)]
+pub struct OwnedPtxFile {
+    /// Borrows from `source`, so it is declared first to be dropped before it.
+    file: PtxFile<'static>,
+    source: Arc<str>,
+}
+
+impl OwnedPtxFile {
+    /// The parsed module, borrowing from `self`.
+    pub fn file(&self) -> &PtxFile<'_> {
+        &self.file
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+}
+
+impl PtxFile<'_> {
+    /// An owned copy of the module.
+    ///
+    /// A module which still equals what its source parses to keeps that source, with its
+    /// comments and layout. Any other module, such as one changed by a `VisitorMut`, holds
+    /// what `Printer::default()` writes for it, which fails to parse if the change left
+    /// text which does not parse.
+    pub fn into_owned(self) -> Result<OwnedPtxFile, nom::Err<nom::error::Error<String>>> {
+        let unchanged = self.source.filter(|source| PtxFile::try_from(*source).is_ok_and(|file| file == self));
+        match unchanged {
+            Some(source) => Arc::<str>::from(source).try_into(),
+            None => Printer::default().print(&self).try_into(),
+        }
+    }
+}
+
+impl TryFrom<Arc<str>> for OwnedPtxFile {
+    type Error = nom::Err<nom::error::Error<String>>;
+
+    fn try_from(source: Arc<str>) -> Result<Self, Self::Error> {
+        // SAFETY: the text of an `Arc<str>` does not move and lives as long as any clone
+        // of the `Arc`. `source` is kept with `file`, which is dropped before it, and `file`
+        // is only handed out with its lifetime shortened to a borrow of `self`.
+        let text: &'static str = unsafe { &*Arc::as_ptr(&source) };
+        let file = PtxFile::try_from(text).map_err(|err| err.to_owned())?;
+        Ok(OwnedPtxFile { file, source })
+    }
+}
+
+impl TryFrom<String> for OwnedPtxFile {
+    type Error = nom::Err<nom::error::Error<String>>;
+
+    fn try_from(source: String) -> Result<Self, Self::Error> {
+        Arc::<str>::from(source).try_into()
+    }
+}
+
+#[cfg(test)]
+mod test_owned_ptx_file {
+    use std::{ptr, string::ToString, thread};
+
+    use crate::{
+        parser::{ptx_file::OwnedPtxFile, PtxFile},
+        ptx_files::{a, kernel},
+        prelude::*,
+    };
+
+    fn assert_send_sync_clone<T: Send + Sync + Clone>() {}
+
+    #[test]
+    fn ast_types_are_send_sync_clone() {
+        assert_send_sync_clone::<PtxParser<'static>>();
+        assert_send_sync_clone::<PtxFile<'static>>();
+        assert_send_sync_clone::<OwnedPtxFile>();
+        assert_send_sync_clone::<FunctionOrGlobal<'static>>();
+        assert_send_sync_clone::<Function<'static>>();
+        assert_send_sync_clone::<FunctionBody<'static>>();
+        assert_send_sync_clone::<BodyLine<'static>>();
+        assert_send_sync_clone::<Global<'static>>();
+        assert_send_sync_clone::<Section<'static>>();
+        assert_send_sync_clone::<Operand<'static>>();
+        assert_send_sync_clone::<TensorInstruction<'static>>();
+    }
+
+    #[test]
+    fn outlives_source() {
+        let source = kernel::_PTX.to_string();
+        let owned = OwnedPtxFile::try_from(source).unwrap();
+        let expected: PtxFile = kernel::_PTX.try_into().unwrap();
+        let functions = thread::spawn(move || {
+            owned
+                .file()
+                .functions()
+                .iter()
+                .map(|function| function.signature.name.to_string())
+                .collect::<Vec<_>>()
+        })
+        .join()
+        .unwrap();
+        assert_eq!(
+            functions,
+            expected
+                .functions()
+                .iter()
+                .map(|function| function.signature.name)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn clones_share_source() {
+        let owned = OwnedPtxFile::try_from(a::_PTX.to_string()).unwrap();
+        let clone = owned.clone();
+        let count = owned.file().functions().len();
+        drop(owned);
+        assert_eq!(clone.file().functions().len(), count);
+        assert!(clone.file().functions().iter().all(|function| clone.source().contains(function.signature.name)));
+    }
+
+    #[test]
+    fn into_owned() {
+        let source = a::_PTX.to_string();
+        let owned = PtxFile::try_from(source.as_str()).unwrap().into_owned().unwrap();
+        drop(source);
+        let expected: PtxFile = a::_PTX.try_into().unwrap();
+        assert_eq!(owned.source(), a::_PTX);
+        assert_eq!(owned.file(), &expected);
+        assert!(ptr::eq(owned.file(), owned.file()));
+    }
+
+    /// Replaces every `ret` with `line`.
+    struct ReplaceReturn(BodyLine<'static>);
+
+    impl<'a> VisitorMut<'a> for ReplaceReturn {
+        fn visit_line_mut(&mut self, line: &mut BodyLine<'a>) {
+            if *line == BodyLine::Return {
+                *line = self.0.clone();
+            }
+        }
+    }
+
+    #[test]
+    fn edited_into_owned() {
+        let mut file: PtxFile = kernel::_PTX.try_into().unwrap();
+        ReplaceReturn(BodyLine::Operation(Operation::new("exit", ""))).visit_module_mut(&mut file);
+        let owned = file.clone().into_owned().unwrap();
+        assert_ne!(owned.source(), kernel::_PTX);
+        assert_eq!(owned.file(), &file);
+
+        let mut file: PtxFile = kernel::_PTX.try_into().unwrap();
+        ReplaceReturn(BodyLine::Unknown("}\n.bogus")).visit_module_mut(&mut file);
+        assert!(file.into_owned().is_err());
+    }
+
+    #[test]
+    fn owned_error() {
+        assert!(OwnedPtxFile::try_from("not ptx".to_string()).is_err());
+    }
+}
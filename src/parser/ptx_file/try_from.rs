@@ -1,8 +1,12 @@
 use nom::{combinator::opt, sequence::preceded};
 
-use crate::parser::{comment::parse::many1_comments_or_whitespace, preamble::parse::parse_preamble, PtxFile};
+use crate::parser::{comment::parse::many1_comments_or_whitespace, preamble::parse::parse_preamble};
+#[cfg(feature = "std")]
+use crate::parser::PtxFile;
 
 use super::PtxParser;
+#[cfg(feature = "std")]
+use super::{FunctionOrGlobal, ItemKind};
 
 impl<'a> TryFrom<&'a str> for PtxParser<'a> {
     type Error = nom::Err<nom::error::Error<&'a str>>;
@@ -16,31 +20,51 @@ impl<'a> TryFrom<&'a str> for PtxParser<'a> {
     }
 }
 
-// impl<'a> TryFrom<&'a str> for PtxFile<'a> {
-//     type Error = nom::Err<nom::error::Error<&'a str>>;
-
-//     fn try_from(value: &'a str) -> Result<Self, Self::Error> {
-//         let ptx: PtxParser = value.try_into()?;
-//         ptx.try_into()
-//     }
-// }
-
-// impl<'a> TryFrom<PtxParser<'a>> for PtxFile<'a> {
-//     type Error = nom::Err<nom::error::Error<&'a str>>;
-
-//     fn try_from(value: PtxParser<'a>) -> Result<Self, Self::Error> {
-//         let mut functions = Vec::new();
-//         let mut globals = Vec::new();
-//         for function_or_global in value {
-//             match function_or_global {
-//                 Ok((_, super::FunctionOrGlobal::Function(function))) => functions.push(function),
-//                 Ok((_, super::FunctionOrGlobal::Global(global))) => globals.push(global),
-//                 Err(err) => return Err(err),
-//             }
-//         }
-
-//         let preamble = value.preamble;
-
-//         Ok(PtxFile { preamble, functions, globals })
-//     }
-// }
\ No newline at end of file
+#[cfg(feature = "std")]
+impl<'a> TryFrom<&'a str> for PtxFile<'a> {
+    type Error = nom::Err<nom::error::Error<&'a str>>;
+
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        let ptx: PtxParser = value.try_into()?;
+        let file: PtxFile = ptx.try_into()?;
+        Ok(PtxFile { source: Some(value), ..file })
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'a> TryFrom<PtxParser<'a>> for PtxFile<'a> {
+    type Error = nom::Err<nom::error::Error<&'a str>>;
+
+    fn try_from(value: PtxParser<'a>) -> Result<Self, Self::Error> {
+        let preamble = value.preamble.clone();
+        let mut functions = Vec::new();
+        let mut globals = Vec::new();
+        let mut files = Vec::new();
+        let mut sections = Vec::new();
+        let mut items = Vec::new();
+        for function_or_global in value {
+            let kind = match function_or_global {
+                Ok((_, FunctionOrGlobal::Function(function))) => {
+                    functions.push(function);
+                    ItemKind::Function
+                }
+                Ok((_, FunctionOrGlobal::Global(global))) => {
+                    globals.push(global);
+                    ItemKind::Global
+                }
+                Ok((_, FunctionOrGlobal::File(file))) => {
+                    files.push(file);
+                    ItemKind::File
+                }
+                Ok((_, FunctionOrGlobal::Section(section))) => {
+                    sections.push(section);
+                    ItemKind::Section
+                }
+                Err(err) => return Err(err),
+            };
+            items.push(kind);
+        }
+
+        Ok(PtxFile { preamble, functions, globals, files, sections, items, source: None })
+    }
+}
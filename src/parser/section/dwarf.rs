@@ -0,0 +1,331 @@
+//! A minimal decoder for the `.debug_abbrev` and `.debug_info` sections emitted in debug builds.
+
+const DW_TAG_COMPILE_UNIT: u64 = 0x11;
+const DW_TAG_SUBPROGRAM: u64 = 0x2e;
+
+const DW_AT_NAME: u64 = 0x03;
+const DW_AT_PRODUCER: u64 = 0x25;
+const DW_AT_LINKAGE_NAME: u64 = 0x6e;
+const DW_AT_MIPS_LINKAGE_NAME: u64 = 0x2007;
+
+const DW_FORM_ADDR: u64 = 0x01;
+const DW_FORM_BLOCK2: u64 = 0x03;
+const DW_FORM_BLOCK4: u64 = 0x04;
+const DW_FORM_DATA2: u64 = 0x05;
+const DW_FORM_DATA4: u64 = 0x06;
+const DW_FORM_DATA8: u64 = 0x07;
+const DW_FORM_STRING: u64 = 0x08;
+const DW_FORM_BLOCK: u64 = 0x09;
+const DW_FORM_BLOCK1: u64 = 0x0a;
+const DW_FORM_DATA1: u64 = 0x0b;
+const DW_FORM_FLAG: u64 = 0x0c;
+const DW_FORM_SDATA: u64 = 0x0d;
+const DW_FORM_STRP: u64 = 0x0e;
+const DW_FORM_UDATA: u64 = 0x0f;
+const DW_FORM_REF_ADDR: u64 = 0x10;
+const DW_FORM_REF1: u64 = 0x11;
+const DW_FORM_REF2: u64 = 0x12;
+const DW_FORM_REF4: u64 = 0x13;
+const DW_FORM_REF8: u64 = 0x14;
+const DW_FORM_REF_UDATA: u64 = 0x15;
+const DW_FORM_SEC_OFFSET: u64 = 0x17;
+const DW_FORM_EXPRLOC: u64 = 0x18;
+const DW_FORM_FLAG_PRESENT: u64 = 0x19;
+const DW_FORM_IMPLICIT_CONST: u64 = 0x21;
+
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum DwarfError {
+    UnexpectedEof,
+    UnknownAbbreviation(u64),
+    UnsupportedForm(u64),
+    InvalidString,
+    /// A unit header with a length past the end of the section or an address size other
+    /// than 4 or 8.
+    InvalidHeader,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Abbreviation {
+    pub code: u64,
+    pub tag: u64,
+    pub has_children: bool,
+    /// `(attribute, form, implicit constant)` triples.
+    pub attributes: Vec<(u64, u64, Option<i64>)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+#[non_exhaustive]
+pub struct CompileUnit {
+    pub offset: usize,
+    pub name: Option<String>,
+    pub producer: Option<String>,
+    pub subprograms: Vec<Subprogram>,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+#[non_exhaustive]
+pub struct Subprogram {
+    pub offset: usize,
+    pub name: Option<String>,
+    pub linkage_name: Option<String>,
+}
+
+struct Reader<'b> {
+    bytes: &'b [u8],
+    offset: usize,
+}
+
+impl<'b> Reader<'b> {
+    fn new(bytes: &'b [u8], offset: usize) -> Self {
+        Reader { bytes, offset }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.offset >= self.bytes.len()
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'b [u8], DwarfError> {
+        let end = self.offset.checked_add(len).ok_or(DwarfError::UnexpectedEof)?;
+        let bytes = self.bytes.get(self.offset..end).ok_or(DwarfError::UnexpectedEof)?;
+        self.offset = end;
+        Ok(bytes)
+    }
+
+    /// Reads a little endian integer of `len` bytes, at most 8.
+    fn fixed(&mut self, len: usize) -> Result<u64, DwarfError> {
+        if len > 8 {
+            return Err(DwarfError::InvalidHeader);
+        }
+        let mut buffer = [0; 8];
+        buffer[..len].copy_from_slice(self.take(len)?);
+        Ok(u64::from_le_bytes(buffer))
+    }
+
+    fn uleb(&mut self) -> Result<u64, DwarfError> {
+        let mut value = 0;
+        let mut shift = 0;
+        loop {
+            let byte = self.take(1)?[0];
+            if shift < 64 {
+                value |= u64::from(byte & 0x7f) << shift;
+            }
+            shift += 7;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+    }
+
+    fn sleb(&mut self) -> Result<i64, DwarfError> {
+        let mut value = 0i64;
+        let mut shift = 0;
+        loop {
+            let byte = self.take(1)?[0];
+            if shift < 64 {
+                value |= i64::from(byte & 0x7f) << shift;
+            }
+            shift += 7;
+            if byte & 0x80 == 0 {
+                if shift < 64 && byte & 0x40 != 0 {
+                    value |= -1 << shift;
+                }
+                return Ok(value);
+            }
+        }
+    }
+
+    fn c_string(&mut self) -> Result<String, DwarfError> {
+        let rest = &self.bytes[self.offset.min(self.bytes.len())..];
+        let len = rest
+            .iter()
+            .position(|b| *b == 0)
+            .ok_or(DwarfError::UnexpectedEof)?;
+        let string = core::str::from_utf8(&rest[..len]).map_err(|_| DwarfError::InvalidString)?;
+        self.offset += len + 1;
+        Ok(string.into())
+    }
+}
+
+/// Decodes the abbreviation table at the start of `debug_abbrev`, up to the 0 code ending
+/// it. The tables of other units follow it in the section.
+pub fn abbreviations(debug_abbrev: &[u8]) -> Result<Vec<Abbreviation>, DwarfError> {
+    let mut reader = Reader::new(debug_abbrev, 0);
+    let mut abbreviations = Vec::new();
+    while !reader.is_empty() {
+        let code = reader.uleb()?;
+        if code == 0 {
+            break;
+        }
+        let tag = reader.uleb()?;
+        let has_children = reader.take(1)?[0] != 0;
+        let mut attributes = Vec::new();
+        loop {
+            let (attribute, form) = (reader.uleb()?, reader.uleb()?);
+            if (attribute, form) == (0, 0) {
+                break;
+            }
+            let constant = match form {
+                DW_FORM_IMPLICIT_CONST => Some(reader.sleb()?),
+                _ => None,
+            };
+            attributes.push((attribute, form, constant));
+        }
+        abbreviations.push(Abbreviation { code, tag, has_children, attributes });
+    }
+    Ok(abbreviations)
+}
+
+enum AttributeValue {
+    String(String),
+    StringOffset(u64),
+    Skipped,
+}
+
+struct UnitHeader {
+    end: usize,
+    offset_size: usize,
+    address_size: usize,
+    version: u64,
+}
+
+fn read_attribute(
+    reader: &mut Reader,
+    form: u64,
+    header: &UnitHeader,
+) -> Result<AttributeValue, DwarfError> {
+    Ok(match form {
+        DW_FORM_ADDR => reader.fixed(header.address_size).map(|_| AttributeValue::Skipped)?,
+        DW_FORM_DATA1 | DW_FORM_REF1 | DW_FORM_FLAG => reader.fixed(1).map(|_| AttributeValue::Skipped)?,
+        DW_FORM_DATA2 | DW_FORM_REF2 => reader.fixed(2).map(|_| AttributeValue::Skipped)?,
+        DW_FORM_DATA4 | DW_FORM_REF4 => reader.fixed(4).map(|_| AttributeValue::Skipped)?,
+        DW_FORM_DATA8 | DW_FORM_REF8 => reader.fixed(8).map(|_| AttributeValue::Skipped)?,
+        DW_FORM_UDATA | DW_FORM_REF_UDATA => reader.uleb().map(|_| AttributeValue::Skipped)?,
+        DW_FORM_SDATA => reader.sleb().map(|_| AttributeValue::Skipped)?,
+        DW_FORM_STRING => AttributeValue::String(reader.c_string()?),
+        DW_FORM_STRP => AttributeValue::StringOffset(reader.fixed(header.offset_size)?),
+        DW_FORM_SEC_OFFSET => reader.fixed(header.offset_size).map(|_| AttributeValue::Skipped)?,
+        DW_FORM_REF_ADDR => {
+            let size = if header.version <= 2 { header.address_size } else { header.offset_size };
+            reader.fixed(size).map(|_| AttributeValue::Skipped)?
+        }
+        DW_FORM_FLAG_PRESENT | DW_FORM_IMPLICIT_CONST => AttributeValue::Skipped,
+        DW_FORM_BLOCK1 => {
+            let len = reader.fixed(1)? as usize;
+            reader.take(len).map(|_| AttributeValue::Skipped)?
+        }
+        DW_FORM_BLOCK2 => {
+            let len = reader.fixed(2)? as usize;
+            reader.take(len).map(|_| AttributeValue::Skipped)?
+        }
+        DW_FORM_BLOCK4 => {
+            let len = reader.fixed(4)? as usize;
+            reader.take(len).map(|_| AttributeValue::Skipped)?
+        }
+        DW_FORM_BLOCK | DW_FORM_EXPRLOC => {
+            let len = reader.uleb()? as usize;
+            reader.take(len).map(|_| AttributeValue::Skipped)?
+        }
+        form => return Err(DwarfError::UnsupportedForm(form)),
+    })
+}
+
+fn read_unit_header(reader: &mut Reader) -> Result<(UnitHeader, u64), DwarfError> {
+    let (length, offset_size) = match reader.fixed(4)? {
+        0xffff_ffff => (reader.fixed(8)?, 8),
+        length => (length, 4),
+    };
+    let end = usize::try_from(length)
+        .ok()
+        .and_then(|length| reader.offset.checked_add(length))
+        .filter(|end| *end <= reader.bytes.len())
+        .ok_or(DwarfError::InvalidHeader)?;
+    let version = reader.fixed(2)?;
+    let (abbrev_offset, address_size) = if version >= 5 {
+        let _unit_type = reader.fixed(1)?;
+        let address_size = reader.fixed(1)?;
+        (reader.fixed(offset_size)?, address_size)
+    } else {
+        let abbrev_offset = reader.fixed(offset_size)?;
+        (abbrev_offset, reader.fixed(1)?)
+    };
+    if !matches!(address_size, 4 | 8) {
+        return Err(DwarfError::InvalidHeader);
+    }
+    let header = UnitHeader {
+        end,
+        offset_size,
+        address_size: address_size as usize,
+        version,
+    };
+    Ok((header, abbrev_offset))
+}
+
+/// Lists the compile units of `.debug_info` with the subprograms they contain.
+///
+/// `debug_str` is needed to resolve names stored with `DW_FORM_strp`.
+pub fn compile_units(
+    debug_abbrev: &[u8],
+    debug_info: &[u8],
+    debug_str: Option<&[u8]>,
+) -> Result<Vec<CompileUnit>, DwarfError> {
+    let mut reader = Reader::new(debug_info, 0);
+    let mut units = Vec::new();
+    while !reader.is_empty() {
+        let unit_offset = reader.offset;
+        let (header, abbrev_offset) = read_unit_header(&mut reader)?;
+        let abbreviations = abbreviations(
+            usize::try_from(abbrev_offset)
+                .ok()
+                .and_then(|offset| debug_abbrev.get(offset..))
+                .ok_or(DwarfError::UnexpectedEof)?,
+        )?;
+        let mut unit = CompileUnit { offset: unit_offset, ..Default::default() };
+        while reader.offset < header.end {
+            let die_offset = reader.offset;
+            let code = reader.uleb()?;
+            if code == 0 {
+                continue;
+            }
+            let abbreviation = abbreviations
+                .iter()
+                .find(|abbreviation| abbreviation.code == code)
+                .ok_or(DwarfError::UnknownAbbreviation(code))?;
+            let (mut name, mut producer, mut linkage_name) = (None, None, None);
+            for (attribute, form, _) in &abbreviation.attributes {
+                let value = match read_attribute(&mut reader, *form, &header)? {
+                    AttributeValue::String(string) => Some(string),
+                    AttributeValue::StringOffset(offset) => match debug_str {
+                        Some(debug_str) => {
+                            let offset = usize::try_from(offset).map_err(|_| DwarfError::UnexpectedEof)?;
+                            Some(Reader::new(debug_str, offset).c_string()?)
+                        }
+                        None => None,
+                    },
+                    AttributeValue::Skipped => None,
+                };
+                match *attribute {
+                    DW_AT_NAME => name = value,
+                    DW_AT_PRODUCER => producer = value,
+                    DW_AT_LINKAGE_NAME | DW_AT_MIPS_LINKAGE_NAME => linkage_name = value,
+                    _ => (),
+                }
+            }
+            match abbreviation.tag {
+                DW_TAG_COMPILE_UNIT => {
+                    unit.name = name;
+                    unit.producer = producer;
+                }
+                DW_TAG_SUBPROGRAM => unit.subprograms.push(Subprogram {
+                    offset: die_offset,
+                    name,
+                    linkage_name,
+                }),
+                _ => (),
+            }
+        }
+        reader.offset = header.end;
+        units.push(unit);
+    }
+    Ok(units)
+}
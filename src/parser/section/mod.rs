@@ -0,0 +1,344 @@
+pub(crate) mod parse;
+pub mod dwarf;
+
+/// A data section such as `.section .debug_info { .b8 1; ... }`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Section<'a> {
+    pub name: &'a str,
+    pub entries: Vec<SectionEntry<'a>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum SectionEntry<'a> {
+    Label(&'a str),
+    /// A `.b8`, `.b16`, `.b32` or `.b64` directive; `width` is in bytes.
+    Data {
+        width: usize,
+        values: Vec<DataValue<'a>>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DataValue<'a> {
+    Integer(i64),
+    Symbol { symbol: &'a str, addend: i64 },
+}
+
+/// The contents of a section laid out as little-endian bytes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SectionBytes<'a> {
+    pub name: &'a str,
+    pub bytes: Vec<u8>,
+    /// Symbol references which could not be resolved and were written as zero.
+    pub relocations: Vec<Relocation<'a>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Relocation<'a> {
+    pub offset: usize,
+    pub width: usize,
+    pub symbol: &'a str,
+    pub addend: i64,
+}
+
+impl<'a> Section<'a> {
+    /// The byte offset of every label, relative to the start of the section.
+    pub fn labels(&self) -> Vec<(&'a str, usize)> {
+        let mut offset = 0;
+        let mut labels = Vec::new();
+        for entry in &self.entries {
+            match entry {
+                SectionEntry::Label(label) => labels.push((*label, offset)),
+                SectionEntry::Data { width, values } => offset += width * values.len(),
+            }
+        }
+        labels
+    }
+
+    /// Lays out the section, asking `resolve` for the value of every symbol reference.
+    pub fn to_bytes(&self, resolve: impl Fn(&str) -> Option<u64>) -> SectionBytes<'a> {
+        let mut bytes = Vec::new();
+        let mut relocations = Vec::new();
+        for entry in &self.entries {
+            let SectionEntry::Data { width, values } = entry else {
+                continue;
+            };
+            for data_value in values {
+                let value = match data_value {
+                    DataValue::Integer(value) => *value as u64,
+                    DataValue::Symbol { symbol, addend } => match resolve(symbol) {
+                        Some(value) => value.wrapping_add(*addend as u64),
+                        None => {
+                            relocations.push(Relocation {
+                                offset: bytes.len(),
+                                width: *width,
+                                symbol,
+                                addend: *addend,
+                            });
+                            0
+                        }
+                    },
+                };
+                bytes.extend_from_slice(&value.to_le_bytes()[..*width]);
+            }
+        }
+        SectionBytes { name: self.name, bytes, relocations }
+    }
+}
+
+/// Lays out `sections`, resolving section names to zero and labels to their offset
+/// within the section declaring them, as DWARF references are section-relative.
+pub fn section_bytes<'a>(sections: &[Section<'a>]) -> Vec<SectionBytes<'a>> {
+    let symbols = sections
+        .iter()
+        .flat_map(|section| {
+            core::iter::once((section.name, 0))
+            .chain(section.labels())
+        })
+        .collect::<Vec<_>>();
+    let resolve = |symbol: &str| {
+        symbols
+        .iter()
+        .find(|(name, _)| *name == symbol)
+        .map(|(_, offset)| *offset as u64)
+    };
+    sections
+        .iter()
+        .map(|section| section.to_bytes(resolve))
+        .collect()
+}
+
+#[cfg(test)]
+mod test_parse_section {
+    use crate::parser::section::{parse::parse_section, DataValue, Section, SectionEntry};
+
+    #[test]
+    fn empty() {
+        assert_eq!(
+            parse_section(".section\t.debug_loc\t{\t}"),
+            Ok(("", Section { name: ".debug_loc", entries: vec![] }))
+        );
+    }
+
+    #[test]
+    fn data_and_comments() {
+        let input = ".section\t.debug_info
+\t{
+.b32 60                                 // Length of Unit
+.b8 2                                   // DWARF version number
+.b32 .debug_abbrev                      // Offset Into Abbrev. Section
+.b8 110,118,0
+.b64 $L__func_begin0+4
+\t}";
+        assert_eq!(
+            parse_section(input),
+            Ok((
+                "",
+                Section {
+                    name: ".debug_info",
+                    entries: vec![
+                        SectionEntry::Data { width: 4, values: vec![DataValue::Integer(60)] },
+                        SectionEntry::Data { width: 1, values: vec![DataValue::Integer(2)] },
+                        SectionEntry::Data {
+                            width: 4,
+                            values: vec![DataValue::Symbol { symbol: ".debug_abbrev", addend: 0 }],
+                        },
+                        SectionEntry::Data {
+                            width: 1,
+                            values: vec![
+                                DataValue::Integer(110),
+                                DataValue::Integer(118),
+                                DataValue::Integer(0),
+                            ],
+                        },
+                        SectionEntry::Data {
+                            width: 8,
+                            values: vec![DataValue::Symbol { symbol: "$L__func_begin0", addend: 4 }],
+                        },
+                    ],
+                }
+            ))
+        );
+    }
+
+    #[test]
+    fn labels_and_semicolons() {
+        let input = ".section .debug_str { $L__info_string0: .b8 97, 0; .b16 -1; }";
+        assert_eq!(
+            parse_section(input),
+            Ok((
+                "",
+                Section {
+                    name: ".debug_str",
+                    entries: vec![
+                        SectionEntry::Label("$L__info_string0"),
+                        SectionEntry::Data {
+                            width: 1,
+                            values: vec![DataValue::Integer(97), DataValue::Integer(0)],
+                        },
+                        SectionEntry::Data { width: 2, values: vec![DataValue::Integer(-1)] },
+                    ],
+                }
+            ))
+        );
+    }
+
+    #[test]
+    fn unknown_directive() {
+        assert!(parse_section(".section .debug_info { .f32 1.0 }").is_err());
+    }
+}
+
+#[cfg(test)]
+mod test_section_bytes {
+    use crate::parser::section::{parse::parse_section, section_bytes, Relocation};
+
+    #[test]
+    fn resolves_labels_and_sections() {
+        let (_, abbrev) = parse_section(".section .debug_abbrev { .b8 1, 0 }").unwrap();
+        let (_, info) = parse_section(
+            ".section .debug_info { .b32 .debug_abbrev\n.b16 $L__str1+1\n.b64 $L__func_begin0 }",
+        )
+        .unwrap();
+        let (_, strings) =
+            parse_section(".section .debug_str { $L__str0: .b8 0,0 $L__str1: .b8 1,0 }").unwrap();
+        let sections = [abbrev, info, strings];
+        let bytes = section_bytes(&sections);
+        assert_eq!(bytes[0].bytes, vec![1, 0]);
+        assert_eq!(bytes[1].bytes, vec![0, 0, 0, 0, 3, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(
+            bytes[1].relocations,
+            vec![Relocation { offset: 6, width: 8, symbol: "$L__func_begin0", addend: 0 }]
+        );
+        assert_eq!(sections[2].labels(), vec![("$L__str0", 0), ("$L__str1", 2)]);
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg(test)]
+mod test_dwarf {
+    use crate::{
+        parser::{
+            PtxFile,
+            section::dwarf::{abbreviations, Abbreviation, CompileUnit, Subprogram},
+        },
+        ptx_files::debug,
+    };
+
+    #[test]
+    fn debug_sections() {
+        let ptx: PtxFile = debug::_PTX.try_into().unwrap();
+        let names = ptx.sections().iter().map(|section| section.name).collect::<Vec<_>>();
+        assert_eq!(names, vec![".debug_abbrev", ".debug_info", ".debug_str", ".debug_loc"]);
+        let bytes = ptx.section_bytes();
+        assert_eq!(bytes[1].bytes.len(), 64);
+        assert_eq!(bytes[1].relocations.len(), 2);
+        assert!(bytes[1].relocations.iter().all(|r| r.symbol == "$L__func_begin0"));
+    }
+
+    #[test]
+    fn decode_abbreviations() {
+        let ptx: PtxFile = debug::_PTX.try_into().unwrap();
+        let bytes = ptx.section_bytes();
+        assert_eq!(
+            abbreviations(&bytes[0].bytes),
+            Ok(vec![
+                Abbreviation {
+                    code: 1,
+                    tag: 0x11,
+                    has_children: true,
+                    attributes: vec![(0x25, 0x08, None), (0x03, 0x08, None), (0x11, 0x01, None)],
+                },
+                Abbreviation {
+                    code: 2,
+                    tag: 0x2e,
+                    has_children: false,
+                    attributes: vec![(0x03, 0x0e, None), (0x6e, 0x08, None), (0x11, 0x01, None)],
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn decode_compile_units() {
+        let ptx: PtxFile = debug::_PTX.try_into().unwrap();
+        assert_eq!(
+            ptx.compile_units(),
+            Ok(vec![CompileUnit {
+                offset: 0,
+                name: Some("square.cu".into()),
+                producer: Some("nvcc".into()),
+                subprograms: vec![Subprogram {
+                    offset: 0x23,
+                    name: Some("square".into()),
+                    linkage_name: Some("_Z6squarePfS_i".into()),
+                }],
+            }])
+        );
+    }
+
+    #[test]
+    fn no_debug_sections() {
+        let ptx: PtxFile = crate::ptx_files::kernel::_PTX.try_into().unwrap();
+        assert_eq!(ptx.compile_units(), Ok(vec![]));
+    }
+}
+
+#[cfg(test)]
+mod test_malformed_dwarf {
+    use crate::parser::section::dwarf::{abbreviations, compile_units, DwarfError};
+
+    /// One abbreviation, a compile unit with a `DW_AT_name` of `DW_FORM_block`.
+    const ABBREV: [u8; 7] = [1, 0x11, 0, 0x03, 0x09, 0, 0];
+
+    /// A DWARF 4 unit header with a 32 bit `unit_length` covering `rest`.
+    fn unit(address_size: u8, rest: &[u8]) -> Vec<u8> {
+        let mut unit = ((7 + rest.len()) as u32).to_le_bytes().to_vec();
+        unit.extend([4, 0, 0, 0, 0, 0, address_size]);
+        unit.extend(rest);
+        unit
+    }
+
+    #[test]
+    fn unit_length_overflow() {
+        let mut info = vec![0xff; 4];
+        info.extend(u64::MAX.to_le_bytes());
+        assert_eq!(compile_units(&ABBREV, &info, None), Err(DwarfError::InvalidHeader));
+    }
+
+    #[test]
+    fn block_length_overflow() {
+        let info = unit(8, &[1, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01]);
+        assert_eq!(compile_units(&ABBREV, &info, None), Err(DwarfError::UnexpectedEof));
+    }
+
+    #[test]
+    fn address_size() {
+        assert_eq!(compile_units(&ABBREV, &unit(16, &[]), None), Err(DwarfError::InvalidHeader));
+        assert_eq!(compile_units(&ABBREV, &unit(2, &[]), None), Err(DwarfError::InvalidHeader));
+        assert_eq!(compile_units(&ABBREV, &unit(8, &[]), None).map(|units| units.len()), Ok(1));
+    }
+
+    #[test]
+    fn truncated_unit() {
+        let mut info = unit(8, &[1, 3, b'a']);
+        info.truncate(info.len() - 1);
+        assert_eq!(compile_units(&ABBREV, &info, None), Err(DwarfError::InvalidHeader));
+    }
+
+    #[test]
+    fn abbreviation_tables() {
+        let mut debug_abbrev = ABBREV.to_vec();
+        debug_abbrev.push(0);
+        debug_abbrev.extend([1, 0x2e, 0, 0x03, 0x08, 0, 0, 0]);
+        assert_eq!(abbreviations(&debug_abbrev).map(|table| table.len()), Ok(1));
+        let mut info = unit(8, &[1, 1, b'a']);
+        let second = info.len();
+        info.extend(unit(8, &[1, b'b', 0]));
+        info[second + 6..second + 10].copy_from_slice(&8u32.to_le_bytes());
+        let units = compile_units(&debug_abbrev, &info, None).unwrap();
+        assert_eq!(units[0].name, None);
+        assert_eq!(units[1].subprograms[0].name.as_deref(), Some("b"));
+    }
+}
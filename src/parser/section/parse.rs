@@ -0,0 +1,86 @@
+use nom::{
+    branch::alt,
+    bytes::complete::{tag, take_while1},
+    character::complete::{char, i64, multispace0, space0, space1},
+    combinator::{eof, opt, value},
+    multi::fold_many0,
+    sequence::{delimited, preceded, terminated, tuple, Tuple},
+    IResult, Parser,
+};
+
+use crate::parser::{comment::parse::many1_comments_or_whitespace, parse_braced_balanced};
+
+use super::{DataValue, Section, SectionEntry};
+
+pub(crate) fn parse_section(input: &str) -> IResult<&str, Section<'_>> {
+    let (input, (name, body)) = (
+        preceded(
+            tag(".section").and(space1),
+            take_while1(|c: char| !c.is_whitespace() && c != '{'),
+        ),
+        preceded(opt(many1_comments_or_whitespace), parse_braced_balanced),
+    )
+    .parse(input)?;
+    let (_, entries) = terminated(
+        fold_many0(
+            preceded(opt(many1_comments_or_whitespace), parse_section_entry),
+            Vec::new,
+            |mut entries, entry| {
+                entries.push(entry);
+                entries
+            },
+        ),
+        opt(many1_comments_or_whitespace).and(eof),
+    )(body)?;
+    Ok((input, Section { name, entries }))
+}
+
+fn parse_section_entry(input: &str) -> IResult<&str, SectionEntry<'_>> {
+    alt((
+        terminated(parse_symbol, char(':')).map(SectionEntry::Label),
+        tuple((
+            alt((
+                value(1, tag(".b8")),
+                value(2, tag(".b16")),
+                value(4, tag(".b32")),
+                value(8, tag(".b64")),
+            )),
+            preceded(
+                space1,
+                parse_data_value.and(fold_many0(
+                    preceded(delimited(space0, char(','), multispace0), parse_data_value),
+                    Vec::new,
+                    |mut values, value| {
+                        values.push(value);
+                        values
+                    },
+                )),
+            ),
+            opt(preceded(space0, char(';'))),
+        ))
+        .map(|(width, (first, mut values), _)| {
+            values.insert(0, first);
+            SectionEntry::Data { width, values }
+        }),
+    ))(input)
+}
+
+fn parse_data_value(input: &str) -> IResult<&str, DataValue<'_>> {
+    alt((
+        i64.map(DataValue::Integer),
+        parse_symbol
+        .and(opt(preceded(space0, alt((char('+'), char('-')))).and(preceded(space0, i64))))
+        .map(|(symbol, addend)| DataValue::Symbol {
+            symbol,
+            addend: match addend {
+                Some(('-', addend)) => -addend,
+                Some((_, addend)) => addend,
+                None => 0,
+            },
+        }),
+    ))(input)
+}
+
+fn parse_symbol(input: &str) -> IResult<&str, &str> {
+    take_while1(|c: char| c.is_alphanumeric() || ['.', '$', '_'].contains(&c))(input)
+}
@@ -0,0 +1,748 @@
+//! Name resolution: which declaration each register, label, parameter, variable and
+//! function name in a module refers to.
+//!
+//! ```
+//! use ptx_parser::prelude::*;
+//!
+//! let ptx: PtxFile = "
+//! .version 7.5
+//! .target sm_80
+//! .address_size 64
+//!
+//! .visible .entry kernel(
+//!     .param .u64 kernel_param_0
+//! )
+//! {
+//!     .reg .b64 %rd<2>;
+//!     ld.param.u64 %rd1, [kernel_param_0];
+//!     st.global.u64 [%rd1], %rd2;
+//!     ret;
+//! }
+//! "
+//! .try_into()
+//! .unwrap();
+//!
+//! let symbols = SymbolTable::resolve(&ptx);
+//! let undefined = symbols.diagnostics().iter().map(ToString::to_string).collect::<Vec<_>>();
+//! assert_eq!(undefined, ["error[E0001]: undefined register `%rd2` in `kernel`"]);
+//! ```
+
+use core::ops::Range;
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    format,
+    string::String,
+    vec::Vec,
+};
+
+use crate::parser::{
+    diagnostic::{Code, Diagnostic},
+    is_special_register,
+    function::{
+        body::{BodyLine, FunctionCall, Operation},
+        Function,
+    },
+    instruction::{
+        types::{ScalarType, StateSpace},
+        Operand,
+    },
+    PtxFile,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SymbolKind {
+    /// A `.func` or `.entry`.
+    Function,
+    /// A module-scope variable, such as `.global` data or a `.texref`.
+    Global,
+    /// A kernel or function parameter, a return value, or a `.param` of a call sequence.
+    Parameter,
+    /// A `.reg` declaration.
+    Register,
+    /// A `.local`, `.shared` or `.const` variable declared in a function body.
+    Variable,
+    Label,
+}
+
+impl SymbolKind {
+    fn describe(&self) -> &'static str {
+        match self {
+            SymbolKind::Function => "function",
+            SymbolKind::Global => "global",
+            SymbolKind::Parameter => "parameter",
+            SymbolKind::Register => "register",
+            SymbolKind::Variable => "variable",
+            SymbolKind::Label => "label",
+        }
+    }
+}
+
+/// Where a name is visible. A name is looked up in the innermost scope first, then in
+/// the scopes around it: a call sequence, its function, then the module.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Scope {
+    Module,
+    /// The body of the function at this index of `PtxFile::functions`.
+    Function(usize),
+    /// The `call`th call sequence in the body of a function, with its own `.param`s.
+    Call { function: usize, call: usize },
+}
+
+impl Scope {
+    /// The scope around this one.
+    pub fn parent(&self) -> Option<Scope> {
+        match *self {
+            Scope::Module => None,
+            Scope::Function(_) => Some(Scope::Module),
+            Scope::Call { function, .. } => Some(Scope::Function(function)),
+        }
+    }
+
+    /// The index in `PtxFile::functions` of the function the scope belongs to.
+    pub fn function(&self) -> Option<usize> {
+        match *self {
+            Scope::Module => None,
+            Scope::Function(function) | Scope::Call { function, .. } => Some(function),
+        }
+    }
+}
+
+/// A declared name.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Symbol<'a> {
+    name: &'a str,
+    kind: SymbolKind,
+    scope: Scope,
+    ty: Option<ScalarType>,
+    space: Option<StateSpace>,
+    count: Option<u32>,
+    function: Option<usize>,
+}
+
+impl<'a> Symbol<'a> {
+    /// The declared name, without the `$` of a label. For a parameterized register
+    /// declaration such as `%r<5>` this is the prefix `%r`.
+    pub fn name(&self) -> &'a str {
+        self.name
+    }
+
+    pub fn kind(&self) -> SymbolKind {
+        self.kind
+    }
+
+    pub fn scope(&self) -> Scope {
+        self.scope
+    }
+
+    /// The declared type, e.g. `.b32` for `.reg .b32 %r<5>` or `.b8` for an array of bytes.
+    pub fn ty(&self) -> Option<ScalarType> {
+        self.ty
+    }
+
+    /// The state space of a register, parameter or variable, e.g. `.local` for
+    /// `.local .align 8 .b8 __local_depot0[16]`.
+    pub fn space(&self) -> Option<StateSpace> {
+        self.space
+    }
+
+    /// The `N` of a parameterized register declaration `%r<N>`.
+    pub fn count(&self) -> Option<u32> {
+        self.count
+    }
+
+    /// For a function, its index in `PtxFile::functions`: the definition if there is one,
+    /// otherwise the first declaration.
+    pub fn function(&self) -> Option<usize> {
+        self.function
+    }
+
+    /// The byte range of the declared name within `source`, the text the module was parsed from.
+    pub fn span_in(&self, source: &str) -> Option<Range<usize>> {
+        crate::parser::span_in(self.name, source)
+    }
+
+    /// Whether `name` is declared by this symbol, e.g. `%r3` by `%r<5>`.
+    pub fn declares(&self, name: &str) -> bool {
+        match self.count {
+            None => self.name == name,
+            Some(count) => register_index(self.name, name).is_some_and(|index| index < count),
+        }
+    }
+}
+
+/// The index of `name` in the range of registers starting with `prefix`, e.g. 12 for
+/// `%r12` in `%r`, but none for `%rd12`.
+fn register_index(prefix: &str, name: &str) -> Option<u32> {
+    let index = name.strip_prefix(prefix)?;
+    if !index.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    index.parse().ok()
+}
+
+/// Identifies a symbol of a `SymbolTable`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SymbolId(usize);
+
+/// A use of a name, annotated with the declaration it resolves to.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Reference<'a> {
+    name: &'a str,
+    kind: SymbolKind,
+    scope: Scope,
+    definition: Option<SymbolId>,
+}
+
+impl<'a> Reference<'a> {
+    /// The name as used, without the `$` of a label.
+    pub fn name(&self) -> &'a str {
+        self.name
+    }
+
+    /// The kind of symbol the use expects: `Function` for the callee of a `call`, and
+    /// `Variable` for a function whose address an instruction takes.
+    pub fn kind(&self) -> SymbolKind {
+        self.kind
+    }
+
+    /// The scope the name is used in.
+    pub fn scope(&self) -> Scope {
+        self.scope
+    }
+
+    /// The declaration of the name, or `None` if it is undefined.
+    pub fn definition(&self) -> Option<SymbolId> {
+        self.definition
+    }
+
+    /// The byte range of the name within `source`, the text the module was parsed from.
+    pub fn span_in(&self, source: &str) -> Option<Range<usize>> {
+        crate::parser::span_in(self.name, source)
+    }
+}
+
+/// The declarations of a module and every reference to them, with a diagnostic for each
+/// undefined or duplicate name.
+#[derive(Debug, Clone, Default)]
+pub struct SymbolTable<'a> {
+    symbols: Vec<Symbol<'a>>,
+    references: Vec<Reference<'a>>,
+    diagnostics: Vec<Diagnostic<'a>>,
+    names: HashMap<(Scope, &'a str), SymbolId>,
+}
+
+impl<'a> SymbolTable<'a> {
+    /// Collects the declarations of `file`, then resolves every name used in its functions.
+    pub fn resolve(file: &PtxFile<'a>) -> Self {
+        let bodies = file
+            .functions
+            .iter()
+            .map(|function| function.body.as_ref().map_or_else(Vec::new, |body| body.parsed().0))
+            .collect();
+        let mut resolver = Resolver { file, bodies, table: SymbolTable::default() };
+        resolver.define();
+        resolver.resolve();
+        resolver.table
+    }
+
+    pub fn symbols(&self) -> &[Symbol<'a>] {
+        &self.symbols
+    }
+
+    pub fn symbol(&self, id: SymbolId) -> &Symbol<'a> {
+        &self.symbols[id.0]
+    }
+
+    /// Every use of a name, function by function.
+    pub fn references(&self) -> &[Reference<'a>] {
+        &self.references
+    }
+
+    /// The declaration `reference` resolves to.
+    pub fn definition(&self, reference: &Reference<'a>) -> Option<&Symbol<'a>> {
+        reference.definition.map(|id| self.symbol(id))
+    }
+
+    /// The uses of the symbol `id`.
+    pub fn references_to(&self, id: SymbolId) -> impl Iterator<Item = &Reference<'a>> {
+        self.references
+            .iter()
+            .filter(move |reference| reference.definition == Some(id))
+    }
+
+    /// Looks `name` up in `scope`, then in the scopes around it. Labels are looked up
+    /// without their `$`.
+    pub fn lookup(&self, scope: Scope, name: &str) -> Option<SymbolId> {
+        let mut scope = Some(scope);
+        while let Some(current) = scope {
+            if let Some(&id) = self.names.get(&(current, name)) {
+                return Some(id);
+            }
+            if let Some(id) = self.lookup_register(current, name) {
+                return Some(id);
+            }
+            scope = current.parent();
+        }
+        None
+    }
+
+    /// Finds `%r12` among the parameterized declarations of `scope`, such as `%r<13>`.
+    fn lookup_register(&self, scope: Scope, name: &str) -> Option<SymbolId> {
+        let prefix = name.trim_end_matches(|c: char| c.is_ascii_digit());
+        if !prefix.starts_with('%') || prefix.len() == name.len() {
+            return None;
+        }
+        let &id = self.names.get(&(scope, prefix))?;
+        self.symbol(id).declares(name).then_some(id)
+    }
+
+    /// The undefined and duplicate names of the module.
+    pub fn diagnostics(&self) -> &[Diagnostic<'a>] {
+        &self.diagnostics
+    }
+}
+
+/// Builds a `SymbolTable`: every declaration first, as labels and registers may be
+/// used before they are declared, then every use.
+struct Resolver<'f, 'a> {
+    file: &'f PtxFile<'a>,
+    bodies: Vec<Vec<BodyLine<'a>>>,
+    table: SymbolTable<'a>,
+}
+
+impl<'a> Resolver<'_, 'a> {
+    fn define(&mut self) {
+        let file = self.file;
+        for global in &file.globals {
+            for mut declaration in declarations(global.raw_string()) {
+                declaration.space = Some(StateSpace::Global);
+                self.define_symbol(declaration, SymbolKind::Global, Scope::Module);
+            }
+        }
+        for (index, function) in file.functions.iter().enumerate() {
+            self.define_function(index, function);
+        }
+        for (index, function) in file.functions.iter().enumerate() {
+            let scope = Scope::Function(index);
+            let return_value = function.signature.return_value.as_ref().map(|value| value.raw_string());
+            let parameters = function.signature.parameters.as_ref().map(|parameters| parameters.raw_string());
+            for declaration in return_value.into_iter().chain(parameters).flat_map(declarations) {
+                self.define_symbol(declaration, SymbolKind::Parameter, scope);
+            }
+            let lines = core::mem::take(&mut self.bodies[index]);
+            self.define_lines(&lines, scope);
+            self.bodies[index] = lines;
+        }
+    }
+
+    /// Defines a function. Declarations without a body may precede the definition.
+    fn define_function(&mut self, index: usize, function: &Function<'a>) {
+        let name = function.signature.name;
+        if let Some(&id) = self.table.names.get(&(Scope::Module, name)) {
+            let symbol = &mut self.table.symbols[id.0];
+            let previous = symbol.function.map(|previous| &self.file.functions[previous]);
+            match previous {
+                Some(_) if function.body.is_none() => return,
+                Some(previous) if previous.body.is_none() => {
+                    symbol.function = Some(index);
+                    return;
+                }
+                _ => (),
+            }
+        }
+        self.define_symbol(Declaration::named(name), SymbolKind::Function, Scope::Module);
+        if let Some(symbol) = self.table.symbols.last_mut() {
+            symbol.function = Some(index);
+        }
+    }
+
+    fn define_lines(&mut self, lines: &[BodyLine<'a>], scope: Scope) {
+        let mut calls = 0;
+        for line in lines {
+            match line {
+                BodyLine::Register(register) => {
+                    for mut declaration in declarations(register.raw_string()) {
+                        declaration.space = Some(StateSpace::Reg);
+                        self.define_symbol(declaration, SymbolKind::Register, scope);
+                    }
+                }
+                BodyLine::Label(label) => self.define_symbol(Declaration::named(label), SymbolKind::Label, scope),
+                BodyLine::Operation(operation) => {
+                    if let Some(kind) = declaration_kind(operation) {
+                        let space = operation.operation().strip_prefix('.').and_then(StateSpace::from_modifier);
+                        for mut declaration in declarations(operation.arguments().unwrap_or_default()) {
+                            declaration.space = space;
+                            self.define_symbol(declaration, kind, scope);
+                        }
+                    }
+                }
+                BodyLine::FunctionCall(call) => {
+                    let function = scope.function().unwrap_or_default();
+                    self.define_lines(&call_lines(call), Scope::Call { function, call: calls });
+                    calls += 1;
+                }
+                _ => (),
+            }
+        }
+    }
+
+    fn define_symbol(&mut self, declaration: Declaration<'a>, kind: SymbolKind, scope: Scope) {
+        let Declaration { name, ty, space, count } = declaration;
+        let id = SymbolId(self.table.symbols.len());
+        self.table.symbols.push(Symbol { name, kind, scope, ty, space, count, function: None });
+        let duplicate = match self.table.names.entry((scope, name)) {
+            Entry::Occupied(_) => true,
+            Entry::Vacant(entry) => {
+                entry.insert(id);
+                false
+            }
+        };
+        if duplicate {
+            let message = format!("duplicate {} `{}`{}", kind.describe(), display_name(kind, name), self.place(scope));
+            self.table.diagnostics.push(Diagnostic::new(Code::DuplicateSymbol, message, name));
+        }
+    }
+
+    fn resolve(&mut self) {
+        for index in 0..self.bodies.len() {
+            let lines = core::mem::take(&mut self.bodies[index]);
+            self.resolve_lines(&lines, Scope::Function(index));
+        }
+    }
+
+    fn resolve_lines(&mut self, lines: &[BodyLine<'a>], scope: Scope) {
+        let mut calls = 0;
+        for line in lines {
+            match line {
+                BodyLine::Operation(operation) => self.resolve_operation(operation, scope),
+                BodyLine::Goto(goto) => {
+                    if let Some(predicate) = goto.predicate() {
+                        self.resolve_register(predicate.register(), scope);
+                    }
+                    self.refer(goto.label(), SymbolKind::Label, scope);
+                }
+                BodyLine::FunctionCall(call) => {
+                    let function = scope.function().unwrap_or_default();
+                    let scope = Scope::Call { function, call: calls };
+                    calls += 1;
+                    self.resolve_lines(&call_lines(call), scope);
+                    self.refer(call.callee(), SymbolKind::Function, scope);
+                    for parameter in call.return_parameters().into_iter().chain(call.argument_parameters()) {
+                        self.refer(parameter, SymbolKind::Parameter, scope);
+                    }
+                }
+                BodyLine::Guarded(predicate, operation) => {
+                    self.resolve_register(predicate.register(), scope);
+                    self.resolve_operation(operation, scope);
+                }
+                _ => (),
+            }
+        }
+    }
+
+    fn resolve_operation(&mut self, operation: &Operation<'a>, scope: Scope) {
+        if operation.operation().starts_with('.') {
+            return;
+        }
+        for operand in operation.operands().unwrap_or_default() {
+            self.resolve_operand(&operand, scope);
+        }
+    }
+
+    fn resolve_operand(&mut self, operand: &Operand<'a>, scope: Scope) {
+        match operand {
+            Operand::Register(name) => self.resolve_register(name, scope),
+            Operand::Pair(first, second) => {
+                self.resolve_register(first, scope);
+                self.resolve_register(second, scope);
+            }
+            Operand::Vector(names) => {
+                for name in names.iter().filter(|name| name.starts_with('%')) {
+                    self.resolve_register(name, scope);
+                }
+            }
+            Operand::Address(address) if address.contains([',', '{']) => {
+                for operand in operand.address_operands().unwrap_or_default() {
+                    self.resolve_operand(&operand, scope);
+                }
+            }
+            Operand::Address(address) => {
+                let base = address.split(['+', '-']).next().unwrap_or_default().trim();
+                match base.chars().next() {
+                    Some('%') => self.resolve_register(base, scope),
+                    Some(c) if !c.is_ascii_digit() => self.refer(base, SymbolKind::Variable, scope),
+                    _ => (),
+                }
+            }
+            Operand::Symbol("_") => (),
+            Operand::Symbol(name) => match name.strip_prefix('$') {
+                Some(label) => self.refer(label, SymbolKind::Label, scope),
+                None => self.refer(name, SymbolKind::Variable, scope),
+            },
+            _ => (),
+        }
+    }
+
+    fn resolve_register(&mut self, name: &'a str, scope: Scope) {
+        if !is_special_register(name) {
+            self.refer(name, SymbolKind::Register, scope);
+        }
+    }
+
+    /// Records a use of `name`, expected to be a symbol of kind `kind`.
+    fn refer(&mut self, name: &'a str, kind: SymbolKind, scope: Scope) {
+        let definition = self.table.lookup(scope, name);
+        if definition.is_none() {
+            let noun = match kind {
+                SymbolKind::Variable => "symbol",
+                kind => kind.describe(),
+            };
+            let message = format!("undefined {noun} `{}`{}", display_name(kind, name), self.place(scope));
+            self.table.diagnostics.push(Diagnostic::new(Code::UndefinedSymbol, message, name));
+        }
+        self.table.references.push(Reference { name, kind, scope, definition });
+    }
+
+    /// `" in `kernel`"` for a scope of the function `kernel`, nothing for the module.
+    fn place(&self, scope: Scope) -> String {
+        match scope.function() {
+            Some(index) => format!(" in `{}`", self.file.functions[index].signature.name),
+            None => String::new(),
+        }
+    }
+}
+
+fn call_lines<'a>(call: &FunctionCall<'a>) -> Vec<BodyLine<'a>> {
+    call.lines().map_while(Result::ok).map(|(_, line)| line).collect()
+}
+
+fn display_name(kind: SymbolKind, name: &str) -> String {
+    match kind {
+        SymbolKind::Label => format!("${name}"),
+        _ => String::from(name),
+    }
+}
+
+/// The kind of variable a state space declaration such as `.local .align 8 .b8
+/// __local_depot0[16]` declares, if the operation is one.
+fn declaration_kind(operation: &Operation<'_>) -> Option<SymbolKind> {
+    match operation.operation() {
+        ".param" => Some(SymbolKind::Parameter),
+        ".local" | ".shared" | ".const" | ".global" => Some(SymbolKind::Variable),
+        _ => None,
+    }
+}
+
+/// A name declared by a declaration such as `.global .align 4 .u32 counter`.
+struct Declaration<'a> {
+    name: &'a str,
+    ty: Option<ScalarType>,
+    space: Option<StateSpace>,
+    count: Option<u32>,
+}
+
+impl<'a> Declaration<'a> {
+    fn named(name: &'a str) -> Self {
+        Declaration { name, ty: None, space: None, count: None }
+    }
+}
+
+/// The names declared by comma separated declarations such as `.param .b64 a, .param
+/// .align 8 .b8 b[16]`, `.global .u32 counter = 0` or `.b32 %r<5>, %temp`, ignoring any
+/// initializer. A declaration without a type or state space takes those of the one before.
+fn declarations(text: &str) -> Vec<Declaration<'_>> {
+    let text = text.split('=').next().unwrap_or_default();
+    let (mut ty, mut space) = (None, None);
+    text.split(',')
+        .filter_map(|declaration| {
+            let tokens = declaration.split_whitespace();
+            let modifiers = tokens.clone().filter_map(|token| token.strip_prefix('.'));
+            space = modifiers.clone().find_map(StateSpace::from_modifier).or(space);
+            ty = modifiers.clone().find_map(ScalarType::from_modifier).or(ty);
+            let token = tokens.last()?;
+            let name = token.split(['[', '<']).next().unwrap_or(token);
+            let count = token.split_once('<').and_then(|(_, count)| count.trim_end_matches('>').parse().ok());
+            (!name.is_empty() && !name.starts_with('.')).then_some(Declaration { name, ty, space, count })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test_symbol_table {
+    use std::{string::ToString, vec::Vec};
+
+    use crate::parser::{
+        diagnostic::Code,
+        instruction::types::{ScalarType, StateSpace},
+        PtxFile,
+    };
+
+    use super::{declarations, Scope, SymbolKind, SymbolTable};
+
+    const SOURCE: &str = "
+.version 7.5
+.target sm_80
+.address_size 64
+
+.global .align 4 .u32 counter;
+.func (.param .b32 func_retval0) next(.param .b32 next_param_0);
+
+.visible .entry kernel(
+    .param .u64 kernel_param_0
+)
+{
+    .reg .pred %p<2>;
+    .reg .b32 %r<4>;
+    .reg .b64 %rd<3>;
+
+    ld.param.u64 %rd1, [kernel_param_0];
+    mov.u32 %r1, %tid.x;
+    setp.eq.s32 %p1, %r1, 0;
+    @%p1 bra $L__BB0_2;
+    @!%p1 st.global.u32 [counter], %r1;
+    { // callseq 0, 0
+    .param .b32 param0;
+    st.param.b32 [param0+0], %r1;
+    .param .b32 retval0;
+    call.uni (retval0), 
+    next, 
+    (
+    param0
+    );
+    ld.param.b32 %r2, [retval0+0];
+    } // callseq 0
+$L__BB0_2:
+    st.global.u32 [%rd1+4], %r2;
+    ret;
+}
+
+.func (.param .b32 func_retval0) next(.param .b32 next_param_0)
+{
+    .reg .b32 %r<3>;
+
+    ld.param.u32 %r1, [next_param_0];
+    add.s32 %r2, %r1, 1;
+    st.param.b32 [func_retval0+0], %r2;
+    ret;
+}
+";
+
+    #[test]
+    fn resolves_every_reference() {
+        let file = PtxFile::try_from(SOURCE).unwrap();
+        let table = SymbolTable::resolve(&file);
+        assert_eq!(table.diagnostics(), []);
+
+        let kernel = Scope::Function(1);
+        let register = table.lookup(kernel, "%r3").unwrap();
+        assert_eq!(table.symbol(register).name(), "%r");
+        assert_eq!(table.symbol(register).count(), Some(4));
+        assert_eq!(table.lookup(kernel, "%r4"), None);
+        assert_eq!(table.lookup(kernel, "%rd"), table.lookup(kernel, "%rd2"));
+        assert_eq!(table.lookup(kernel, "%r1"), Some(register));
+        assert_eq!(table.references_to(register).count(), 6);
+
+        let label = table.lookup(kernel, "L__BB0_2").unwrap();
+        assert_eq!(table.symbol(label).kind(), SymbolKind::Label);
+        assert_eq!(table.references_to(label).count(), 1);
+
+        let next = table.lookup(Scope::Module, "next").unwrap();
+        assert_eq!(table.symbol(next).function(), Some(2));
+        let call = table.references_to(next).next().unwrap();
+        assert_eq!(call.scope(), Scope::Call { function: 1, call: 0 });
+        let callee = &file.functions()[table.symbol(next).function().unwrap()];
+        assert!(callee.body.is_some());
+
+        let retval = table.lookup(call.scope(), "retval0").unwrap();
+        assert_eq!(table.symbol(retval).kind(), SymbolKind::Parameter);
+        assert_eq!(table.references_to(retval).count(), 2);
+        assert_eq!(table.lookup(kernel, "retval0"), None);
+
+        let counter = table.lookup(kernel, "counter").unwrap();
+        assert_eq!(table.symbol(counter).scope(), Scope::Module);
+        let reference = table.references_to(counter).next().unwrap();
+        let span = reference.span_in(SOURCE).unwrap();
+        assert_eq!(&SOURCE[span], "counter");
+        assert_eq!(table.definition(reference), Some(table.symbol(counter)));
+
+        let param = table.lookup(Scope::Function(2), "next_param_0").unwrap();
+        assert_eq!(table.references_to(param).count(), 1);
+        assert_eq!(table.lookup(Scope::Function(2), "func_retval0").map(|id| table.symbol(id).kind()), Some(SymbolKind::Parameter));
+    }
+
+    #[test]
+    fn reports_undefined_and_duplicate_symbols() {
+        let source = SOURCE
+            .replace("bra $L__BB0_2", "bra $L__BB0_9")
+            .replace("add.s32 %r2, %r1, 1", "add.s32 %r2, %r1, %r7")
+            .replace("next, ", "missing, ")
+            .replace(".reg .b64 %rd<3>;", ".reg .b64 %rd<3>;\n    .reg .b32 %r<2>;");
+        let file = PtxFile::try_from(source.as_str()).unwrap();
+        let table = SymbolTable::resolve(&file);
+        let diagnostics = table.diagnostics().iter().map(ToString::to_string).collect::<Vec<_>>();
+        assert_eq!(
+            diagnostics,
+            [
+                "error[E0002]: duplicate register `%r` in `kernel`",
+                "error[E0001]: undefined label `$L__BB0_9` in `kernel`",
+                "error[E0001]: undefined function `missing` in `kernel`",
+                "error[E0001]: undefined register `%r7` in `next`",
+            ]
+        );
+        let undefined = &table.diagnostics()[3];
+        assert_eq!(undefined.code(), Code::UndefinedSymbol);
+        assert_eq!(&source[undefined.span_in(&source).unwrap()], "%r7");
+    }
+
+    #[test]
+    fn redefined_functions() {
+        let source = ".version 7.5\n.target sm_80\n.address_size 64\n\n.func f(.param .b32 a)\n{\n\tret;\n}\n\n.func f(.param .b32 a)\n{\n\tret;\n}\n";
+        let file = PtxFile::try_from(source).unwrap();
+        let table = SymbolTable::resolve(&file);
+        let diagnostics = table.diagnostics().iter().map(ToString::to_string).collect::<Vec<_>>();
+        assert_eq!(diagnostics, ["error[E0002]: duplicate function `f`"]);
+    }
+
+    #[test]
+    fn declared_names() {
+        let names = |text| declarations(text).into_iter().map(|declaration| declaration.name).collect::<Vec<_>>();
+        assert_eq!(names(".param .b64 a,\n.param .align 8 .b8 b[16]"), ["a", "b"]);
+        assert_eq!(names(".global .u32 counter = 0"), ["counter"]);
+        assert_eq!(names(".global .align 8 .u64 table[2] = {a, b}"), ["table"]);
+
+        let registers = declarations(".b32 %r<5>, %temp");
+        assert_eq!((registers[0].name, registers[0].count), ("%r", Some(5)));
+        assert_eq!((registers[1].name, registers[1].count, registers[1].ty), ("%temp", None, Some(ScalarType::B32)));
+        let local = &declarations(".local .align 8 .b8 __local_depot0[16]")[0];
+        assert_eq!((local.ty, local.space), (Some(ScalarType::B8), Some(StateSpace::Local)));
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg(test)]
+mod test_resolve_files {
+    use std::vec::Vec;
+
+    use crate::{parser::PtxFile, ptx_files};
+
+    use super::SymbolTable;
+
+    #[test]
+    fn no_undefined_symbols() {
+        for source in ptx_files::ALL {
+            let file = PtxFile::try_from(source).unwrap();
+            let table = SymbolTable::resolve(&file);
+            assert_eq!(table.diagnostics(), [], "{}", &source[..source.len().min(200)]);
+            assert!(!table.references().is_empty());
+        }
+    }
+
+    #[test]
+    fn example_file_is_incomplete() {
+        let file = PtxFile::try_from(ptx_files::_EXAMPLE_FILE).unwrap();
+        let table = SymbolTable::resolve(&file);
+        let undefined = table.diagnostics().iter().map(|diagnostic| diagnostic.text()).collect::<Vec<_>>();
+        assert_eq!(undefined[..3], ["__cudaparm__Z6kernelPiS_i_a", "__cudaparm__Z6kernelPiS_i_b", "%rd3"]);
+        assert!(undefined.contains(&"%f4"));
+    }
+}
@@ -0,0 +1,578 @@
+//! Checks for mistakes `ptxas` would reject, without running `ptxas`.
+//!
+//! ```
+//! use ptx_parser::prelude::*;
+//!
+//! let ptx: PtxFile = "
+//! .version 7.5
+//! .target sm_80
+//! .address_size 64
+//!
+//! .visible .entry kernel(
+//!     .param .u64 kernel_param_0
+//! )
+//! {
+//!     .reg .b32 %r<2>;
+//!     .reg .b64 %rd<2>;
+//!     ld.param.u64 %rd1, [kernel_param_0];
+//!     add.u32 %r1, %rd1, 1;
+//!     ret;
+//! }
+//! "
+//! .try_into()
+//! .unwrap();
+//!
+//! let diagnostics = ptx.validate();
+//! assert_eq!(diagnostics[0].code(), Code::TypeMismatch);
+//! assert_eq!(diagnostics[0].text(), "%rd1");
+//! ```
+
+use std::{format, string::String, vec::Vec};
+
+use crate::parser::{
+    availability,
+    diagnostic::{Code, Diagnostic},
+    function::body::{BodyLine, Operation},
+    instruction::{
+        memory::{AccessKind, MemoryAccess},
+        types::{MemoryOrder, ScalarType, StateSpace},
+        Operand,
+    },
+    symbol::{Scope, SymbolKind, SymbolTable},
+    PtxFile,
+};
+
+/// Resolves the names of `file` and checks every instruction, returning the diagnostics
+/// in the order of the source.
+pub fn validate<'a>(file: &PtxFile<'a>) -> Vec<Diagnostic<'a>> {
+    let symbols = SymbolTable::resolve(file);
+    let mut validator = Validator {
+        symbols: &symbols,
+        address_bits: file.preamble.address_size().bits().unwrap_or(64) as usize,
+        entry: false,
+        diagnostics: symbols.diagnostics().to_vec(),
+    };
+    validator.diagnostics.extend(availability::check(file));
+    for (index, function) in file.functions.iter().enumerate() {
+        let Some(body) = &function.body else { continue };
+        validator.entry = function.signature.entry;
+        validator.check_lines(&body.parsed().0, Scope::Function(index));
+    }
+    let mut diagnostics = validator.diagnostics;
+    diagnostics.sort_by_key(|diagnostic| diagnostic.text().as_ptr() as usize);
+    diagnostics
+}
+
+/// The kinds of value a type describes; registers of one kind cannot hold operands of another.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Family {
+    Bit,
+    Integer,
+    Float,
+    Predicate,
+}
+
+impl Family {
+    fn of(ty: ScalarType) -> Self {
+        match ty {
+            ScalarType::Pred => Family::Predicate,
+            ScalarType::B8 | ScalarType::B16 | ScalarType::B32 | ScalarType::B64 | ScalarType::B128 => Family::Bit,
+            ty if ty.is_float() => Family::Float,
+            _ => Family::Integer,
+        }
+    }
+}
+
+/// The register an operand needs, e.g. a 64-bit integer for the destination of `mul.wide.s32`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Expected {
+    bits: usize,
+    family: Family,
+}
+
+impl Expected {
+    const PREDICATE: Expected = Expected { bits: 1, family: Family::Predicate };
+    const U32: Expected = Expected { bits: 32, family: Family::Integer };
+
+    fn of(ty: ScalarType) -> Self {
+        Expected { bits: ty.bits(), family: Family::of(ty) }
+    }
+
+    fn wide(ty: ScalarType) -> Self {
+        Expected { bits: ty.bits() * 2, family: Family::of(ty) }
+    }
+
+    /// Whether a register declared `declared` may be used. With `relaxed`, as for `ld`,
+    /// `st` and `cvt`, integer and bit-size registers may be wider than the operand.
+    fn accepts(&self, declared: ScalarType, relaxed: bool) -> bool {
+        let (bits, family) = (declared.bits(), Family::of(declared));
+        if family == Family::Predicate || self.family == Family::Predicate {
+            return family == self.family;
+        }
+        match (family, self.family) {
+            (Family::Float, Family::Integer) | (Family::Integer, Family::Float) => false,
+            (Family::Float, _) | (_, Family::Float) => bits == self.bits,
+            _ if relaxed => bits >= self.bits,
+            _ => bits == self.bits,
+        }
+    }
+}
+
+impl core::fmt::Display for Expected {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self.family {
+            Family::Predicate => f.write_str("a predicate"),
+            Family::Bit => write!(f, "a {}-bit register", self.bits),
+            Family::Integer => write!(f, "a {}-bit integer", self.bits),
+            Family::Float => write!(f, "a {}-bit float", self.bits),
+        }
+    }
+}
+
+/// The operands an instruction takes: what each register must be, or `None` if the
+/// operand is not checked.
+struct Signature {
+    operands: Vec<Option<Expected>>,
+    relaxed: bool,
+}
+
+impl Signature {
+    fn new(operands: Vec<Option<Expected>>) -> Self {
+        Signature { operands, relaxed: false }
+    }
+}
+
+/// The signature of the common arithmetic, comparison and conversion instructions.
+fn signature(operation: &Operation<'_>, types: &[ScalarType]) -> Option<Signature> {
+    let opcode = operation.opcode();
+    let wide = operation.modifiers().any(|modifier| modifier == "wide");
+    let data = || types.last().copied().map(Expected::of);
+    let unary = || Signature::new(vec![data(); 2]);
+    Some(match opcode {
+        "add" | "sub" | "min" | "max" | "and" | "or" | "xor" | "div" | "rem" | "copysign" => {
+            Signature::new(vec![data(); 3])
+        }
+        "mul" if wide => Signature::new(vec![types.last().copied().map(Expected::wide), data(), data()]),
+        "mul" => Signature::new(vec![data(); 3]),
+        "mad" if wide => {
+            let wide = types.last().copied().map(Expected::wide);
+            Signature::new(vec![wide, data(), data(), wide])
+        }
+        "mad" | "fma" => Signature::new(vec![data(); 4]),
+        "mov" | "not" | "neg" | "abs" | "cnot" | "sqrt" | "rsqrt" | "rcp" | "sin" | "cos" | "lg2" | "ex2" | "brev" => unary(),
+        "popc" | "clz" => Signature::new(vec![Some(Expected::U32), data()]),
+        "shl" | "shr" => Signature::new(vec![data(), data(), Some(Expected::U32)]),
+        "selp" => Signature::new(vec![data(), data(), data(), Some(Expected::PREDICATE)]),
+        "setp" if has_boolean_operator(operation) => {
+            Signature::new(vec![Some(Expected::PREDICATE), data(), data(), Some(Expected::PREDICATE)])
+        }
+        "setp" => Signature::new(vec![Some(Expected::PREDICATE), data(), data()]),
+        "cvt" => Signature {
+            operands: vec![types.first().copied().map(Expected::of), types.get(1).copied().map(Expected::of)],
+            relaxed: true,
+        },
+        "cvta" => unary(),
+        "bra" => Signature::new(vec![None]),
+        "ret" | "exit" => Signature::new(Vec::new()),
+        _ => return None,
+    })
+}
+
+fn has_boolean_operator(operation: &Operation<'_>) -> bool {
+    operation.modifiers().any(|modifier| matches!(modifier, "and" | "or" | "xor"))
+}
+
+const ROUNDING: [&str; 8] = ["rn", "rz", "rm", "rp", "rni", "rzi", "rmi", "rpi"];
+
+/// Why the modifiers of `operation` cannot be combined, if they cannot.
+fn invalid_modifiers(operation: &Operation<'_>, types: &[ScalarType]) -> Option<&'static str> {
+    let opcode = operation.opcode();
+    let has = |name: &str| operation.modifiers().any(|modifier| modifier == name);
+    let arithmetic = matches!(opcode, "add" | "sub" | "mul" | "mad" | "min" | "max" | "div" | "neg" | "abs" | "fma");
+    let is_float = types.iter().any(ScalarType::is_float);
+    if operation.modifiers().filter(|modifier| ROUNDING.contains(modifier)).count() > 1 {
+        return Some("more than one rounding mode");
+    }
+    if arithmetic {
+        match types {
+            [] => return Some("missing type"),
+            [_] => (),
+            _ => return Some("more than one type"),
+        }
+        if !is_float && operation.modifiers().any(|modifier| ROUNDING.contains(&modifier)) {
+            return Some("rounding mode on an integer instruction");
+        }
+        if is_float && (has("hi") || has("lo") || has("wide")) {
+            return Some("`.hi`, `.lo` or `.wide` on a floating point instruction");
+        }
+        if has("wide") && !types.iter().all(|ty| matches!(ty.bits(), 16 | 32)) {
+            return Some("`.wide` needs a 16 or 32-bit integer type");
+        }
+        if has("sat") && !is_float && types != [ScalarType::S32] {
+            return Some("`.sat` needs a floating point or `.s32` type");
+        }
+    }
+    if has("ftz") && !types.iter().any(|ty| ty.is_float() && *ty != ScalarType::F64) {
+        return Some("`.ftz` needs a single or half precision floating point type");
+    }
+    if matches!(opcode, "ld" | "ldu" | "st" | "atom" | "red") {
+        if operation.modifiers().filter_map(StateSpace::from_modifier).count() > 1 {
+            return Some("more than one state space");
+        }
+        let orders = operation.modifiers().filter_map(MemoryOrder::from_modifier).collect::<Vec<_>>();
+        let scoped = operation.modifiers().any(|modifier| matches!(modifier, "cta" | "cluster" | "gpu" | "sys"));
+        match (opcode, orders.as_slice()) {
+            (_, [_, _, ..]) => return Some("more than one memory order"),
+            (_, [MemoryOrder::Volatile]) if scoped => return Some("a scope on a `.volatile` access"),
+            ("ld" | "st", [order]) if *order != MemoryOrder::Volatile && !scoped => {
+                return Some("a memory order without a scope")
+            }
+            ("ld", [MemoryOrder::Release]) => return Some("`.release` on a load"),
+            ("st", [MemoryOrder::Acquire]) => return Some("`.acquire` on a store"),
+            _ => (),
+        }
+        if has("nc") && (opcode != "ld" || !has("global")) {
+            return Some("`.nc` on anything but `ld.global`");
+        }
+    }
+    None
+}
+
+struct Validator<'t, 'a> {
+    symbols: &'t SymbolTable<'a>,
+    address_bits: usize,
+    /// Whether the function being checked is a kernel, whose parameters are read-only.
+    entry: bool,
+    diagnostics: Vec<Diagnostic<'a>>,
+}
+
+impl<'a> Validator<'_, 'a> {
+    fn report(&mut self, code: Code, message: String, text: &'a str) {
+        self.diagnostics.push(Diagnostic::new(code, message, text));
+    }
+
+    fn check_lines(&mut self, lines: &[BodyLine<'a>], scope: Scope) {
+        let mut calls = 0;
+        for line in lines {
+            match line {
+                BodyLine::Operation(operation) => self.check_operation(operation, scope),
+                BodyLine::Goto(goto) => {
+                    if let Some(predicate) = goto.predicate() {
+                        self.check_register(predicate.register(), Expected::PREDICATE, false, "a branch", scope);
+                    }
+                }
+                BodyLine::FunctionCall(call) => {
+                    let function = scope.function().unwrap_or_default();
+                    let lines = call.lines().map_while(Result::ok).map(|(_, line)| line).collect::<Vec<_>>();
+                    self.check_lines(&lines, Scope::Call { function, call: calls });
+                    calls += 1;
+                }
+                BodyLine::Guarded(predicate, operation) => {
+                    self.check_register(predicate.register(), Expected::PREDICATE, false, operation.operation(), scope);
+                    self.check_operation(operation, scope);
+                }
+                _ => (),
+            }
+        }
+    }
+
+    fn check_operation(&mut self, operation: &Operation<'a>, scope: Scope) {
+        let name = operation.operation();
+        if name.starts_with('.') {
+            return;
+        }
+        let Ok(operands) = operation.operands() else { return };
+        let types = operation.modifiers().filter_map(ScalarType::from_modifier).collect::<Vec<_>>();
+        if let Some(reason) = invalid_modifiers(operation, &types) {
+            self.report(Code::InvalidModifiers, format!("invalid modifiers on `{name}`: {reason}"), name);
+            return;
+        }
+        if matches!(operation.opcode(), "ld" | "ldu" | "st") {
+            self.check_memory_access(operation, &operands, scope);
+            return;
+        }
+        if matches!(operation.opcode(), "atom" | "red") {
+            self.check_atomic(operation, &operands, scope);
+        }
+        if operation.opcode() == "cvta" {
+            self.check_cvta(operation, &types);
+        }
+        let Some(signature) = signature(operation, &types) else { return };
+        if operands.len() != signature.operands.len() {
+            let message = format!("`{name}` takes {} operands, found {}", signature.operands.len(), operands.len());
+            self.report(Code::OperandCount, message, name);
+            return;
+        }
+        for (operand, expected) in operands.iter().zip(signature.operands) {
+            match (operand, expected) {
+                (Operand::Register(register), Some(expected)) => {
+                    self.check_register(register, expected, signature.relaxed, name, scope)
+                }
+                (Operand::Pair(first, second), Some(expected)) => {
+                    self.check_register(first, expected, signature.relaxed, name, scope);
+                    self.check_register(second, expected, signature.relaxed, name, scope);
+                }
+                _ => (),
+            }
+        }
+    }
+
+    /// Checks that `register` is declared with a type `expected` accepts.
+    fn check_register(&mut self, register: &'a str, expected: Expected, relaxed: bool, user: &str, scope: Scope) {
+        let Some(declared) = self.register_type(register, scope) else { return };
+        if !expected.accepts(declared, relaxed) {
+            let message = format!(
+                "`{register}` is declared `.{}`, but `{user}` expects {expected}",
+                declared.modifier(),
+            );
+            self.report(Code::TypeMismatch, message, register);
+        }
+    }
+
+    fn register_type(&self, register: &str, scope: Scope) -> Option<ScalarType> {
+        let symbol = self.symbols.symbol(self.symbols.lookup(scope, register)?);
+        (symbol.kind() == SymbolKind::Register).then(|| symbol.ty())?
+    }
+
+    fn check_memory_access(&mut self, operation: &Operation<'a>, operands: &[Operand<'a>], scope: Scope) {
+        let name = operation.operation();
+        let Ok(access) = MemoryAccess::try_from(operation) else {
+            let message = format!("`{name}` takes a value and an address, found {} operands", operands.len());
+            self.report(Code::OperandCount, message, name);
+            return;
+        };
+        if let Err(mismatch) = access.check_vector_operand() {
+            let message = format!("`{name}` moves {} values, found {}", mismatch.expected, mismatch.found);
+            self.report(Code::OperandCount, message, name);
+            return;
+        }
+        let values = match &access.value {
+            Operand::Register(register) => vec![*register],
+            Operand::Vector(registers) => registers.clone(),
+            _ => Vec::new(),
+        };
+        for register in values.into_iter().filter(|register| register.starts_with('%')) {
+            self.check_register(register, Expected::of(access.ty), true, name, scope);
+        }
+        if access.kind == AccessKind::Store && access.state_space == StateSpace::Const {
+            self.report(Code::StateSpaceMisuse, format!("`{name}` writes to read-only `.const` memory"), name);
+        }
+        self.check_address(name, &access.address, access.state_space, access.kind == AccessKind::Store, scope);
+    }
+
+    fn check_atomic(&mut self, operation: &Operation<'a>, operands: &[Operand<'a>], scope: Scope) {
+        let name = operation.operation();
+        let space = operation.modifiers().find_map(StateSpace::from_modifier).unwrap_or(StateSpace::Generic);
+        if matches!(space, StateSpace::Local | StateSpace::Const | StateSpace::Param) {
+            let message = format!("`{name}` is an atomic on `.{}` memory", space.modifier().unwrap_or_default());
+            self.report(Code::StateSpaceMisuse, message, name);
+            return;
+        }
+        if let Some(address) = operands.iter().find(|operand| matches!(operand, Operand::Address(_))) {
+            self.check_address(name, address, space, true, scope);
+        }
+    }
+
+    fn check_cvta(&mut self, operation: &Operation<'a>, types: &[ScalarType]) {
+        let name = operation.operation();
+        let global = operation.modifiers().any(|modifier| modifier == "global");
+        match types {
+            [ty] if global && ty.bits() != self.address_bits => {
+                let message = format!("`{name}` converts {}-bit addresses, but `.address_size` is {}", ty.bits(), self.address_bits);
+                self.report(Code::PointerWidth, message, name);
+            }
+            _ => (),
+        }
+    }
+
+    /// Checks the base of an address: a register must be as wide as a pointer into
+    /// `space`, and a variable must be declared in `space`.
+    fn check_address(&mut self, name: &'a str, address: &Operand<'a>, space: StateSpace, writes: bool, scope: Scope) {
+        let Operand::Address(address) = address else { return };
+        if address.contains([',', '{']) {
+            return;
+        }
+        let base = address.split(['+', '-']).next().unwrap_or_default().trim();
+        if base.starts_with('%') {
+            let Some(declared) = self.register_type(base, scope) else { return };
+            if matches!(space, StateSpace::Generic | StateSpace::Global) && declared.bits() != self.address_bits {
+                let message = format!(
+                    "address `{base}` of `{name}` is {}-bit, but `.address_size` is {}",
+                    declared.bits(),
+                    self.address_bits,
+                );
+                self.report(Code::PointerWidth, message, base);
+            }
+            return;
+        }
+        let Some(id) = self.symbols.lookup(scope, base) else { return };
+        let symbol = self.symbols.symbol(id);
+        let Some(declared) = symbol.space() else { return };
+        let matches = declared == space
+            || space == StateSpace::Generic
+            || (declared.is_shared() && space.is_shared());
+        if !matches {
+            let message = format!(
+                "`{base}` is declared in `.{}`, but `{name}` accesses `.{}`",
+                declared.modifier().unwrap_or_default(),
+                space.modifier().unwrap_or_default(),
+            );
+            self.report(Code::StateSpaceMisuse, message, base);
+        } else if writes && self.entry && symbol.kind() == SymbolKind::Parameter && matches!(symbol.scope(), Scope::Function(_)) {
+            self.report(Code::StateSpaceMisuse, format!("`{name}` writes to the kernel parameter `{base}`"), base);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_validate {
+    use std::{string::String, vec::Vec};
+
+    use crate::parser::{diagnostic::Code, PtxFile};
+
+    /// Validates a kernel with the given body, returning the codes and texts of the diagnostics.
+    fn validate(body: &str) -> Vec<(Code, String)> {
+        let source = std::format!(
+            "
+.version 8.0
+.target sm_90
+.address_size 64
+
+.global .align 4 .u32 counter;
+
+.visible .entry kernel(
+    .param .u64 kernel_param_0
+)
+{{
+    .reg .pred %p<3>;
+    .reg .b16 %rs<3>;
+    .reg .b32 %r<5>;
+    .reg .f32 %f<3>;
+    .reg .b64 %rd<5>;
+    .shared .align 4 .f32 partial;
+
+{body}
+$L__BB0_1:
+    ret;
+}}
+"
+        );
+        let file = PtxFile::try_from(source.as_str()).unwrap();
+        file.validate()
+            .into_iter()
+            .map(|diagnostic| (diagnostic.code(), String::from(diagnostic.text())))
+            .collect()
+    }
+
+    fn codes(body: &str) -> Vec<Code> {
+        validate(body).into_iter().map(|(code, _)| code).collect()
+    }
+
+    #[test]
+    fn valid() {
+        let body = "
+    ld.param.u64 %rd1, [kernel_param_0];
+    cvta.to.global.u64 %rd2, %rd1;
+    mov.u32 %r1, %tid.x;
+    mul.wide.u32 %rd3, %r1, 4;
+    add.s64 %rd4, %rd2, %rd3;
+    ld.global.nc.f32 %f1, [%rd4];
+    cvt.u16.u32 %rs1, %r1;
+    cvt.rn.f32.u16 %f2, %rs1;
+    setp.lt.f32 %p1, %f1, %f2;
+    selp.b32 %r2, 1, 0, %p1;
+    @%p1 st.global.f32 [%rd4+4], %f1;
+    st.shared.f32 [partial], %f1;
+    ld.const.u32 %r3, [%rd1];
+    atom.global.add.u32 %r4, [counter], 1;
+    ld.acquire.gpu.global.u32 %r4, [%rd4];
+    shl.b64 %rd4, %rd4, %r1;
+    @!%p1 bra $L__BB0_1;";
+        assert_eq!(validate(body), []);
+    }
+
+    #[test]
+    fn undefined_symbols() {
+        assert_eq!(
+            validate("    mov.u32 %r9, 0;\n    bra.uni $L__BB0_7;"),
+            [(Code::UndefinedSymbol, "%r9".into()), (Code::UndefinedSymbol, "L__BB0_7".into())]
+        );
+    }
+
+    #[test]
+    fn type_mismatches() {
+        assert_eq!(
+            validate("    add.u32 %r1, %rd1, 1;\n    add.s32 %r1, %f1, %r2;\n    @%r1 bra $L__BB0_1;"),
+            [
+                (Code::TypeMismatch, "%rd1".into()),
+                (Code::TypeMismatch, "%f1".into()),
+                (Code::TypeMismatch, "%r1".into()),
+            ]
+        );
+        assert_eq!(codes("    add.f32 %f1, %r1, %f2;"), []);
+        assert_eq!(validate("    setp.lt.s32 %p1|%r2, %r1, 0;"), [(Code::TypeMismatch, "%r2".into())]);
+        assert_eq!(codes("    mul.wide.s32 %r1, %r2, %r3;"), [Code::TypeMismatch]);
+        assert_eq!(codes("    ld.global.u8 %r1, [%rd1];\n    ld.global.f32 %rd1, [%rd2];"), [Code::TypeMismatch]);
+    }
+
+    #[test]
+    fn invalid_modifiers() {
+        for line in [
+            "add.rn.s32 %r1, %r2, %r3",
+            "add.s32.u32 %r1, %r2, %r3",
+            "mul.wide.f32 %f1, %f2, %f2",
+            "add.sat.u32 %r1, %r2, %r3",
+            "add.ftz.s32 %r1, %r2, %r3",
+            "ld.global.shared.u32 %r1, [%rd1]",
+            "ld.shared.nc.u32 %r1, [%rd1]",
+            "ld.acquire.global.u32 %r1, [%rd1]",
+            "st.acquire.gpu.global.u32 [%rd1], %r1",
+            "cvt.rn.rz.f32.f64 %f1, %rd1",
+        ] {
+            assert_eq!(codes(&std::format!("    {line};")), [Code::InvalidModifiers], "{line}");
+        }
+    }
+
+    #[test]
+    fn operand_counts() {
+        assert_eq!(validate("    add.s32 %r1, %r2;"), [(Code::OperandCount, "add.s32".into())]);
+        assert_eq!(codes("    setp.eq.s32 %p1, %r1, 0, %p2;"), [Code::OperandCount]);
+        assert_eq!(codes("    setp.eq.and.s32 %p1, %r1, 0, %p2;"), []);
+        assert_eq!(codes("    ld.global.v2.f32 {%f1, %f2, %f2}, [%rd1];"), [Code::OperandCount]);
+    }
+
+    #[test]
+    fn state_space_misuse() {
+        assert_eq!(
+            validate("    st.const.u32 [%rd1], %r1;\n    ld.shared.u32 %r1, [counter];\n    st.param.u64 [kernel_param_0], %rd1;"),
+            [
+                (Code::StateSpaceMisuse, "st.const.u32".into()),
+                (Code::StateSpaceMisuse, "counter".into()),
+                (Code::StateSpaceMisuse, "kernel_param_0".into()),
+            ]
+        );
+        assert_eq!(codes("    atom.local.add.u32 %r1, [%rd1], 1;"), [Code::StateSpaceMisuse]);
+        assert_eq!(codes("    ld.u32 %r1, [counter];"), []);
+    }
+
+    #[test]
+    fn pointer_widths() {
+        assert_eq!(
+            validate("    ld.global.u32 %r1, [%r2];\n    cvta.to.global.u32 %r1, %r2;\n    ld.shared.u32 %r1, [%r2];"),
+            [(Code::PointerWidth, "%r2".into()), (Code::PointerWidth, "cvta.to.global.u32".into())]
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_validate_files {
+    use crate::{parser::PtxFile, ptx_files};
+
+    #[test]
+    fn generated_files_are_valid() {
+        for source in ptx_files::ALL {
+            let file = PtxFile::try_from(source).unwrap();
+            assert_eq!(file.validate(), [], "{}", &source[..source.len().min(200)]);
+        }
+    }
+}
@@ -0,0 +1,528 @@
+//! Walks over the AST which call back into a `Visitor` or `VisitorMut` for every node.
+//!
+//! Each `visit_*` method defaults to the matching `walk_*` function, which visits the
+//! children of the node. An implementation overrides the methods for the nodes it cares
+//! about, and calls the `walk_*` function from its override to keep descending.
+
+use super::{
+    comment::Comment,
+    function::{
+        body::{BodyLine, FunctionBody, FunctionCall, Goto, Operation, Predicate, Register},
+        Function,
+    },
+    global::Global,
+    instruction::Operand,
+    location::{File, Location},
+    ptx_file::FunctionOrGlobal,
+    section::Section,
+    PtxParser,
+};
+#[cfg(feature = "std")]
+use super::PtxFile;
+
+pub trait Visitor<'a> {
+    #[cfg(feature = "std")]
+    fn visit_module(&mut self, module: &PtxFile<'a>) {
+        walk_module(self, module)
+    }
+
+    fn visit_item(&mut self, item: &FunctionOrGlobal<'a>) {
+        walk_item(self, item)
+    }
+
+    fn visit_function(&mut self, function: &Function<'a>) {
+        walk_function(self, function)
+    }
+
+    fn visit_global(&mut self, _global: &Global<'a>) {}
+
+    fn visit_file(&mut self, _file: &File<'a>) {}
+
+    fn visit_section(&mut self, _section: &Section<'a>) {}
+
+    /// The lines of a function body or of a call sequence.
+    fn visit_block(&mut self, block: &[BodyLine<'a>]) {
+        walk_block(self, block)
+    }
+
+    fn visit_line(&mut self, line: &BodyLine<'a>) {
+        walk_line(self, line)
+    }
+
+    fn visit_register(&mut self, _register: &Register<'a>) {}
+
+    fn visit_operation(&mut self, operation: &Operation<'a>) {
+        walk_operation(self, operation)
+    }
+
+    fn visit_operand(&mut self, _operand: &Operand<'a>) {}
+
+    fn visit_label(&mut self, _label: &'a str) {}
+
+    fn visit_location(&mut self, _location: &Location) {}
+
+    fn visit_goto(&mut self, goto: &Goto<'a>) {
+        walk_goto(self, goto)
+    }
+
+    fn visit_call(&mut self, call: &FunctionCall<'a>) {
+        walk_call(self, call)
+    }
+
+    /// A predicated instruction other than a branch.
+    fn visit_guarded(&mut self, predicate: &Predicate<'a>, operation: &Operation<'a>) {
+        walk_guarded(self, predicate, operation)
+    }
+
+    fn visit_comment(&mut self, _comment: &Comment<'a>) {}
+
+    /// A line which does not parse as any other kind of line.
+    fn visit_unknown(&mut self, _raw_string: &'a str) {}
+}
+
+/// Visits every item of `parser`, stopping at the first one which fails to parse.
+pub fn walk_parser<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, parser: PtxParser<'a>) {
+    for (_, item) in parser.map_while(Result::ok) {
+        visitor.visit_item(&item);
+    }
+}
+
+#[cfg(feature = "std")]
+pub fn walk_module<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, module: &PtxFile<'a>) {
+    module.globals.iter().for_each(|global| visitor.visit_global(global));
+    module.functions.iter().for_each(|function| visitor.visit_function(function));
+    module.files.iter().for_each(|file| visitor.visit_file(file));
+    module.sections.iter().for_each(|section| visitor.visit_section(section));
+}
+
+pub fn walk_item<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, item: &FunctionOrGlobal<'a>) {
+    match item {
+        FunctionOrGlobal::Function(function) => visitor.visit_function(function),
+        FunctionOrGlobal::Global(global) => visitor.visit_global(global),
+        FunctionOrGlobal::File(file) => visitor.visit_file(file),
+        FunctionOrGlobal::Section(section) => visitor.visit_section(section),
+    }
+}
+
+/// Visits the body of `function` up to its first line which fails to parse.
+pub fn walk_function<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, function: &Function<'a>) {
+    if let Some(body) = &function.body {
+        visitor.visit_block(&body.parsed().0);
+    }
+}
+
+pub fn walk_block<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, block: &[BodyLine<'a>]) {
+    block.iter().for_each(|line| visitor.visit_line(line));
+}
+
+pub fn walk_line<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, line: &BodyLine<'a>) {
+    match line {
+        BodyLine::Register(register) => visitor.visit_register(register),
+        BodyLine::Operation(operation) => visitor.visit_operation(operation),
+        BodyLine::Label(label) => visitor.visit_label(label),
+        BodyLine::Location(location) => visitor.visit_location(location),
+        BodyLine::Goto(goto) => visitor.visit_goto(goto),
+        BodyLine::Return | BodyLine::OpenBrace | BodyLine::CloseBrace => (),
+        BodyLine::FunctionCall(call) => visitor.visit_call(call),
+        BodyLine::Unknown(raw_string) => visitor.visit_unknown(raw_string),
+        BodyLine::Guarded(predicate, operation) => visitor.visit_guarded(predicate, operation),
+        BodyLine::Comment(comment) => visitor.visit_comment(comment),
+    }
+}
+
+/// Visits the register of the guard of a conditional branch as an operand.
+pub fn walk_goto<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, goto: &Goto<'a>) {
+    if let Some(predicate) = goto.predicate() {
+        visitor.visit_operand(&Operand::Register(predicate.register()));
+    }
+}
+
+/// Visits the register of the guard as an operand, then the operation.
+pub fn walk_guarded<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, predicate: &Predicate<'a>, operation: &Operation<'a>) {
+    visitor.visit_operand(&Operand::Register(predicate.register()));
+    visitor.visit_operation(operation);
+}
+
+/// Visits the operands of `operation`, if they parse.
+pub fn walk_operation<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, operation: &Operation<'a>) {
+    for operand in operation.operands().unwrap_or_default() {
+        visitor.visit_operand(&operand);
+    }
+}
+
+/// Visits the lines of the call sequence as a block.
+pub fn walk_call<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, call: &FunctionCall<'a>) {
+    let lines = call.lines().map_while(Result::ok).map(|(_, line)| line).collect::<Vec<_>>();
+    visitor.visit_block(&lines);
+}
+
+/// Like `Visitor`, but with mutable access so nodes can be rewritten in place.
+///
+/// Function bodies are visited as a `Vec` of lines, so lines can also be inserted or
+/// removed; the body is rebuilt from the lines once they have been visited.
+pub trait VisitorMut<'a> {
+    #[cfg(feature = "std")]
+    fn visit_module_mut(&mut self, module: &mut PtxFile<'a>) {
+        walk_module_mut(self, module)
+    }
+
+    fn visit_item_mut(&mut self, item: &mut FunctionOrGlobal<'a>) {
+        walk_item_mut(self, item)
+    }
+
+    fn visit_function_mut(&mut self, function: &mut Function<'a>) {
+        walk_function_mut(self, function)
+    }
+
+    fn visit_global_mut(&mut self, _global: &mut Global<'a>) {}
+
+    fn visit_file_mut(&mut self, _file: &mut File<'a>) {}
+
+    fn visit_section_mut(&mut self, _section: &mut Section<'a>) {}
+
+    fn visit_block_mut(&mut self, block: &mut Vec<BodyLine<'a>>) {
+        walk_block_mut(self, block)
+    }
+
+    fn visit_line_mut(&mut self, line: &mut BodyLine<'a>) {
+        walk_line_mut(self, line)
+    }
+
+    fn visit_register_mut(&mut self, _register: &mut Register<'a>) {}
+
+    fn visit_operation_mut(&mut self, operation: &mut Operation<'a>) {
+        walk_operation_mut(self, operation)
+    }
+
+    fn visit_operand_mut(&mut self, _operand: &mut Operand<'a>) {}
+
+    fn visit_label_mut(&mut self, _label: &mut &'a str) {}
+
+    fn visit_location_mut(&mut self, _location: &mut Location) {}
+
+    fn visit_goto_mut(&mut self, goto: &mut Goto<'a>) {
+        walk_goto_mut(self, goto)
+    }
+
+    fn visit_call_mut(&mut self, call: &mut FunctionCall<'a>) {
+        walk_call_mut(self, call)
+    }
+
+    /// A predicated instruction other than a branch.
+    fn visit_guarded_mut(&mut self, predicate: &mut Predicate<'a>, operation: &mut Operation<'a>) {
+        walk_guarded_mut(self, predicate, operation)
+    }
+
+    fn visit_comment_mut(&mut self, _comment: &mut Comment<'a>) {}
+
+    /// A line which does not parse as any other kind of line.
+    fn visit_unknown_mut(&mut self, _raw_string: &mut &'a str) {}
+}
+
+#[cfg(feature = "std")]
+pub fn walk_module_mut<'a, V: VisitorMut<'a> + ?Sized>(visitor: &mut V, module: &mut PtxFile<'a>) {
+    module.globals.iter_mut().for_each(|global| visitor.visit_global_mut(global));
+    module.functions.iter_mut().for_each(|function| visitor.visit_function_mut(function));
+    module.files.iter_mut().for_each(|file| visitor.visit_file_mut(file));
+    module.sections.iter_mut().for_each(|section| visitor.visit_section_mut(section));
+}
+
+pub fn walk_item_mut<'a, V: VisitorMut<'a> + ?Sized>(visitor: &mut V, item: &mut FunctionOrGlobal<'a>) {
+    match item {
+        FunctionOrGlobal::Function(function) => visitor.visit_function_mut(function),
+        FunctionOrGlobal::Global(global) => visitor.visit_global_mut(global),
+        FunctionOrGlobal::File(file) => visitor.visit_file_mut(file),
+        FunctionOrGlobal::Section(section) => visitor.visit_section_mut(section),
+    }
+}
+
+/// Visits the parsed lines of the body of `function`, then replaces the body with them if
+/// any changed. Text from the first line which fails to parse on is kept after the visited
+/// lines.
+pub fn walk_function_mut<'a, V: VisitorMut<'a> + ?Sized>(visitor: &mut V, function: &mut Function<'a>) {
+    if let Some(body) = &mut function.body {
+        let (mut lines, rest) = body.parsed();
+        let original = lines.clone();
+        visitor.visit_block_mut(&mut lines);
+        if lines != original {
+            *body = FunctionBody::from_lines(lines).with_rest(rest);
+        }
+    }
+}
+
+pub fn walk_block_mut<'a, V: VisitorMut<'a> + ?Sized>(visitor: &mut V, block: &mut Vec<BodyLine<'a>>) {
+    block.iter_mut().for_each(|line| visitor.visit_line_mut(line));
+}
+
+pub fn walk_line_mut<'a, V: VisitorMut<'a> + ?Sized>(visitor: &mut V, line: &mut BodyLine<'a>) {
+    match line {
+        BodyLine::Register(register) => visitor.visit_register_mut(register),
+        BodyLine::Operation(operation) => visitor.visit_operation_mut(operation),
+        BodyLine::Label(label) => visitor.visit_label_mut(label),
+        BodyLine::Location(location) => visitor.visit_location_mut(location),
+        BodyLine::Goto(goto) => visitor.visit_goto_mut(goto),
+        BodyLine::Return | BodyLine::OpenBrace | BodyLine::CloseBrace => (),
+        BodyLine::FunctionCall(call) => visitor.visit_call_mut(call),
+        BodyLine::Unknown(raw_string) => visitor.visit_unknown_mut(raw_string),
+        BodyLine::Guarded(predicate, operation) => visitor.visit_guarded_mut(predicate, operation),
+        BodyLine::Comment(comment) => visitor.visit_comment_mut(comment),
+    }
+}
+
+/// Visits the register of the guard of a conditional branch as an operand, and stores
+/// it back if it is still a register.
+pub fn walk_goto_mut<'a, V: VisitorMut<'a> + ?Sized>(visitor: &mut V, goto: &mut Goto<'a>) {
+    if let Some(mut predicate) = goto.predicate().cloned() {
+        walk_predicate_mut(visitor, &mut predicate);
+        goto.set_predicate(Some(predicate));
+    }
+}
+
+/// Visits the lines before and after the `call.uni` statement as two blocks, and stores
+/// them back if any changed. Lines from the first one which fails to parse on are dropped
+/// in that case.
+pub fn walk_call_mut<'a, V: VisitorMut<'a> + ?Sized>(visitor: &mut V, call: &mut FunctionCall<'a>) {
+    let original = call.split_lines();
+    let (mut before, mut after) = original.clone();
+    visitor.visit_block_mut(&mut before);
+    visitor.visit_block_mut(&mut after);
+    if (&before, &after) != (&original.0, &original.1) {
+        call.set_lines(before, after);
+    }
+}
+
+/// Visits the register of the guard as an operand, then the operation.
+pub fn walk_guarded_mut<'a, V: VisitorMut<'a> + ?Sized>(
+    visitor: &mut V,
+    predicate: &mut Predicate<'a>,
+    operation: &mut Operation<'a>,
+) {
+    walk_predicate_mut(visitor, predicate);
+    visitor.visit_operation_mut(operation);
+}
+
+fn walk_predicate_mut<'a, V: VisitorMut<'a> + ?Sized>(visitor: &mut V, predicate: &mut Predicate<'a>) {
+    let mut operand = Operand::Register(predicate.register());
+    visitor.visit_operand_mut(&mut operand);
+    if let Operand::Register(register) = operand {
+        *predicate = match predicate {
+            Predicate::True(_) => Predicate::True(register),
+            Predicate::False(_) => Predicate::False(register),
+        };
+    }
+}
+
+/// Visits the operands of `operation`, if they parse, and stores them back if any changed.
+pub fn walk_operation_mut<'a, V: VisitorMut<'a> + ?Sized>(visitor: &mut V, operation: &mut Operation<'a>) {
+    let Ok(original) = operation.operands() else {
+        return;
+    };
+    let mut operands = original.clone();
+    operands.iter_mut().for_each(|operand| visitor.visit_operand_mut(operand));
+    if operands != original {
+        operation.set_operands(operands);
+    }
+}
+
+#[cfg(test)]
+mod test_visitor {
+    use crate::{
+        parser::{
+            function::{
+                body::{BodyLine, FunctionCall, Operation},
+                Function,
+            },
+            instruction::Operand,
+            visit::{walk_function, walk_line, walk_parser, Visitor},
+            PtxParser,
+        },
+        ptx_files::{a, kernel},
+    };
+
+    #[derive(Default)]
+    struct Counts {
+        functions: usize,
+        operations: usize,
+        registers: usize,
+        calls: usize,
+        lines: usize,
+    }
+
+    impl<'a> Visitor<'a> for Counts {
+        fn visit_function(&mut self, function: &Function<'a>) {
+            self.functions += 1;
+            walk_function(self, function);
+        }
+
+        fn visit_line(&mut self, line: &BodyLine<'a>) {
+            self.lines += 1;
+            walk_line(self, line);
+        }
+
+        fn visit_operation(&mut self, _operation: &Operation<'a>) {
+            self.operations += 1;
+        }
+
+        fn visit_operand(&mut self, operand: &Operand<'a>) {
+            self.registers += matches!(operand, Operand::Register(_)) as usize;
+        }
+
+        fn visit_call(&mut self, _call: &FunctionCall<'a>) {
+            self.calls += 1;
+        }
+    }
+
+    #[test]
+    fn counts() {
+        let mut counts = Counts::default();
+        let ptx: PtxParser = kernel::_PTX.try_into().unwrap();
+        walk_parser(&mut counts, ptx);
+        assert_eq!(counts.functions, 1);
+        assert!(counts.operations > 0 && counts.lines > counts.operations);
+        // Only the guard of the branch is visited as an operand, since `visit_operation`
+        // is overridden without walking.
+        assert_eq!(counts.registers, 1);
+
+        let mut counts = Counts::default();
+        let ptx: PtxParser = a::_PTX.try_into().unwrap();
+        walk_parser(&mut counts, ptx);
+        assert!(counts.calls > 0);
+    }
+
+    struct Registers<'a>(Vec<&'a str>);
+
+    impl<'a> Visitor<'a> for Registers<'a> {
+        fn visit_operand(&mut self, operand: &Operand<'a>) {
+            if let Operand::Register(name) = operand {
+                self.0.push(name);
+            }
+        }
+    }
+
+    #[test]
+    fn call_sequences_are_walked() {
+        let mut registers = Registers(Vec::new());
+        let ptx: PtxParser = a::_PTX.try_into().unwrap();
+        walk_parser(&mut registers, ptx);
+        // `%rd2` is only stored to a parameter inside the first call sequence.
+        assert!(registers.0.contains(&"%rd2"));
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg(test)]
+mod test_visitor_mut {
+    use std::string::ToString;
+
+    use crate::{
+        parser::{
+            function::body::{BodyLine, Operation},
+            instruction::Operand,
+            printer::Equivalent,
+            visit::{walk_block_mut, Visitor, VisitorMut},
+            PtxFile,
+        },
+        ptx_files::kernel,
+    };
+
+    /// Renames `%r1` to `%r100` and drops `.loc` lines.
+    struct Rewrite;
+
+    impl<'a> VisitorMut<'a> for Rewrite {
+        fn visit_block_mut(&mut self, block: &mut Vec<BodyLine<'a>>) {
+            block.retain(|line| !matches!(line, BodyLine::Location(_)));
+            walk_block_mut(self, block);
+        }
+
+        fn visit_operand_mut(&mut self, operand: &mut Operand<'a>) {
+            if *operand == Operand::Register("%r1") {
+                *operand = Operand::Register("%r100");
+            }
+        }
+    }
+
+    struct Uses<'a>(Vec<Operation<'a>>);
+
+    impl<'a> Visitor<'a> for Uses<'a> {
+        fn visit_operation(&mut self, operation: &Operation<'a>) {
+            if operation.operands().unwrap().contains(&Operand::Register("%r100")) {
+                self.0.push(operation.clone());
+            }
+        }
+    }
+
+    #[test]
+    fn rewrite_registers() {
+        let mut ptx: PtxFile = kernel::_PTX.try_into().unwrap();
+        let original = ptx.clone();
+        Rewrite.visit_module_mut(&mut ptx);
+        assert_ne!(ptx, original);
+
+        let mut uses = Uses(Vec::new());
+        uses.visit_module(&ptx);
+        assert!(!uses.0.is_empty());
+
+        let printed = ptx.to_string();
+        assert!(printed.contains("%r100"));
+        let reparsed: PtxFile = printed.as_str().try_into().unwrap();
+        assert!(reparsed.equivalent(&ptx));
+    }
+
+    /// Renames one register wherever it is an operand.
+    struct Rename(&'static str, &'static str);
+
+    impl<'a> VisitorMut<'a> for Rename {
+        fn visit_operand_mut(&mut self, operand: &mut Operand<'a>) {
+            if *operand == Operand::Register(self.0) {
+                *operand = Operand::Register(self.1);
+            }
+        }
+    }
+
+    #[test]
+    fn rename_guards_and_call_sequences() {
+        let source = "
+.version 8.0
+.target sm_90
+.address_size 64
+
+.func g(.param .b64 g_param_0);
+
+.visible .entry k(.param .u64 k_param_0)
+{
+\t.reg .pred %p<2>;
+\t.reg .b32 %r<2>;
+\t.reg .b64 %rd<3>;
+\t// the pointer
+\tld.param.u64 %rd2, [k_param_0]; /* may be null */
+\tsetp.eq.s64 %p1, %rd2, 0;
+\t@%p1 add.s64 %rd2, %rd2, 1;
+\t@!%p1 bra $L__BB0_2;
+\t{ // callseq 0, 0
+\t.param .b64 param0;
+\tst.param.b64 [param0], %rd2;
+\tcall.uni g, (param0);
+\t} // callseq 0
+$L__BB0_2:
+\tret;
+}
+";
+        let mut ptx: PtxFile = source.try_into().unwrap();
+        Rename("%p1", "%p0").visit_module_mut(&mut ptx);
+        Rename("%rd2", "%rd1").visit_module_mut(&mut ptx);
+        let printed = ptx.to_string();
+        assert!(!printed.contains("%p1") && !printed.contains("%rd2"), "{printed}");
+        assert!(printed.contains("@%p0 add.s64 %rd1, %rd1, 1;"));
+        assert!(printed.contains("@!%p0 bra $L__BB0_2;"));
+        assert!(printed.contains("st.param.b64 [param0], %rd1;"));
+        assert!(printed.contains("call.uni g, (param0);"));
+        assert!(printed.contains("\t// the pointer\n\tld.param.u64 %rd1, [k_param_0];\n\t/* may be null */\n"));
+        assert!(printed.contains("// callseq 0, 0"));
+
+        let reparsed: PtxFile = printed.as_str().try_into().unwrap();
+        assert!(reparsed.equivalent(&ptx));
+        let mut unchanged = reparsed.clone();
+        Rename("%p1", "%p0").visit_module_mut(&mut unchanged);
+        assert_eq!(unchanged, reparsed);
+    }
+}
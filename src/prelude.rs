@@ -0,0 +1,81 @@
+//! The types needed to walk the output of the parser.
+//!
+//! ```
+//! use ptx_parser::prelude::*;
+//!
+//! let ptx: PtxParser = "
+//! .version 7.5
+//! .target sm_80
+//! .address_size 64
+//!
+//! .visible .entry kernel(
+//!     .param .u64 kernel_param_0
+//! )
+//! {
+//!     .reg .b64 %rd<2>;
+//!     ld.param.u64 %rd1, [kernel_param_0];
+//!     ret;
+//! }
+//! "
+//! .try_into()
+//! .unwrap();
+//!
+//! assert_eq!(ptx.preamble().target().target(), "sm_80");
+//! for (_, item) in ptx.into_iter().flatten() {
+//!     let FunctionOrGlobal::Function(function) = item else { continue };
+//!     assert_eq!(function.signature.name, "kernel");
+//!     for (_, line) in function.body.into_iter().flatten().flatten() {
+//!         match line {
+//!             BodyLine::Register(register) => assert_eq!(register.count(), Some(2)),
+//!             BodyLine::Operation(operation) => assert_eq!(operation.opcode(), "ld"),
+//!             _ => (),
+//!         }
+//!     }
+//! }
+//! ```
+
+#[cfg(feature = "std")]
+pub use crate::parser::{
+    call_graph::{Call, CallGraph, CallKind},
+    ptx_file::OwnedPtxFile,
+    symbol::{Reference, Symbol, SymbolKind, SymbolTable},
+    PtxFile,
+};
+pub use crate::parser::{
+    availability::{Requirement, SmTarget},
+    cfg::{
+        dataflow::{Analysis, Direction, Lattice, Solution},
+        dominance::DominatorTree,
+        liveness::{Liveness, RegisterPressure},
+        loops::{Loop, LoopForest, LoopId},
+        reaching::{DefUseChains, Definition, DefinitionId, ReachingDefinitions, Use, UseId},
+        ssa::{Phi, Ssa, SsaName},
+        BasicBlock, BlockId, Cfg, Jump, Registers, Terminator,
+    },
+    comment::Comment,
+    cst::{Node, NodeKind, SyntaxTree, Trivia},
+    diagnostic::{Code, Diagnostic, Severity},
+    function::{
+        body::{BodyLine, FunctionBody, FunctionCall, Goto, Operation, Predicate, Register},
+        inline_asm::InlineAsm,
+        Directive, Directives, Function, FunctionSignature, Parameter, Parameters, ReturnValue,
+    },
+    global::{Global, OpaqueDeclaration},
+    instruction::{
+        async_copy::AsyncInstruction,
+        atomic::{AtomicInstruction, FenceInstruction},
+        cluster::ClusterInstruction,
+        memory::MemoryAccess,
+        mma::TensorInstruction,
+        texture::TextureInstruction,
+        types::{MemoryOrder, ScalarType, Scope, StateSpace, VectorWidth},
+        Operand,
+    },
+    location::{File, Location},
+    preamble::{AddressSize, Preamble, Target, Version},
+    printer::{Equivalent, Indent, Print, Printer},
+    ptx_file::FunctionOrGlobal,
+    section::Section,
+    visit::{Visitor, VisitorMut},
+    PtxParser,
+};
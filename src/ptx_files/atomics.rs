@@ -0,0 +1,60 @@
+pub(crate) const _PTX: &str = ".version 7.8
+.target sm_80
+.address_size 64
+
+	// .globl	publish
+
+.visible .entry publish(
+	.param .u64 publish_param_0,
+	.param .u64 publish_param_1
+)
+{
+	.reg .pred 	%p<2>;
+	.reg .b32 	%r<6>;
+	.reg .b64 	%rd<5>;
+
+	ld.param.u64 	%rd1, [publish_param_0];
+	ld.param.u64 	%rd2, [publish_param_1];
+	cvta.to.global.u64 	%rd3, %rd1;
+	cvta.to.global.u64 	%rd4, %rd2;
+$L__BB0_1:
+	atom.acquire.sys.global.cas.b32 	%r1, [%rd3], 0, 1;
+	setp.ne.s32 	%p1, %r1, 0;
+	@%p1 bra 	$L__BB0_1;
+	ld.relaxed.sys.global.u32 	%r2, [%rd4];
+	add.s32 	%r3, %r2, 1;
+	st.relaxed.sys.global.u32 	[%rd4], %r3;
+	fence.acq_rel.sys;
+	atom.release.sys.global.exch.b32 	%r4, [%rd3], 0;
+	ret;
+
+}
+	// .globl	histogram
+.visible .entry histogram(
+	.param .u64 histogram_param_0,
+	.param .u64 histogram_param_1
+)
+{
+	.reg .pred 	%p<2>;
+	.reg .b32 	%r<8>;
+	.reg .b64 	%rd<5>;
+	.shared .align 4 .b8 bins[1024];
+
+	ld.param.u64 	%rd1, [histogram_param_0];
+	ld.param.u64 	%rd2, [histogram_param_1];
+	cvta.to.global.u64 	%rd3, %rd1;
+	cvta.to.global.u64 	%rd4, %rd2;
+	mov.u32 	%r1, %tid.x;
+	shl.b32 	%r2, %r1, 2;
+	mov.u32 	%r3, bins;
+	add.s32 	%r4, %r3, %r2;
+	atom.shared.add.u32 	%r5, [%r4], 1;
+	membar.gl;
+	ld.shared.u32 	%r6, [%r4];
+	setp.ne.s32 	%p1, %r6, 0;
+	@%p1 red.shared::cta.add.u32 	[%r4], 1;
+	red.global.add.u32 	[%rd3], %r6;
+	ret;
+
+}
+";
@@ -0,0 +1,43 @@
+pub(crate) const _PTX: &str = ".version 8.0
+.target sm_90
+.address_size 64
+
+	// .globl	reduce_cluster
+
+.visible .entry reduce_cluster(
+	.param .u64 reduce_cluster_param_0
+)
+.explicitcluster
+.reqnctapercluster 2, 1, 1
+{
+	.reg .pred 	%p<2>;
+	.reg .b32 	%r<10>;
+	.reg .f32 	%f<4>;
+	.reg .b64 	%rd<3>;
+	.shared .align 4 .f32 partial;
+
+	ld.param.u64 	%rd1, [reduce_cluster_param_0];
+	mov.u32 	%r1, %cluster_ctarank;
+	mov.u32 	%r2, %cluster_nctarank;
+	mov.u32 	%r3, partial;
+	mov.f32 	%f1, 0f3F800000;
+	st.shared::cta.f32 	[%r3], %f1;
+	barrier.cluster.arrive.release.aligned;
+	barrier.cluster.wait.acquire.aligned;
+	add.s32 	%r4, %r1, 1;
+	rem.u32 	%r5, %r4, %r2;
+	mapa.shared::cluster.u32 	%r6, %r3, %r5;
+	getctarank.shared::cluster.u32 	%r7, %r6;
+	ld.shared::cluster.f32 	%f2, [%r6];
+	add.f32 	%f3, %f1, %f2;
+	barrier.cluster.arrive.aligned;
+	barrier.cluster.wait.aligned;
+	setp.ne.s32 	%p1, %r1, 0;
+	@%p1 bra 	$L__BB0_2;
+	cvta.to.global.u64 	%rd2, %rd1;
+	st.global.f32 	[%rd2], %f3;
+$L__BB0_2:
+	ret;
+
+}
+";
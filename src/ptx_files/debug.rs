@@ -0,0 +1,93 @@
+pub(crate) const _PTX: &str = "//
+// Generated by LLVM NVPTX Back-End
+//
+
+.version 7.0
+.target sm_52, debug
+.address_size 64
+
+	// .globl	_Z6squarePfS_i
+
+.visible .entry _Z6squarePfS_i(
+	.param .u64 _Z6squarePfS_i_param_0,
+	.param .u64 _Z6squarePfS_i_param_1
+)
+{
+	.reg .b32 	%r<2>;
+	.reg .f32 	%f<3>;
+	.reg .b64 	%rd<5>;
+
+$L__func_begin0:
+	.loc	1 3 0
+
+	ld.param.u64 	%rd1, [_Z6squarePfS_i_param_0];
+	ld.param.u64 	%rd2, [_Z6squarePfS_i_param_1];
+$L__tmp0:
+	.loc	1 4 13
+	mov.u32 	%r1, %tid.x;
+	mul.wide.u32 	%rd3, %r1, 4;
+	add.s64 	%rd4, %rd1, %rd3;
+	ld.f32 	%f1, [%rd4];
+	mul.f32 	%f2, %f1, %f1;
+	add.s64 	%rd4, %rd2, %rd3;
+	st.f32 	[%rd4], %f2;
+	.loc	1 5 1
+	ret;
+$L__tmp1:
+$L__func_end0:
+
+}
+	.file	1 \"/home/user/square.cu\"
+	.section	.debug_abbrev
+	{
+.b8 1                                   // Abbreviation Code
+.b8 17                                  // DW_TAG_compile_unit
+.b8 1                                   // DW_CHILDREN_yes
+.b8 37                                  // DW_AT_producer
+.b8 8                                   // DW_FORM_string
+.b8 3                                   // DW_AT_name
+.b8 8                                   // DW_FORM_string
+.b8 17                                  // DW_AT_low_pc
+.b8 1                                   // DW_FORM_addr
+.b8 0                                   // EOM(1)
+.b8 0                                   // EOM(2)
+.b8 2                                   // Abbreviation Code
+.b8 46                                  // DW_TAG_subprogram
+.b8 0                                   // DW_CHILDREN_no
+.b8 3                                   // DW_AT_name
+.b8 14                                  // DW_FORM_strp
+.b8 110                                 // DW_AT_linkage_name
+.b8 8                                   // DW_FORM_string
+.b8 17                                  // DW_AT_low_pc
+.b8 1                                   // DW_FORM_addr
+.b8 0                                   // EOM(1)
+.b8 0                                   // EOM(2)
+.b8 0                                   // EOM(3)
+	}
+	.section	.debug_info
+	{
+.b32 60                                 // Length of Unit
+.b8 2                                   // DWARF version number
+.b8 0
+.b32 .debug_abbrev                      // Offset Into Abbrev. Section
+.b8 8                                   // Address Size (in bytes)
+.b8 1                                   // Abbrev [1] 0xb:0x35 DW_TAG_compile_unit
+.b8 110,118,99,99                       // DW_AT_producer
+.b8 0
+.b8 115,113,117,97,114,101,46,99,117    // DW_AT_name
+.b8 0
+.b64 $L__func_begin0                    // DW_AT_low_pc
+.b8 2                                   // Abbrev [2] 0x23:0x1c DW_TAG_subprogram
+.b32 $L__info_string0                   // DW_AT_name
+.b8 95,90,54,115,113,117,97,114,101,80,102,83,95,105 // DW_AT_linkage_name
+.b8 0
+.b64 $L__func_begin0                    // DW_AT_low_pc
+.b8 0                                   // End Of Children Mark
+	}
+	.section	.debug_str
+	{
+$L__info_string0:
+.b8 115,113,117,97,114,101,0            // string offset=0
+	}
+	.section	.debug_loc	{	}
+";
@@ -0,0 +1,53 @@
+pub(crate) const _PTX: &str = "//
+// Generated by NVIDIA NVVM Compiler
+//
+// Compiler Build ID: CL-32267302
+// Cuda compilation tools, release 12.0, V12.0.140
+// Based on NVVM 7.0.1
+//
+
+.version 8.0
+.target sm_80
+.address_size 64
+
+	// .globl	lane_ids
+
+.visible .entry lane_ids(
+	.param .u64 lane_ids_param_0
+)
+{
+	.reg .b32 	%r<5>;
+	.reg .b64 	%rd<5>;
+
+	ld.param.u64 	%rd1, [lane_ids_param_0];
+	cvta.to.global.u64 	%rd2, %rd1;
+	mov.u32 	%r1, %tid.x;
+	// begin inline asm
+	mov.u32 %r2, %laneid;
+	// end inline asm
+	// begin inline asm
+	mov.u32 %r3, %smid;
+	// end inline asm
+	add.s32 	%r4, %r2, %r3;
+	mul.wide.u32 	%rd3, %r1, 4;
+	add.s64 	%rd4, %rd2, %rd3;
+	st.global.u32 	[%rd4], %r4;
+	ret;
+
+}
+	// .globl	plain
+.visible .entry plain(
+	.param .u64 plain_param_0
+)
+{
+	.reg .b32 	%r<2>;
+	.reg .b64 	%rd<3>;
+
+	ld.param.u64 	%rd1, [plain_param_0];
+	cvta.to.global.u64 	%rd2, %rd1;
+	mov.u32 	%r1, %tid.x;
+	st.global.u32 	[%rd2], %r1;
+	ret;
+
+}
+";
@@ -0,0 +1,55 @@
+pub(crate) const _PTX: &str = "//
+// Generated by NVIDIA NVVM Compiler
+//
+// Compiler Build ID: CL-32267302
+// Cuda compilation tools, release 12.0, V12.0.140
+// Based on NVVM 7.0.1
+//
+
+.version 8.0
+.target sm_52
+.address_size 64
+
+	// .globl	_Z6squarePfS_i
+
+.visible .entry _Z6squarePfS_i(
+	.param .u64 _Z6squarePfS_i_param_0,
+	.param .u64 _Z6squarePfS_i_param_1,
+	.param .u32 _Z6squarePfS_i_param_2
+)
+{
+	.reg .pred 	%p<2>;
+	.reg .b32 	%r<6>;
+	.reg .f32 	%f<3>;
+	.reg .b64 	%rd<8>;
+
+
+	ld.param.u64 	%rd1, [_Z6squarePfS_i_param_0];
+	ld.param.u64 	%rd2, [_Z6squarePfS_i_param_1];
+	ld.param.u32 	%r2, [_Z6squarePfS_i_param_2];
+	.loc	1 3 13
+	mov.u32 	%r3, %ctaid.x;
+	mov.u32 	%r4, %ntid.x;
+	mov.u32 	%r5, %tid.x;
+	mad.lo.s32 	%r1, %r3, %r4, %r5;
+	.loc	1 4 5
+	setp.ge.s32 	%p1, %r1, %r2;
+	@%p1 bra 	$L__BB0_2;
+
+	.loc	1 5 9
+	cvta.to.global.u64 	%rd3, %rd2;
+	mul.wide.s32 	%rd4, %r1, 4;
+	add.s64 	%rd5, %rd3, %rd4;
+	ld.global.f32 	%f1, [%rd5];
+	mul.f32 	%f2, %f1, %f1;
+	cvta.to.global.u64 	%rd6, %rd1;
+	add.s64 	%rd7, %rd6, %rd4;
+	st.global.f32 	[%rd7], %f2;
+
+$L__BB0_2:
+	.loc	1 7 1
+	ret;
+
+}
+	.file	1 \"/home/user/square.cu\"
+";
@@ -1,8 +1,35 @@
 pub(crate) mod a;
+pub(crate) mod atomics;
 pub(crate) mod b;
 pub(crate) mod c;
+pub(crate) mod cluster;
 pub(crate) mod d;
+pub(crate) mod debug;
+pub(crate) mod inline_asm;
 pub(crate) mod kernel;
+pub(crate) mod lineinfo;
+pub(crate) mod pipeline;
+pub(crate) mod tensor;
+pub(crate) mod texture;
+
+/// Every file produced by a compiler. They all parse, resolve and validate, unlike
+/// [`_EXAMPLE_FILE`].
+#[cfg(all(test, feature = "std"))]
+pub(crate) const ALL: [&str; 13] = [
+    a::_PTX,
+    atomics::_PTX,
+    b::_PTX,
+    c::_PTX,
+    cluster::_PTX,
+    d::_PTX,
+    debug::_PTX,
+    inline_asm::_PTX,
+    kernel::_PTX,
+    lineinfo::_PTX,
+    pipeline::_PTX,
+    tensor::_PTX,
+    texture::_PTX,
+];
 
 pub(crate) const _EXAMPLE_FILE: &str = ".version 7.5
 .target sm_30
@@ -0,0 +1,41 @@
+pub(crate) const _PTX: &str = ".version 8.0
+.target sm_90
+.address_size 64
+
+	// .globl	pipeline
+
+.visible .entry pipeline(
+	.param .u64 pipeline_param_0,
+	.param .u64 pipeline_param_1
+)
+{
+	.reg .pred 	%p<3>;
+	.reg .b32 	%r<12>;
+	.reg .f32 	%f<4>;
+	.reg .b64 	%rd<8>;
+	.shared .align 16 .b8 buffer[512];
+	.shared .align 8 .b64 barrier;
+
+	ld.param.u64 	%rd1, [pipeline_param_0];
+	ld.param.u64 	%rd2, [pipeline_param_1];
+	mov.u32 	%r1, buffer;
+	mov.u32 	%r2, barrier;
+	cp.async.ca.shared.global 	[%r1], [%rd1], 16;
+	cp.async.commit_group;
+	cp.async.cg.shared.global 	[%r1+256], [%rd1+256], 16;
+	cp.async.commit_group;
+	cp.async.wait_group 	1;
+	ld.shared.f32 	%f1, [%r1];
+	ld.shared.f32 	%f2, [%r1+256];
+	cp.async.wait_all;
+	mbarrier.init.shared::cta.b64 	[%r2], 1;
+	mbarrier.arrive.expect_tx.shared::cta.b64 	%rd3, [%r2], 256;
+	cp.async.bulk.tensor.2d.shared::cluster.global.mbarrier::complete_tx::bytes 	[%r1], [%rd2, {%r3, %r4}], [%r2];
+$L__BB0_1:
+	mbarrier.try_wait.parity.shared::cta.b64 	%p1, [%r2], 0;
+	@!%p1 bra 	$L__BB0_1;
+	ld.shared.f32 	%f3, [%r1];
+	ret;
+
+}
+";
@@ -0,0 +1,34 @@
+pub(crate) const _PTX: &str = ".version 8.0
+.target sm_90a
+.address_size 64
+
+	// .globl	matmul
+
+.visible .entry matmul(
+	.param .u64 matmul_param_0,
+	.param .u64 matmul_param_1
+)
+{
+	.reg .pred 	%p<2>;
+	.reg .b32 	%r<16>;
+	.reg .f32 	%f<16>;
+	.reg .b64 	%rd<8>;
+	.shared .align 16 .b8 tile[4096];
+
+	ld.param.u64 	%rd1, [matmul_param_0];
+	ld.param.u64 	%rd2, [matmul_param_1];
+	mov.u32 	%r1, tile;
+	ldmatrix.sync.aligned.m8n8.x4.shared.b16 	{%r2, %r3, %r4, %r5}, [%r1];
+	ldmatrix.sync.aligned.m8n8.x2.trans.shared.b16 	{%r6, %r7}, [%r1+512];
+	mov.f32 	%f1, 0f00000000;
+	mma.sync.aligned.m16n8k16.row.col.f32.f16.f16.f32 	{%f2, %f3, %f4, %f5}, {%r2, %r3, %r4, %r5}, {%r6, %r7}, {%f1, %f1, %f1, %f1};
+	wgmma.fence.sync.aligned;
+	wgmma.mma_async.sync.aligned.m64n8k16.f32.f16.f16 	{%f6, %f7, %f8, %f9}, %rd1, %rd2, 1, 1, 1, 0, 0;
+	wgmma.commit_group.sync.aligned;
+	wgmma.wait_group.sync.aligned 	0;
+	cvta.to.global.u64 	%rd3, %rd2;
+	st.global.v4.f32 	[%rd3], {%f2, %f3, %f4, %f5};
+	ret;
+
+}
+";
@@ -0,0 +1,31 @@
+pub(crate) const _PTX: &str = ".version 7.0
+.target sm_52
+.address_size 64
+
+.global .texref tex_a;
+.global .samplerref smp = { addr_mode_0 = clamp_to_edge, filter_mode = linear };
+.global .surfref surf_a;
+
+.visible .entry blur(
+	.param .u64 blur_param_0,
+	.param .texref blur_param_1
+)
+{
+	.reg .b32 	%r<8>;
+	.reg .f32 	%f<8>;
+	.reg .b64 	%rd<3>;
+
+	ld.param.u64 	%rd1, [blur_param_0];
+	mov.u32 	%r1, %tid.x;
+	cvt.rn.f32.u32 	%f1, %r1;
+	mov.f32 	%f2, 0f3F000000;
+	tex.2d.v4.f32.f32 	{%f3, %f4, %f5, %f6}, [tex_a, smp, {%f1, %f2}];
+	txq.width.b32 	%r2, [tex_a];
+	suld.b.1d.b32.trap 	{%r3}, [surf_a, {%r1}];
+	sust.b.1d.b32.trap 	[surf_a, {%r1}], {%r2};
+	cvta.to.global.u64 	%rd2, %rd1;
+	st.global.f32 	[%rd2], %f3;
+	ret;
+
+}
+";